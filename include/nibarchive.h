@@ -0,0 +1,167 @@
+#ifndef NIBARCHIVE_H
+#define NIBARCHIVE_H
+
+/* This file is generated by cbindgen. Don't edit it manually. */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+// Returned by functions that return counts if they fail.
+#define NIB_ERROR SIZE_MAX
+
+// Type of a [NibValue].
+typedef enum NibValueType {
+  NIB_VALUE_TYPE_INT8,
+  NIB_VALUE_TYPE_INT16,
+  NIB_VALUE_TYPE_INT32,
+  NIB_VALUE_TYPE_INT64,
+  NIB_VALUE_TYPE_BOOL,
+  NIB_VALUE_TYPE_FLOAT,
+  NIB_VALUE_TYPE_DOUBLE,
+  NIB_VALUE_TYPE_DATA,
+  NIB_VALUE_TYPE_NIL,
+  NIB_VALUE_TYPE_OBJECT_REF,
+  NIB_VALUE_TYPE_UNKNOWN,
+} NibValueType;
+
+// An opaque handle of a decoded archive.
+typedef struct NibArchive NibArchive;
+
+// An object of an archive.
+typedef struct NibObject {
+  int32_t class_name_index;
+  // Index of the first value of the object.
+  int32_t values_index;
+  int32_t value_count;
+} NibObject;
+
+// A value of an archive.
+//
+// Integers, booleans and object references are stored in `integer`, floats and doubles
+// in `real`. Data and raw payloads of unknown values are pointed to by `data`, which
+// is `NULL` if they're empty. The type byte of an unknown value is stored in `integer`.
+typedef struct NibValue {
+  int32_t key_index;
+  enum NibValueType value_type;
+  int64_t integer;
+  double real;
+  const uint8_t *data;
+  size_t data_len;
+} NibValue;
+
+// A buffer allocated by the library, which has to be freed with [nib_buffer_free()].
+typedef struct NibBuffer {
+  uint8_t *data;
+  size_t len;
+} NibBuffer;
+
+// Returns the message of the last error that occurred in the current thread,
+// or `NULL` if there was none.
+//
+// The message stays valid until the next failing call in the same thread.
+const char *nib_last_error(void);
+
+// Decodes an archive from a file at a given null-terminated UTF-8 path.
+//
+// Returns `NULL` on failure. The archive has to be freed with [nib_archive_free()].
+//
+// # Safety
+//
+// `path` has to be a valid null-terminated string.
+struct NibArchive *nib_archive_open(const char *path);
+
+// Decodes an archive from `len` bytes of `data`.
+//
+// Returns `NULL` on failure. The archive has to be freed with [nib_archive_free()].
+//
+// # Safety
+//
+// `data` has to point to at least `len` readable bytes.
+struct NibArchive *nib_archive_from_bytes(const uint8_t *data, size_t len);
+
+// Frees an archive. Does nothing if `archive` is `NULL`.
+//
+// # Safety
+//
+// `archive` has to be returned by this library and not freed yet.
+void nib_archive_free(struct NibArchive *archive);
+
+// Returns the count of objects of an archive, or [NIB_ERROR] if `archive` is `NULL`.
+//
+// # Safety
+//
+// `archive` has to be a valid archive or `NULL`.
+size_t nib_archive_object_count(const struct NibArchive *archive);
+
+// Returns the count of keys of an archive, or [NIB_ERROR] if `archive` is `NULL`.
+//
+// # Safety
+//
+// `archive` has to be a valid archive or `NULL`.
+size_t nib_archive_key_count(const struct NibArchive *archive);
+
+// Returns the count of values of an archive, or [NIB_ERROR] if `archive` is `NULL`.
+//
+// # Safety
+//
+// `archive` has to be a valid archive or `NULL`.
+size_t nib_archive_value_count(const struct NibArchive *archive);
+
+// Returns the count of class names of an archive, or [NIB_ERROR] if `archive` is `NULL`.
+//
+// # Safety
+//
+// `archive` has to be a valid archive or `NULL`.
+size_t nib_archive_class_name_count(const struct NibArchive *archive);
+
+// Writes an object at `index` into `out`. Returns `false` if the index is out of bounds
+// or a pointer is `NULL`.
+//
+// # Safety
+//
+// `archive` has to be a valid archive and `out` has to be writable, or they have to be `NULL`.
+bool nib_archive_object(const struct NibArchive *archive, size_t index, struct NibObject *out);
+
+// Writes a value at `index` into `out`. Returns `false` if the index is out of bounds
+// or a pointer is `NULL`.
+//
+// # Safety
+//
+// `archive` has to be a valid archive and `out` has to be writable, or they have to be `NULL`.
+bool nib_archive_value(const struct NibArchive *archive, size_t index, struct NibValue *out);
+
+// Returns a key at `index` and writes its length into `len`.
+// Returns `NULL` if the index is out of bounds or a pointer is `NULL`.
+//
+// # Safety
+//
+// `archive` has to be a valid archive and `len` has to be writable, or they have to be `NULL`.
+const uint8_t *nib_archive_key(const struct NibArchive *archive, size_t index, size_t *len);
+
+// Returns a class name at `index` and writes its length into `len`.
+// Returns `NULL` if the index is out of bounds or a pointer is `NULL`.
+//
+// # Safety
+//
+// `archive` has to be a valid archive and `len` has to be writable, or they have to be `NULL`.
+const uint8_t *nib_archive_class_name(const struct NibArchive *archive, size_t index, size_t *len);
+
+// Encodes an archive. The buffer has to be freed with [nib_buffer_free()].
+// Returns a buffer with `NULL` data if `archive` is `NULL`.
+//
+// # Safety
+//
+// `archive` has to be a valid archive or `NULL`.
+struct NibBuffer nib_archive_encode(const struct NibArchive *archive);
+
+// Frees a buffer returned by [nib_archive_encode()].
+//
+// # Safety
+//
+// `buffer` has to be returned by this library and not freed yet.
+void nib_buffer_free(struct NibBuffer buffer);
+
+#endif  /* NIBARCHIVE_H */
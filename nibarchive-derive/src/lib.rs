@@ -0,0 +1,204 @@
+//! Derive macros for the `FromNib` and `ToNib` traits of
+//! [nibarchive](https://crates.io/crates/nibarchive). Use them through the `derive`
+//! feature of nibarchive instead of depending on this crate directly.
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{
+    parse_macro_input, Attribute, Data, DeriveInput, Fields, GenericArgument, Ident, LitStr,
+    PathArguments, Type,
+};
+
+/// How a field is stored in an archive.
+enum Kind {
+    /// A single value.
+    Value,
+    /// A reference to an object.
+    Object,
+    /// A reference to an array of objects.
+    Array,
+    /// Not stored at all.
+    Skip,
+}
+
+struct Field {
+    ident: Ident,
+    key: LitStr,
+    kind: Kind,
+    default: bool,
+    optional: bool,
+}
+
+impl Field {
+    fn parse(field: &syn::Field) -> syn::Result<Self> {
+        let ident = field.ident.clone().expect("only named fields are parsed");
+        let mut key = LitStr::new(&ident.to_string(), ident.span());
+        let mut kind = Kind::Value;
+        let mut default = false;
+        for attr in nib_attrs(&field.attrs) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("key") {
+                    key = meta.value()?.parse()?;
+                } else if meta.path.is_ident("object") {
+                    kind = Kind::Object;
+                } else if meta.path.is_ident("array") {
+                    kind = Kind::Array;
+                } else if meta.path.is_ident("skip") {
+                    kind = Kind::Skip;
+                } else if meta.path.is_ident("default") {
+                    default = true;
+                } else {
+                    return Err(meta.error("unsupported nib attribute"));
+                }
+                Ok(())
+            })?;
+        }
+        Ok(Self {
+            ident,
+            key,
+            kind,
+            default,
+            optional: is_option(&field.ty),
+        })
+    }
+}
+
+fn nib_attrs(attrs: &[Attribute]) -> impl Iterator<Item = &Attribute> {
+    attrs.iter().filter(|attr| attr.path().is_ident("nib"))
+}
+
+/// Returns `true` if a type is `Option<T>`.
+fn is_option(ty: &Type) -> bool {
+    let Type::Path(path) = ty else {
+        return false;
+    };
+    path.path.segments.last().is_some_and(|segment| {
+        segment.ident == "Option"
+            && matches!(&segment.arguments, PathArguments::AngleBracketed(args)
+                if matches!(args.args.first(), Some(GenericArgument::Type(_))))
+    })
+}
+
+fn named_fields(input: &DeriveInput) -> syn::Result<Vec<Field>> {
+    match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => fields.named.iter().map(Field::parse).collect(),
+            _ => Err(syn::Error::new_spanned(
+                &input.ident,
+                "only structs with named fields are supported",
+            )),
+        },
+        _ => Err(syn::Error::new_spanned(
+            &input.ident,
+            "only structs with named fields are supported",
+        )),
+    }
+}
+
+/// Derives `FromNib` for a struct with named fields.
+///
+/// Every field is read from a value whose key is the name of the field. Attributes:
+///
+/// - `#[nib(key = "UIFrame")]` reads a field from a value with a given key.
+/// - `#[nib(default)]` falls back to the default value if the field is missing.
+/// - `#[nib(object)]` decodes a referenced object implementing `FromNib`.
+/// - `#[nib(array)]` decodes elements of a referenced array into a `Vec`.
+/// - `#[nib(skip)]` doesn't read a field and sets it to the default value.
+///
+/// Fields of type `Option<T>` are `None` if they're missing.
+#[proc_macro_derive(FromNib, attributes(nib))]
+pub fn derive_from_nib(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    from_nib(&input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+fn from_nib(input: &DeriveInput) -> syn::Result<TokenStream2> {
+    let fields = named_fields(input)?;
+    let reads = fields.iter().map(|field| {
+        let Field { ident, key, .. } = field;
+        let read = match (&field.kind, field.optional, field.default) {
+            (Kind::Skip, _, _) => quote!(::core::default::Default::default()),
+            (Kind::Array, _, _) => quote!(ctx.array(obj, #key)?),
+            (Kind::Object, true, _) => quote!(ctx.object_opt(obj, #key)?),
+            (Kind::Object, false, true) => {
+                quote!(ctx.object_opt(obj, #key)?.unwrap_or_default())
+            }
+            (Kind::Object, false, false) => quote!(ctx.object(obj, #key)?),
+            (Kind::Value, true, _) => quote!(ctx.field_opt(obj, #key)?),
+            (Kind::Value, false, true) => quote!(ctx.field_or_default(obj, #key)?),
+            (Kind::Value, false, false) => quote!(ctx.field(obj, #key)?),
+        };
+        quote!(#ident: #read)
+    });
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics ::nibarchive::FromNib for #name #ty_generics #where_clause {
+            fn from_nib(
+                obj: &::nibarchive::Object,
+                ctx: &::nibarchive::NibContext,
+            ) -> ::core::result::Result<Self, ::nibarchive::Error> {
+                ::core::result::Result::Ok(Self { #(#reads,)* })
+            }
+        }
+    })
+}
+
+/// Derives `ToNib` for a struct with named fields.
+///
+/// The class name of the object is the name of the struct unless it's set with
+/// `#[nib(class = "UIView")]`. Field attributes are the same as for `FromNib`,
+/// `#[nib(default)]` has no effect. Fields of type `Option<T>` are written only
+/// if they're `Some`.
+#[proc_macro_derive(ToNib, attributes(nib))]
+pub fn derive_to_nib(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    to_nib(&input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+fn to_nib(input: &DeriveInput) -> syn::Result<TokenStream2> {
+    let name = &input.ident;
+    let mut class_name = LitStr::new(&name.to_string(), name.span());
+    for attr in nib_attrs(&input.attrs) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("class") {
+                class_name = meta.value()?.parse()?;
+                Ok(())
+            } else {
+                Err(meta.error("unsupported nib attribute"))
+            }
+        })?;
+    }
+    let fields = named_fields(input)?;
+    let writes = fields.iter().map(|field| {
+        let Field { ident, key, .. } = field;
+        match (&field.kind, field.optional) {
+            (Kind::Skip, _) => quote!(),
+            (Kind::Array, _) => quote!(enc.array(#key, &self.#ident);),
+            (Kind::Object, true) => quote! {
+                if let ::core::option::Option::Some(value) = &self.#ident {
+                    enc.object(#key, value);
+                }
+            },
+            (Kind::Object, false) => quote!(enc.object(#key, &self.#ident);),
+            (Kind::Value, _) => quote!(enc.field(#key, &self.#ident);),
+        }
+    });
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics ::nibarchive::ToNib for #name #ty_generics #where_clause {
+            fn class_name(&self) -> &str {
+                #class_name
+            }
+
+            fn to_nib(&self, enc: &mut ::nibarchive::NibEncoder) {
+                #(#writes)*
+            }
+        }
+    })
+}
@@ -0,0 +1,78 @@
+use nibarchive::{FromNib, NIBArchive, ToNib};
+
+#[derive(Debug, Default, PartialEq, FromNib, ToNib)]
+#[nib(class = "UILabel")]
+struct Label {
+    #[nib(key = "UIText")]
+    text: String,
+    #[nib(key = "UINumberOfLines", default)]
+    lines: i32,
+}
+
+#[derive(Debug, Default, PartialEq, FromNib, ToNib)]
+#[nib(class = "UIView")]
+struct View {
+    #[nib(key = "UITag")]
+    tag: i64,
+    #[nib(key = "UIHidden")]
+    hidden: Option<bool>,
+    #[nib(key = "UIAlpha")]
+    alpha: f64,
+    #[nib(key = "UILabel", object)]
+    label: Option<Label>,
+    #[nib(key = "UISubviews", array)]
+    subviews: Vec<Label>,
+    #[nib(skip)]
+    cached: u32,
+}
+
+#[test]
+fn roundtrip() {
+    let view = View {
+        tag: 42,
+        hidden: Some(true),
+        alpha: 0.5,
+        label: Some(Label {
+            text: "Title".into(),
+            lines: 2,
+        }),
+        subviews: vec![
+            Label {
+                text: "First".into(),
+                lines: 1,
+            },
+            Label {
+                text: "Second".into(),
+                lines: 0,
+            },
+        ],
+        cached: 7,
+    };
+    let archive = NIBArchive::from_bytes(NIBArchive::from_object(&view).to_bytes()).unwrap();
+    assert_eq!(
+        archive
+            .class_name(archive.objects()[0].class_name_id())
+            .unwrap()
+            .name(),
+        "UIView"
+    );
+
+    let decoded: View = archive.decode_object(0).unwrap();
+    assert_eq!(decoded, View { cached: 0, ..view });
+}
+
+#[test]
+fn missing_fields() {
+    let archive = NIBArchive::from_object(&View::default());
+    let decoded: View = archive.decode_object(0).unwrap();
+    assert_eq!(decoded, View::default());
+
+    let label = NIBArchive::from_object(&Label::default());
+    assert!(label.decode_object::<View>(0).is_err());
+}
+
+#[test]
+fn ui() {
+    let t = trybuild::TestCases::new();
+    t.compile_fail("tests/ui/*.rs");
+}
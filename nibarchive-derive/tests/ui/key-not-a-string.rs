@@ -0,0 +1,9 @@
+use nibarchive::FromNib;
+
+#[derive(FromNib)]
+struct View {
+    #[nib(key = 42)]
+    tag: i64,
+}
+
+fn main() {}
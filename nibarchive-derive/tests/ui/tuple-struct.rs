@@ -0,0 +1,6 @@
+use nibarchive::ToNib;
+
+#[derive(ToNib)]
+struct View(i64);
+
+fn main() {}
@@ -0,0 +1,9 @@
+use nibarchive::FromNib;
+
+#[derive(FromNib)]
+struct View {
+    #[nib(rename = "UITag")]
+    tag: i64,
+}
+
+fn main() {}
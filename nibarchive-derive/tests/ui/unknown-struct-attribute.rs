@@ -0,0 +1,9 @@
+use nibarchive::ToNib;
+
+#[derive(ToNib)]
+#[nib(class_name = "UIView")]
+struct View {
+    tag: i64,
+}
+
+fn main() {}
@@ -0,0 +1,111 @@
+use crate::{
+    consts::{classes, keys},
+    NIBArchive, ValueVariant,
+};
+use std::collections::HashMap;
+
+/// Classes of views a user interacts with. Such views need an accessibility label.
+const INTERACTIVE_CLASSES: &[&str] = &[classes::UI_CONTROL, classes::NS_CONTROL];
+
+/// Accessibility related values of a single view.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct AccessibilityInfo {
+    /// Index of the view object.
+    pub index: usize,
+    pub class_name: String,
+    pub identifier: Option<String>,
+    pub label: Option<String>,
+    pub hint: Option<String>,
+    /// Raw `UIAccessibilityTraits` bit mask.
+    pub traits: Option<u64>,
+    pub is_accessibility_element: Option<bool>,
+    /// Whether the view is a control, e.g. a button or a text field.
+    pub is_interactive: bool,
+}
+
+impl AccessibilityInfo {
+    /// Returns `true` if the view is interactive, exposed to accessibility
+    /// and doesn't have a label.
+    pub fn is_missing_label(&self) -> bool {
+        self.is_interactive && self.is_accessibility_element != Some(false) && self.label.is_none()
+    }
+}
+
+impl NIBArchive {
+    /// Returns accessibility related values of all views of the archive.
+    ///
+    /// Values are collected from both the view objects themselves and the attributes
+    /// Interface Builder sets at load time (see [NIBArchive::runtime_attributes()]).
+    pub fn accessibility(&self) -> Vec<AccessibilityInfo> {
+        let mut infos: HashMap<usize, AccessibilityInfo> = HashMap::new();
+        for index in (0..self.objects.len()).filter(|i| self.is_view(*i)) {
+            let class_name = self
+                .class_names
+                .get(self.objects[index].class_name_id().as_usize())
+                .map_or_else(String::new, |c| c.name().to_string());
+            let mut info = AccessibilityInfo {
+                index,
+                class_name,
+                is_interactive: INTERACTIVE_CLASSES
+                    .iter()
+                    .any(|c| self.is_kind_of(index, c)),
+                ..Default::default()
+            };
+            for (key, key_path) in [
+                (keys::UI_ACCESSIBILITY_IDENTIFIER, "accessibilityIdentifier"),
+                (keys::UI_ACCESSIBILITY_LABEL, "accessibilityLabel"),
+                (keys::UI_ACCESSIBILITY_HINT, "accessibilityHint"),
+                (keys::UI_ACCESSIBILITY_TRAITS, "accessibilityTraits"),
+                (keys::UI_IS_ACCESSIBILITY_ELEMENT, "isAccessibilityElement"),
+            ] {
+                if let Some(value) = self.object_value(index, key) {
+                    self.set_accessibility_value(&mut info, key_path, value.value());
+                }
+            }
+            infos.insert(index, info);
+        }
+        for attr in self.runtime_attributes() {
+            if let Some(info) = infos.get_mut(&attr.object) {
+                self.set_accessibility_value(info, &attr.key_path, &attr.value);
+            }
+        }
+        let mut result: Vec<AccessibilityInfo> = infos.into_values().collect();
+        result.sort_by_key(|info| info.index);
+        result
+    }
+
+    fn set_accessibility_value(
+        &self,
+        info: &mut AccessibilityInfo,
+        key_path: &str,
+        value: &ValueVariant,
+    ) {
+        match key_path {
+            "accessibilityIdentifier" => info.identifier = self.resolve_string(value),
+            "accessibilityLabel" => info.label = self.resolve_string(value),
+            "accessibilityHint" => info.hint = self.resolve_string(value),
+            "accessibilityTraits" => {
+                info.traits = self.resolve_number(value).map(|v| v as u64);
+            }
+            "isAccessibilityElement" => {
+                info.is_accessibility_element = match value {
+                    ValueVariant::Bool(v) => Some(*v),
+                    value => self.resolve_number(value).map(|v| v != 0),
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Returns an integer stored either inline or in an `NSNumber` object.
+    pub(crate) fn resolve_number(&self, value: &ValueVariant) -> Option<i64> {
+        match value {
+            ValueVariant::ObjectRef(r) => self.objects.get(*r as usize).and_then(|obj| {
+                self.object_values(obj)
+                    .iter()
+                    .find_map(|v| v.value().as_i64())
+            }),
+            value => value.as_i64(),
+        }
+    }
+}
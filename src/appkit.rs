@@ -0,0 +1,167 @@
+//! Typed models of common AppKit classes.
+//!
+//! It's the macOS counterpart of the [uikit](crate::uikit) module, if it's enabled.
+//! The models implement [FromNib], so they can be decoded with
+//! [NIBArchive::decode_object()](crate::NIBArchive::decode_object()). Subclasses embed
+//! the model of their superclass, e.g. [NSButton] has an [NSView]. Titles and texts
+//! of controls are read from their cells.
+//!
+//! ```no_run
+//! use nibarchive::{appkit::NSWindowTemplate, NIBArchive};
+//!
+//! let archive = NIBArchive::from_file("MainMenu.nib")?;
+//! for (index, _) in archive.find_by_class("NSWindowTemplate") {
+//!     let window: NSWindowTemplate = archive.decode_object(index)?;
+//!     println!("{:?} at {}", window.title, window.frame);
+//! }
+//! # Ok::<(), nibarchive::Error>(())
+//! ```
+
+use crate::{consts::keys, Error, FromNib, NibContext, Object, ObjectIndex, Rect, Size};
+
+/// A flag of `NSvFlags` that is set for hidden views.
+const HIDDEN_FLAG: i64 = 0x8000_0000;
+/// Bits of `NSvFlags` that store the autoresizing mask.
+const AUTORESIZING_MASK: i64 = 0x3F;
+
+/// An `NSView`.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct NSView {
+    pub class_name: String,
+    /// Frame in the coordinate system of the superview. Only its size is known
+    /// if the archive stores `NSFrameSize` instead of `NSFrame`.
+    pub frame: Rect,
+    pub tag: i64,
+    pub hidden: bool,
+    pub autoresizing_mask: i64,
+    pub subviews: Vec<NSView>,
+}
+
+impl FromNib for NSView {
+    fn from_nib(obj: &Object, ctx: &NibContext) -> Result<Self, Error> {
+        let frame = match ctx.field_opt(obj, keys::NS_FRAME)? {
+            Some(frame) => frame,
+            None => Rect {
+                size: ctx.field_or_default(obj, keys::NS_FRAME_SIZE)?,
+                ..Default::default()
+            },
+        };
+        let flags: i64 = ctx.field_or_default(obj, keys::NS_V_FLAGS)?;
+        Ok(Self {
+            class_name: ctx.class_name(obj).to_string(),
+            frame,
+            tag: ctx.field_or_default(obj, keys::NS_TAG)?,
+            hidden: flags & HIDDEN_FLAG != 0,
+            autoresizing_mask: flags & AUTORESIZING_MASK,
+            subviews: ctx.array(obj, keys::NS_SUBVIEWS)?,
+        })
+    }
+}
+
+/// An `NSButton`.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct NSButton {
+    pub view: NSView,
+    pub enabled: bool,
+    pub title: Option<String>,
+    pub key_equivalent: Option<String>,
+}
+
+impl FromNib for NSButton {
+    fn from_nib(obj: &Object, ctx: &NibContext) -> Result<Self, Error> {
+        let cell = cell(obj, ctx)?;
+        Ok(Self {
+            view: NSView::from_nib(obj, ctx)?,
+            enabled: ctx.field_opt(obj, keys::NS_ENABLED)?.unwrap_or(true),
+            title: cell_string(obj, cell, ctx, keys::NS_CONTENTS)?,
+            key_equivalent: cell_string(obj, cell, ctx, keys::NS_KEY_EQUIVALENT)?,
+        })
+    }
+}
+
+/// An `NSTextField`.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct NSTextField {
+    pub view: NSView,
+    pub enabled: bool,
+    pub text: Option<String>,
+    pub placeholder: Option<String>,
+}
+
+impl FromNib for NSTextField {
+    fn from_nib(obj: &Object, ctx: &NibContext) -> Result<Self, Error> {
+        let cell = cell(obj, ctx)?;
+        Ok(Self {
+            view: NSView::from_nib(obj, ctx)?,
+            enabled: ctx.field_opt(obj, keys::NS_ENABLED)?.unwrap_or(true),
+            text: cell_string(obj, cell, ctx, keys::NS_CONTENTS)?,
+            placeholder: cell_string(obj, cell, ctx, keys::NS_PLACEHOLDER_STRING)?,
+        })
+    }
+}
+
+/// An `NSWindowTemplate`, which describes a window created when a nib is loaded.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct NSWindowTemplate {
+    pub title: Option<String>,
+    /// Class of the window, e.g. `NSWindow` or `NSPanel`.
+    pub window_class: Option<String>,
+    pub frame: Rect,
+    pub style_mask: i64,
+    pub min_size: Option<Size>,
+    pub max_size: Option<Size>,
+    pub autosave_name: Option<String>,
+    pub content_view: Option<NSView>,
+}
+
+impl FromNib for NSWindowTemplate {
+    fn from_nib(obj: &Object, ctx: &NibContext) -> Result<Self, Error> {
+        Ok(Self {
+            title: ctx.field_opt(obj, keys::NS_WINDOW_TITLE)?,
+            window_class: ctx.field_opt(obj, keys::NS_WINDOW_CLASS)?,
+            frame: ctx.field_or_default(obj, keys::NS_WINDOW_RECT)?,
+            style_mask: ctx.field_or_default(obj, keys::NS_WINDOW_STYLE_MASK)?,
+            min_size: ctx.field_opt(obj, keys::NS_MIN_SIZE)?,
+            max_size: ctx.field_opt(obj, keys::NS_MAX_SIZE)?,
+            autosave_name: ctx.field_opt(obj, keys::NS_FRAME_AUTOSAVE_NAME)?,
+            content_view: ctx.object_opt(obj, keys::NS_WINDOW_VIEW)?,
+        })
+    }
+}
+
+/// An `NSViewController`.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct NSViewController {
+    pub class_name: String,
+    pub title: Option<String>,
+    pub nib_name: Option<String>,
+    pub view: Option<NSView>,
+}
+
+impl FromNib for NSViewController {
+    fn from_nib(obj: &Object, ctx: &NibContext) -> Result<Self, Error> {
+        Ok(Self {
+            class_name: ctx.class_name(obj).to_string(),
+            title: ctx.field_opt(obj, keys::NS_TITLE)?,
+            nib_name: ctx.field_opt(obj, keys::NS_NIB_NAME)?,
+            view: ctx.object_opt(obj, keys::NS_VIEW)?,
+        })
+    }
+}
+
+/// Returns the cell of a control, if there's one.
+fn cell<'a>(obj: &Object, ctx: &NibContext<'a>) -> Result<Option<&'a Object>, Error> {
+    Ok(ctx
+        .field_opt::<ObjectIndex>(obj, keys::NS_CELL)?
+        .and_then(|index| ctx.archive().objects().get(index.as_usize())))
+}
+
+/// Reads a string from the cell of a control, or from the control itself if it has no cell.
+fn cell_string(
+    obj: &Object,
+    cell: Option<&Object>,
+    ctx: &NibContext,
+    key: &str,
+) -> Result<Option<String>, Error> {
+    ctx.field_opt(cell.unwrap_or(obj), key)
+}
@@ -0,0 +1,48 @@
+use crate::{DecodeOptions, DecodeWarning, Error, LimitKind, NIBArchive};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+
+impl NIBArchive {
+    /// Reads and decodes a NIB Archive from a given async reader.
+    ///
+    /// The whole archive is read into memory first, decoding itself doesn't block.
+    pub async fn from_async_reader<T: AsyncRead + Unpin>(reader: &mut T) -> Result<Self, Error> {
+        let (archive, _) =
+            Self::from_async_reader_with_options(reader, &DecodeOptions::default()).await?;
+        Ok(archive)
+    }
+
+    /// Same as [NIBArchive::from_async_reader()], but uses given options.
+    ///
+    /// Reading stops with an error once the stream exceeds
+    /// [the total size limit](crate::Limits::max_total_size()).
+    pub async fn from_async_reader_with_options<T: AsyncRead + Unpin>(
+        reader: &mut T,
+        options: &DecodeOptions,
+    ) -> Result<(Self, Vec<DecodeWarning>), Error> {
+        let max = options.limits().max_total_size();
+        let mut bytes = Vec::new();
+        reader
+            .take(max.saturating_add(1))
+            .read_to_end(&mut bytes)
+            .await?;
+        if bytes.len() as u64 > max {
+            return Err(Error::LimitExceeded {
+                limit: LimitKind::TotalSize,
+                value: bytes.len() as u64,
+                max,
+                offset: max,
+            });
+        }
+        Self::from_slice_with_options(&bytes, options)
+    }
+
+    /// Encodes the given archive using an async writer.
+    pub async fn to_async_writer<T: AsyncWrite + Unpin>(
+        &self,
+        writer: &mut T,
+    ) -> Result<(), Error> {
+        writer.write_all(&self.to_bytes()).await?;
+        writer.flush().await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,144 @@
+//! Decoding of many archives at once, e.g. all nibs of an app bundle.
+
+use crate::{DecodeOptions, DecodeWarning, Error, NIBArchive};
+use std::{
+    fs::{self, File},
+    io::BufReader,
+    path::{Path, PathBuf},
+};
+
+/// A file decoded by [decode_dir()].
+#[derive(Debug)]
+pub struct DecodedFile {
+    pub path: PathBuf,
+    pub result: Result<(NIBArchive, Vec<DecodeWarning>), Error>,
+}
+
+/// Walks a directory tree and decodes every `.nib` file in it, including the ones
+/// inside of `.nib` bundles and compiled storyboards (`.storyboardc`).
+///
+/// Files are decoded in parallel if the `rayon` feature is enabled. Results are sorted
+/// by path. Returns an error only if the directory tree can't be read.
+pub fn decode_dir<P: AsRef<Path>>(
+    path: P,
+    options: &DecodeOptions,
+) -> Result<Vec<DecodedFile>, Error> {
+    let files = nib_files(path.as_ref())?;
+    #[cfg(feature = "rayon")]
+    let decoded = {
+        use rayon::prelude::*;
+        files
+            .into_par_iter()
+            .map(|path| decode_file(path, options))
+            .collect()
+    };
+    #[cfg(not(feature = "rayon"))]
+    let decoded = files
+        .into_iter()
+        .map(|path| decode_file(path, options))
+        .collect();
+    Ok(decoded)
+}
+
+/// Same as [decode_dir()], but passes every decoded file to `callback` as soon as
+/// it's decoded instead of collecting them. The order of files isn't specified.
+pub fn decode_dir_each<P, F>(path: P, options: &DecodeOptions, callback: F) -> Result<(), Error>
+where
+    P: AsRef<Path>,
+    F: Fn(DecodedFile) + Send + Sync,
+{
+    let files = nib_files(path.as_ref())?;
+    #[cfg(feature = "rayon")]
+    {
+        use rayon::prelude::*;
+        files
+            .into_par_iter()
+            .for_each(|path| callback(decode_file(path, options)));
+    }
+    #[cfg(not(feature = "rayon"))]
+    for path in files {
+        callback(decode_file(path, options));
+    }
+    Ok(())
+}
+
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(skip(options), fields(path = %path.display()))
+)]
+fn decode_file(path: PathBuf, options: &DecodeOptions) -> DecodedFile {
+    let result = File::open(&path)
+        .map_err(Error::from)
+        .and_then(|file| NIBArchive::from_reader_with_options(&mut BufReader::new(file), options));
+    DecodedFile { path, result }
+}
+
+/// Returns paths of all `.nib` files of a directory tree sorted.
+fn nib_files(dir: &Path) -> Result<Vec<PathBuf>, Error> {
+    let mut files = Vec::new();
+    let mut dirs = vec![dir.to_path_buf()];
+    while let Some(dir) = dirs.pop() {
+        for entry in fs::read_dir(dir)? {
+            let entry = entry?;
+            let path = entry.path();
+            if entry.file_type()?.is_dir() {
+                dirs.push(path);
+            } else if path.extension().is_some_and(|ext| ext == "nib") {
+                files.push(path);
+            }
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ClassName, Object};
+    use std::sync::Mutex;
+
+    #[test]
+    fn nested_nibs() {
+        let root = std::env::temp_dir().join(format!("nibarchive-batch-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        let bundle = root.join("Main.storyboardc").join("View.nib");
+        fs::create_dir_all(&bundle).unwrap();
+
+        let archive = NIBArchive::new(
+            vec![Object::new(0, 0, 0)],
+            Vec::new(),
+            Vec::new(),
+            vec![ClassName::with_fallbacks("NSObject".into(), Vec::new())],
+        )
+        .unwrap();
+        fs::write(root.join("Top.nib"), archive.to_bytes()).unwrap();
+        fs::write(bundle.join("runtime.nib"), archive.to_bytes()).unwrap();
+        fs::write(bundle.join("objects-13.0+.nib"), b"broken").unwrap();
+        fs::write(root.join("Main.storyboardc").join("Info.plist"), b"").unwrap();
+
+        let options = DecodeOptions::default();
+        let decoded = decode_dir(&root, &options).unwrap();
+        let paths: Vec<_> = decoded
+            .iter()
+            .map(|f| f.path.strip_prefix(&root).unwrap().to_path_buf())
+            .collect();
+        assert_eq!(
+            paths,
+            [
+                Path::new("Main.storyboardc/View.nib/objects-13.0+.nib"),
+                Path::new("Main.storyboardc/View.nib/runtime.nib"),
+                Path::new("Top.nib"),
+            ]
+        );
+        assert!(decoded[0].result.is_err());
+        assert_eq!(decoded[1].result.as_ref().unwrap().0, archive);
+        assert_eq!(decoded[2].result.as_ref().unwrap().0, archive);
+
+        let count = Mutex::new(0);
+        decode_dir_each(&root, &options, |_| *count.lock().unwrap() += 1).unwrap();
+        assert_eq!(count.into_inner().unwrap(), 3);
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -0,0 +1,371 @@
+use crate::{
+    decoder::{validate_utf8, Decoder},
+    header::Header,
+    io::Cursor,
+    value::*,
+    ClassName, ClassNameIndex, DecodeOptions, DecodeWarning, Error, KeyIndex, LimitKind,
+    NIBArchive, Object, Section, Value, ValueVariant,
+};
+use alloc::{string::ToString, vec::Vec};
+
+type SliceDecoder<'d, 'a> = Decoder<'d, Cursor<&'a [u8]>>;
+
+/// A NIB Archive decoded from a byte slice without copying its contents.
+///
+/// Keys, class names and data values borrow from the slice, so only the arrays of
+/// elements are allocated. Use [NIBArchiveRef::to_owned()] to get a [NIBArchive]
+/// that can be modified.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NIBArchiveRef<'a> {
+    objects: Vec<Object>,
+    keys: Vec<&'a str>,
+    values: Vec<ValueRef<'a>>,
+    class_names: Vec<ClassNameRef<'a>>,
+    format_version: u32,
+    coder_version: u32,
+}
+
+/// A decoded archive along with its header and the position right after its class names.
+pub(crate) type Decoded<'a> = (NIBArchiveRef<'a>, Header, u64, Vec<DecodeWarning>);
+
+impl<'a> NIBArchiveRef<'a> {
+    /// Decodes a NIB Archive from a given slice of bytes, e.g. a memory mapped file.
+    pub fn from_bytes(bytes: &'a [u8]) -> Result<Self, Error> {
+        let (archive, _) = Self::from_bytes_with_options(bytes, &DecodeOptions::default())?;
+        Ok(archive)
+    }
+
+    /// Same as [NIBArchiveRef::from_bytes()], but uses given options.
+    ///
+    /// [Preserved encoding](DecodeOptions::set_preserve_encoding()) is ignored.
+    pub fn from_bytes_with_options(
+        bytes: &'a [u8],
+        options: &DecodeOptions,
+    ) -> Result<(Self, Vec<DecodeWarning>), Error> {
+        let (archive, _, _, warnings) = Self::decode(bytes, options)?;
+        Ok((archive, warnings))
+    }
+
+    /// Decodes an archive. Owned archives are decoded by this function as well.
+    pub(crate) fn decode(bytes: &'a [u8], options: &DecodeOptions) -> Result<Decoded<'a>, Error> {
+        let mut cursor = Cursor::new(bytes);
+        let header = NIBArchive::read_header(&mut cursor)?;
+        let mut decoder = Decoder::new(&mut cursor, options)?;
+        NIBArchive::check_header(&mut decoder, &header)?;
+        trace_event!(
+            debug,
+            format_version = header.format_version,
+            coder_version = header.coder_version,
+            object_count = header.object_count,
+            key_count = header.key_count,
+            value_count = header.value_count,
+            class_name_count = header.class_name_count,
+            "Decoded the header"
+        );
+
+        let mut objects = Vec::with_capacity(header.object_count as usize);
+        for i in 0..header.object_count {
+            let offset = Some(decoder.position());
+            let obj = Object::try_from_decoder(&mut decoder)?;
+            NIBArchive::check_object(&obj, header.value_count, header.class_name_count, offset)?;
+            objects.push(obj);
+            decoder.progress(Section::Objects, i + 1, header.object_count);
+        }
+        check_position!(decoder, header.offset_keys, Section::Keys);
+        trace_event!(trace, section = %Section::Objects, end = decoder.position(), "Decoded a section");
+
+        let mut keys = Vec::with_capacity(header.key_count as usize);
+        for i in 0..header.key_count {
+            keys.push(decoder.key()?);
+            decoder.progress(Section::Keys, i + 1, header.key_count);
+        }
+        check_position!(decoder, header.offset_values, Section::Values);
+        trace_event!(trace, section = %Section::Keys, end = decoder.position(), "Decoded a section");
+
+        let mut values = Vec::with_capacity(header.value_count as usize);
+        for i in 0..header.value_count {
+            decoder.set_values_left(
+                header.offset_class_names as u64,
+                header.value_count - i - 1,
+                header.key_count,
+            );
+            let offset = Some(decoder.position());
+            let val = ValueRef::try_from_decoder(&mut decoder)?;
+            NIBArchive::check_key_index(val.key_index, header.key_count, offset)?;
+            values.push(val);
+            decoder.progress(Section::Values, i + 1, header.value_count);
+        }
+        check_position!(decoder, header.offset_class_names, Section::ClassNames);
+        trace_event!(trace, section = %Section::Values, end = decoder.position(), "Decoded a section");
+
+        let mut class_names = Vec::with_capacity(header.class_name_count as usize);
+        for i in 0..header.class_name_count {
+            let offset = Some(decoder.position());
+            let cls = ClassNameRef::try_from_decoder(&mut decoder)?;
+            NIBArchive::check_fallback_classes(
+                &cls.fallback_classes_indeces,
+                header.class_name_count,
+                offset,
+            )?;
+            class_names.push(cls);
+            decoder.progress(Section::ClassNames, i + 1, header.class_name_count);
+        }
+
+        let end = decoder.position();
+        trace_event!(trace, section = %Section::ClassNames, end, "Decoded a section");
+        let warnings = decoder.into_warnings();
+        #[cfg(feature = "tracing")]
+        for warning in &warnings {
+            tracing::warn!(%warning, "Decoding anomaly");
+        }
+
+        let archive = Self {
+            objects,
+            keys,
+            values,
+            class_names,
+            format_version: header.format_version,
+            coder_version: header.coder_version,
+        };
+        Ok((archive, header, end, warnings))
+    }
+
+    /// Copies the contents into an owned [NIBArchive].
+    pub fn to_owned(&self) -> NIBArchive {
+        let mut archive = NIBArchive::new_unchecked(
+            self.objects.clone(),
+            self.keys.iter().map(|key| key.to_string()).collect(),
+            self.values.iter().map(Value::from).collect(),
+            self.class_names.iter().map(ClassName::from).collect(),
+        );
+        archive.format_version = self.format_version;
+        archive.coder_version = self.coder_version;
+        archive
+    }
+
+    /// Returns a reference to a vector of the archive's [objects](Object).
+    pub fn objects(&self) -> &[Object] {
+        &self.objects
+    }
+
+    /// Returns an array of the archive's keys.
+    pub fn keys(&self) -> &[&'a str] {
+        &self.keys
+    }
+
+    /// Returns a reference to a vector of the archive's [values](ValueRef).
+    pub fn values(&self) -> &[ValueRef<'a>] {
+        &self.values
+    }
+
+    /// Returns a reference to a vector of the archive's [class names](ClassNameRef).
+    pub fn class_names(&self) -> &[ClassNameRef<'a>] {
+        &self.class_names
+    }
+
+    /// Returns the format version of the archive.
+    pub fn format_version(&self) -> u32 {
+        self.format_version
+    }
+
+    /// Returns the coder version of the archive.
+    pub fn coder_version(&self) -> u32 {
+        self.coder_version
+    }
+}
+
+/// A value of a [NIBArchiveRef]. Look at [ValueVariant] for more details.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ValueVariantRef<'a> {
+    Int8(i8),
+    Int16(i16),
+    Int32(i32),
+    Int64(i64),
+    Bool(bool),
+    Float(f32),
+    Double(f64),
+    Data(&'a [u8]),
+    Nil,
+    ObjectRef(u32),
+    Unknown { type_byte: u8, raw: &'a [u8] },
+}
+
+impl From<ValueVariantRef<'_>> for ValueVariant {
+    fn from(value: ValueVariantRef<'_>) -> Self {
+        match value {
+            ValueVariantRef::Int8(v) => ValueVariant::Int8(v),
+            ValueVariantRef::Int16(v) => ValueVariant::Int16(v),
+            ValueVariantRef::Int32(v) => ValueVariant::Int32(v),
+            ValueVariantRef::Int64(v) => ValueVariant::Int64(v),
+            ValueVariantRef::Bool(v) => ValueVariant::Bool(v),
+            ValueVariantRef::Float(v) => ValueVariant::Float(v),
+            ValueVariantRef::Double(v) => ValueVariant::Double(v),
+            ValueVariantRef::Data(v) => ValueVariant::Data(v.to_vec()),
+            ValueVariantRef::Nil => ValueVariant::Nil,
+            ValueVariantRef::ObjectRef(v) => ValueVariant::ObjectRef(v),
+            ValueVariantRef::Unknown { type_byte, raw } => ValueVariant::Unknown {
+                type_byte,
+                raw: raw.to_vec(),
+            },
+        }
+    }
+}
+
+/// A single value of a [NIBArchiveRef].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ValueRef<'a> {
+    key_index: KeyIndex,
+    value: ValueVariantRef<'a>,
+}
+
+impl<'a> ValueRef<'a> {
+    pub(crate) fn try_from_decoder(decoder: &mut SliceDecoder<'_, 'a>) -> Result<Self, Error> {
+        let key_index = KeyIndex(decoder.index("key index")?);
+        let value_type_byte = decoder.u8()?;
+        let value = match value_type_byte {
+            TYPE_INT8 => ValueVariantRef::Int8(decoder.fixed()?),
+            TYPE_INT16 => ValueVariantRef::Int16(decoder.fixed()?),
+            TYPE_INT32 => ValueVariantRef::Int32(decoder.fixed()?),
+            TYPE_INT64 => ValueVariantRef::Int64(decoder.fixed()?),
+            TYPE_BOOL_FALSE => ValueVariantRef::Bool(false),
+            TYPE_BOOL_TRUE => ValueVariantRef::Bool(true),
+            TYPE_FLOAT => ValueVariantRef::Float(decoder.fixed()?),
+            TYPE_DOUBLE => ValueVariantRef::Double(decoder.fixed()?),
+            TYPE_DATA => {
+                let length = decoder.length()?;
+                ValueVariantRef::Data(decoder.take(length)?)
+            }
+            TYPE_NIL => ValueVariantRef::Nil,
+            TYPE_OBJECT_REF => ValueVariantRef::ObjectRef(decoder.fixed()?),
+            _ if decoder.options().lenient_value_types() => ValueVariantRef::Unknown {
+                type_byte: value_type_byte,
+                raw: decoder.unknown_payload()?,
+            },
+            _ => {
+                return Err(Error::UnknownValueType {
+                    type_byte: value_type_byte,
+                    offset: decoder.position() - 1,
+                })
+            }
+        };
+        Ok(Self { key_index, value })
+    }
+
+    /// Returns an index to a key with value's name.
+    pub fn key_id(&self) -> KeyIndex {
+        self.key_index
+    }
+
+    /// Return the underlying value.
+    pub fn value(&self) -> &ValueVariantRef<'a> {
+        &self.value
+    }
+}
+
+impl From<&ValueRef<'_>> for Value {
+    fn from(value: &ValueRef<'_>) -> Self {
+        Value::new(value.key_index, value.value.into())
+    }
+}
+
+/// A single class name of a [NIBArchiveRef].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClassNameRef<'a> {
+    name: &'a str,
+    fallback_classes_indeces: Vec<ClassNameIndex>,
+}
+
+impl<'a> ClassNameRef<'a> {
+    pub(crate) fn try_from_decoder(decoder: &mut SliceDecoder<'_, 'a>) -> Result<Self, Error> {
+        let offset = decoder.position();
+        let length = decoder.length()?;
+        let fallback_classes_count = decoder.index("fallback classes count")?;
+        decoder.check_count(fallback_classes_count as u32, LimitKind::FallbackClassCount)?;
+        let mut fallback_classes_indeces = Vec::with_capacity(fallback_classes_count as usize);
+        for _ in 0..fallback_classes_count {
+            fallback_classes_indeces.push(ClassNameIndex(decoder.fixed()?));
+        }
+        let name_bytes = decoder.take(length)?;
+        // Name is \0 terminated, so we have to remove the trailing \0
+        let name_bytes = &name_bytes[..length.saturating_sub(1)];
+        let name = validate_utf8(name_bytes).map_err(|_| Error::InvalidUtf8 {
+            section: Section::ClassNames,
+            offset,
+        })?;
+        Ok(Self {
+            name,
+            fallback_classes_indeces,
+        })
+    }
+
+    /// Returns the name of a class.
+    pub fn name(&self) -> &'a str {
+        self.name
+    }
+
+    /// Returns an array of indeces for fallback classes.
+    pub fn fallback_class_ids(&self) -> &[ClassNameIndex] {
+        &self.fallback_classes_indeces
+    }
+}
+
+impl From<&ClassNameRef<'_>> for ClassName {
+    fn from(value: &ClassNameRef<'_>) -> Self {
+        ClassName::with_fallbacks(
+            value.name.to_string(),
+            value.fallback_classes_indeces.clone(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        io::Cursor, ClassName, DecodeOptions, Error, LimitKind, Limits, NIBArchive, NIBArchiveRef,
+        Object, Value, ValueVariant,
+    };
+    use alloc::{string::ToString, vec, vec::Vec};
+
+    fn archive() -> NIBArchive {
+        NIBArchive::new(
+            vec![Object::new(0, 0, 2)],
+            vec!["UIData".to_string(), "UIValue".to_string()],
+            vec![
+                Value::new(0, ValueVariant::Data(vec![1, 2, 3])),
+                Value::new(1, ValueVariant::Double(1.5)),
+            ],
+            vec![ClassName::with_fallbacks(
+                "NSObject".to_string(),
+                Vec::new(),
+            )],
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn to_owned() {
+        let bytes = archive().to_bytes();
+        let borrowed = NIBArchiveRef::from_bytes(&bytes).unwrap();
+        assert_eq!(borrowed.keys(), ["UIData", "UIValue"]);
+        assert_eq!(borrowed.class_names()[0].name(), "NSObject");
+        assert_eq!(borrowed.to_owned(), NIBArchive::from_bytes(&bytes).unwrap());
+    }
+
+    #[test]
+    fn reader_size_limit() {
+        let bytes = archive().to_bytes();
+        let mut limits = Limits::new();
+        limits.set_max_total_size(bytes.len() as u64 - 1);
+        let mut options = DecodeOptions::new();
+        options.set_limits(limits);
+        let err =
+            NIBArchive::from_reader_with_options(&mut Cursor::new(&bytes), &options).unwrap_err();
+        assert!(matches!(
+            err,
+            Error::LimitExceeded {
+                limit: LimitKind::TotalSize,
+                ..
+            }
+        ));
+    }
+}
@@ -0,0 +1,459 @@
+//! A C API for decoding and encoding archives.
+//!
+//! Functions are prefixed with `nib_` and declared in `include/nibarchive.h`, which is
+//! generated with `cbindgen --config cbindgen.toml --output include/nibarchive.h`.
+//! A shared or static library can be built with
+//! `cargo rustc --release --features capi --crate-type cdylib` (or `staticlib`).
+//!
+//! Strings and data returned by the API are borrowed from an archive and stay valid
+//! until the archive is freed. They aren't null-terminated, their lengths are returned
+//! separately. Functions that fail return `NULL`, `false` or [NIB_ERROR], and the error
+//! message is available through [nib_last_error()]. Null handles and panics are reported
+//! as errors too, no panic unwinds into the caller.
+
+use crate::{NIBArchive, ValueVariant};
+use std::{
+    cell::RefCell,
+    ffi::{c_char, CStr, CString},
+    panic::{catch_unwind, AssertUnwindSafe},
+    ptr, slice,
+};
+
+/// Returned by functions that return counts if they fail.
+pub const NIB_ERROR: usize = usize::MAX;
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+/// An opaque handle of a decoded archive.
+pub struct NibArchive(NIBArchive);
+
+/// An object of an archive.
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct NibObject {
+    pub class_name_index: i32,
+    /// Index of the first value of the object.
+    pub values_index: i32,
+    pub value_count: i32,
+}
+
+/// Type of a [NibValue].
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NibValueType {
+    Int8,
+    Int16,
+    Int32,
+    Int64,
+    Bool,
+    Float,
+    Double,
+    Data,
+    Nil,
+    ObjectRef,
+    Unknown,
+}
+
+/// A value of an archive.
+///
+/// Integers, booleans and object references are stored in `integer`, floats and doubles
+/// in `real`. Data and raw payloads of unknown values are pointed to by `data`, which
+/// is `NULL` if they're empty. The type byte of an unknown value is stored in `integer`.
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct NibValue {
+    pub key_index: i32,
+    pub value_type: NibValueType,
+    pub integer: i64,
+    pub real: f64,
+    pub data: *const u8,
+    pub data_len: usize,
+}
+
+/// A buffer allocated by the library, which has to be freed with [nib_buffer_free()].
+#[repr(C)]
+#[derive(Debug)]
+pub struct NibBuffer {
+    pub data: *mut u8,
+    pub len: usize,
+}
+
+fn set_last_error(message: String) {
+    let message = CString::new(message.replace('\0', " ")).unwrap_or_default();
+    LAST_ERROR.with(|e| *e.borrow_mut() = Some(message));
+}
+
+/// Runs `f`, returning `error` and setting the last error if it fails or panics.
+fn guard<T>(error: T, f: impl FnOnce() -> Result<T, String>) -> T {
+    match catch_unwind(AssertUnwindSafe(f)) {
+        Ok(Ok(value)) => value,
+        Ok(Err(message)) => {
+            set_last_error(message);
+            error
+        }
+        Err(panic) => {
+            let message = panic
+                .downcast_ref::<&str>()
+                .copied()
+                .or_else(|| panic.downcast_ref::<String>().map(String::as_str))
+                .unwrap_or("unknown panic");
+            set_last_error(format!("Panicked: {message}"));
+            error
+        }
+    }
+}
+
+/// Dereferences a handle of an archive, failing if it's null.
+unsafe fn handle<'a>(archive: *const NibArchive) -> Result<&'a NIBArchive, String> {
+    archive
+        .as_ref()
+        .map(|a| &a.0)
+        .ok_or_else(|| "The archive is null".to_string())
+}
+
+/// Dereferences an output pointer, failing if it's null.
+unsafe fn writable<'a, T>(ptr: *mut T, name: &str) -> Result<&'a mut T, String> {
+    ptr.as_mut()
+        .ok_or_else(|| format!("The {name} pointer is null"))
+}
+
+fn into_handle(result: Result<NIBArchive, crate::Error>) -> Result<*mut NibArchive, String> {
+    match result {
+        Ok(archive) => Ok(Box::into_raw(Box::new(NibArchive(archive)))),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+/// Returns the message of the last error that occurred in the current thread,
+/// or `NULL` if there was none.
+///
+/// The message stays valid until the next failing call in the same thread.
+#[no_mangle]
+pub extern "C" fn nib_last_error() -> *const c_char {
+    LAST_ERROR.with(|e| e.borrow().as_ref().map_or(ptr::null(), |s| s.as_ptr()))
+}
+
+/// Decodes an archive from a file at a given null-terminated UTF-8 path.
+///
+/// Returns `NULL` on failure. The archive has to be freed with [nib_archive_free()].
+///
+/// # Safety
+///
+/// `path` has to be a valid null-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn nib_archive_open(path: *const c_char) -> *mut NibArchive {
+    guard(ptr::null_mut(), || {
+        if path.is_null() {
+            return Err("The path is null".to_string());
+        }
+        let path = CStr::from_ptr(path)
+            .to_str()
+            .map_err(|_| "The path isn't valid UTF-8".to_string())?;
+        into_handle(NIBArchive::from_file(path))
+    })
+}
+
+/// Decodes an archive from `len` bytes of `data`.
+///
+/// Returns `NULL` on failure. The archive has to be freed with [nib_archive_free()].
+///
+/// # Safety
+///
+/// `data` has to point to at least `len` readable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn nib_archive_from_bytes(data: *const u8, len: usize) -> *mut NibArchive {
+    guard(ptr::null_mut(), || {
+        if data.is_null() {
+            return Err("The data is null".to_string());
+        }
+        into_handle(NIBArchive::from_bytes(slice::from_raw_parts(data, len)))
+    })
+}
+
+/// Frees an archive. Does nothing if `archive` is `NULL`.
+///
+/// # Safety
+///
+/// `archive` has to be returned by this library and not freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn nib_archive_free(archive: *mut NibArchive) {
+    guard((), || {
+        if !archive.is_null() {
+            drop(Box::from_raw(archive));
+        }
+        Ok(())
+    })
+}
+
+/// Returns the count of objects of an archive, or [NIB_ERROR] if `archive` is `NULL`.
+///
+/// # Safety
+///
+/// `archive` has to be a valid archive or `NULL`.
+#[no_mangle]
+pub unsafe extern "C" fn nib_archive_object_count(archive: *const NibArchive) -> usize {
+    guard(NIB_ERROR, || Ok(handle(archive)?.objects().len()))
+}
+
+/// Returns the count of keys of an archive, or [NIB_ERROR] if `archive` is `NULL`.
+///
+/// # Safety
+///
+/// `archive` has to be a valid archive or `NULL`.
+#[no_mangle]
+pub unsafe extern "C" fn nib_archive_key_count(archive: *const NibArchive) -> usize {
+    guard(NIB_ERROR, || Ok(handle(archive)?.keys().len()))
+}
+
+/// Returns the count of values of an archive, or [NIB_ERROR] if `archive` is `NULL`.
+///
+/// # Safety
+///
+/// `archive` has to be a valid archive or `NULL`.
+#[no_mangle]
+pub unsafe extern "C" fn nib_archive_value_count(archive: *const NibArchive) -> usize {
+    guard(NIB_ERROR, || Ok(handle(archive)?.values().len()))
+}
+
+/// Returns the count of class names of an archive, or [NIB_ERROR] if `archive` is `NULL`.
+///
+/// # Safety
+///
+/// `archive` has to be a valid archive or `NULL`.
+#[no_mangle]
+pub unsafe extern "C" fn nib_archive_class_name_count(archive: *const NibArchive) -> usize {
+    guard(NIB_ERROR, || Ok(handle(archive)?.class_names().len()))
+}
+
+/// Writes an object at `index` into `out`. Returns `false` if the index is out of bounds
+/// or a pointer is `NULL`.
+///
+/// # Safety
+///
+/// `archive` has to be a valid archive and `out` has to be writable, or they have to be `NULL`.
+#[no_mangle]
+pub unsafe extern "C" fn nib_archive_object(
+    archive: *const NibArchive,
+    index: usize,
+    out: *mut NibObject,
+) -> bool {
+    guard(false, || {
+        let archive = handle(archive)?;
+        let out = writable(out, "object")?;
+        let Some(obj) = archive.objects().get(index) else {
+            return Err(format!("Object index {index} is out of bounds"));
+        };
+        *out = NibObject {
+            class_name_index: obj.class_name_id().0,
+            values_index: obj.first_value_id().0,
+            value_count: obj.value_count(),
+        };
+        Ok(true)
+    })
+}
+
+/// Writes a value at `index` into `out`. Returns `false` if the index is out of bounds
+/// or a pointer is `NULL`.
+///
+/// # Safety
+///
+/// `archive` has to be a valid archive and `out` has to be writable, or they have to be `NULL`.
+#[no_mangle]
+pub unsafe extern "C" fn nib_archive_value(
+    archive: *const NibArchive,
+    index: usize,
+    out: *mut NibValue,
+) -> bool {
+    guard(false, || {
+        let archive = handle(archive)?;
+        let out = writable(out, "value")?;
+        let Some(val) = archive.values().get(index) else {
+            return Err(format!("Value index {index} is out of bounds"));
+        };
+        use NibValueType as T;
+        let (value_type, integer, real, data): (_, i64, f64, &[u8]) = match val.value() {
+            ValueVariant::Int8(v) => (T::Int8, *v as i64, 0.0, &[]),
+            ValueVariant::Int16(v) => (T::Int16, *v as i64, 0.0, &[]),
+            ValueVariant::Int32(v) => (T::Int32, *v as i64, 0.0, &[]),
+            ValueVariant::Int64(v) => (T::Int64, *v, 0.0, &[]),
+            ValueVariant::Bool(v) => (T::Bool, *v as i64, 0.0, &[]),
+            ValueVariant::Float(v) => (T::Float, 0, *v as f64, &[]),
+            ValueVariant::Double(v) => (T::Double, 0, *v, &[]),
+            ValueVariant::Data(v) => (T::Data, 0, 0.0, v),
+            ValueVariant::Nil => (T::Nil, 0, 0.0, &[]),
+            ValueVariant::ObjectRef(v) => (T::ObjectRef, *v as i64, 0.0, &[]),
+            ValueVariant::Unknown { type_byte, raw } => (T::Unknown, *type_byte as i64, 0.0, raw),
+        };
+        *out = NibValue {
+            key_index: val.key_id().0,
+            value_type,
+            integer,
+            real,
+            data: if data.is_empty() {
+                ptr::null()
+            } else {
+                data.as_ptr()
+            },
+            data_len: data.len(),
+        };
+        Ok(true)
+    })
+}
+
+/// Returns a key at `index` and writes its length into `len`.
+/// Returns `NULL` if the index is out of bounds or a pointer is `NULL`.
+///
+/// # Safety
+///
+/// `archive` has to be a valid archive and `len` has to be writable, or they have to be `NULL`.
+#[no_mangle]
+pub unsafe extern "C" fn nib_archive_key(
+    archive: *const NibArchive,
+    index: usize,
+    len: *mut usize,
+) -> *const u8 {
+    guard(ptr::null(), || {
+        let archive = handle(archive)?;
+        let len = writable(len, "length")?;
+        let Some(key) = archive.keys().get(index) else {
+            return Err(format!("Key index {index} is out of bounds"));
+        };
+        *len = key.len();
+        Ok(key.as_ptr())
+    })
+}
+
+/// Returns a class name at `index` and writes its length into `len`.
+/// Returns `NULL` if the index is out of bounds or a pointer is `NULL`.
+///
+/// # Safety
+///
+/// `archive` has to be a valid archive and `len` has to be writable, or they have to be `NULL`.
+#[no_mangle]
+pub unsafe extern "C" fn nib_archive_class_name(
+    archive: *const NibArchive,
+    index: usize,
+    len: *mut usize,
+) -> *const u8 {
+    guard(ptr::null(), || {
+        let archive = handle(archive)?;
+        let len = writable(len, "length")?;
+        let Some(class_name) = archive.class_names().get(index) else {
+            return Err(format!("Class name index {index} is out of bounds"));
+        };
+        *len = class_name.name().len();
+        Ok(class_name.name().as_ptr())
+    })
+}
+
+/// Encodes an archive. The buffer has to be freed with [nib_buffer_free()].
+/// Returns a buffer with `NULL` data if `archive` is `NULL`.
+///
+/// # Safety
+///
+/// `archive` has to be a valid archive or `NULL`.
+#[no_mangle]
+pub unsafe extern "C" fn nib_archive_encode(archive: *const NibArchive) -> NibBuffer {
+    let error = NibBuffer {
+        data: ptr::null_mut(),
+        len: 0,
+    };
+    guard(error, || {
+        let bytes = handle(archive)?.to_bytes().into_boxed_slice();
+        let len = bytes.len();
+        Ok(NibBuffer {
+            data: Box::into_raw(bytes) as *mut u8,
+            len,
+        })
+    })
+}
+
+/// Frees a buffer returned by [nib_archive_encode()].
+///
+/// # Safety
+///
+/// `buffer` has to be returned by this library and not freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn nib_buffer_free(buffer: NibBuffer) {
+    guard((), || {
+        if !buffer.data.is_null() {
+            drop(Box::from_raw(ptr::slice_from_raw_parts_mut(
+                buffer.data,
+                buffer.len,
+            )));
+        }
+        Ok(())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ClassName, Object, Value};
+
+    fn last_error() -> String {
+        let error = nib_last_error();
+        assert!(!error.is_null());
+        unsafe { CStr::from_ptr(error) }
+            .to_string_lossy()
+            .into_owned()
+    }
+
+    #[test]
+    fn null_handles() {
+        unsafe {
+            assert_eq!(nib_archive_object_count(ptr::null()), NIB_ERROR);
+            assert_eq!(last_error(), "The archive is null");
+            let mut obj = NibObject {
+                class_name_index: 0,
+                values_index: 0,
+                value_count: 0,
+            };
+            assert!(!nib_archive_object(ptr::null(), 0, &mut obj));
+            let mut len = 0;
+            assert!(nib_archive_key(ptr::null(), 0, &mut len).is_null());
+            assert!(nib_archive_encode(ptr::null()).data.is_null());
+            assert!(nib_archive_from_bytes(ptr::null(), 0).is_null());
+            assert_eq!(last_error(), "The data is null");
+            nib_archive_free(ptr::null_mut());
+        }
+    }
+
+    #[test]
+    fn archive() {
+        let bytes = NIBArchive::new(
+            vec![Object::new(0, 0, 1)],
+            vec!["UITag".into()],
+            vec![Value::new(0, ValueVariant::Int8(7))],
+            vec![ClassName::with_fallbacks("UIView".into(), Vec::new())],
+        )
+        .unwrap()
+        .to_bytes();
+        unsafe {
+            let archive = nib_archive_from_bytes(bytes.as_ptr(), bytes.len());
+            assert!(!archive.is_null());
+            assert_eq!(nib_archive_value_count(archive), 1);
+
+            let mut len = 0;
+            let name = nib_archive_class_name(archive, 0, &mut len);
+            assert_eq!(slice::from_raw_parts(name, len), b"UIView");
+            assert!(nib_archive_class_name(archive, 1, &mut len).is_null());
+            assert_eq!(last_error(), "Class name index 1 is out of bounds");
+            assert!(nib_archive_class_name(archive, 0, ptr::null_mut()).is_null());
+            assert_eq!(last_error(), "The length pointer is null");
+
+            let buffer = nib_archive_encode(archive);
+            assert_eq!(slice::from_raw_parts(buffer.data, buffer.len), &bytes[..]);
+            nib_buffer_free(buffer);
+            nib_archive_free(archive);
+        }
+    }
+
+    #[test]
+    fn panics() {
+        assert!(!guard(false, || panic!("broken")));
+        assert_eq!(last_error(), "Panicked: broken");
+    }
+}
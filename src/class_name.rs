@@ -1,31 +1,27 @@
-use crate::{decode_var_int, encode_var_int, Error};
-use std::io::{Read, Seek};
+use crate::{
+    codec::{write_fixed, Endianness},
+    encode_var_int, var_int_len, ClassNameIndex,
+};
+use alloc::{string::String, vec::Vec};
 
 /// Represents a single class name of a NIB Archive.
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub struct ClassName {
     name: String,
-    fallback_classes_indeces: Vec<i32>,
+    fallback_classes_indeces: Vec<ClassNameIndex>,
 }
 
 impl ClassName {
-    pub(crate) fn try_from_reader<T: Read + Seek>(mut reader: &mut T) -> Result<Self, Error> {
-        let length = decode_var_int(&mut reader)?;
-        let fallback_classes_count = decode_var_int(&mut reader)?;
-        let mut fallback_classes_indeces = Vec::with_capacity(fallback_classes_count as usize);
-        for _ in 0..fallback_classes_count {
-            let mut buf = [0; 4];
-            reader.read_exact(&mut buf)?;
-            fallback_classes_indeces.push(i32::from_le_bytes(buf));
-        }
-        let mut name_bytes = vec![0; length as usize];
-        reader.read_exact(&mut name_bytes)?;
-        name_bytes.pop(); // Name is \0 terminated, so we have to remove the trailing \0
-        let name = String::from_utf8(name_bytes)?;
-        Ok(Self {
-            name,
-            fallback_classes_indeces,
-        })
+    pub(crate) fn encoded_len(&self) -> usize {
+        let count = self.fallback_classes_indeces.len();
+        // The name is followed by \0
+        var_int_len(self.name.len() as i32 + 1)
+            + var_int_len(count as i32)
+            + count * 4
+            + self.name.len()
+            + 1
     }
 
     pub(crate) fn to_bytes(&self) -> Vec<u8> {
@@ -34,7 +30,7 @@ impl ClassName {
             self.fallback_classes_indeces.len() as i32
         ));
         for cls in &self.fallback_classes_indeces {
-            bytes.extend_from_slice(&cls.to_le_bytes());
+            write_fixed(cls.0, &mut bytes, Endianness::Little);
         }
         bytes.extend_from_slice(self.name.as_bytes());
         bytes.push(0x00);
@@ -42,13 +38,25 @@ impl ClassName {
     }
 
     /// Creates a new NIB Archive class name.
-    pub fn new(name: String, fallback_classes_indeces: Vec<i32>) -> Self {
+    pub fn with_fallbacks(name: String, fallback_classes_indeces: Vec<ClassNameIndex>) -> Self {
         Self {
             name,
             fallback_classes_indeces,
         }
     }
 
+    /// Creates a new NIB Archive class name from raw fallback classes indeces.
+    #[deprecated(note = "use `ClassName::with_fallbacks()`, which takes typed indeces")]
+    pub fn new(name: String, fallback_classes_indeces: Vec<i32>) -> Self {
+        Self::with_fallbacks(
+            name,
+            fallback_classes_indeces
+                .into_iter()
+                .map(ClassNameIndex)
+                .collect(),
+        )
+    }
+
     /// Returns the name of a class.
     pub fn name(&self) -> &str {
         &self.name
@@ -60,28 +68,41 @@ impl ClassName {
     }
 
     /// Returns an array of indeces for fallback classes.
-    pub fn fallback_classes_indeces(&self) -> &[i32] {
+    pub fn fallback_class_ids(&self) -> &[ClassNameIndex] {
         &self.fallback_classes_indeces
     }
 
+    /// Returns an array of raw indeces for fallback classes.
+    #[deprecated(note = "use `ClassName::fallback_class_ids()`, which returns typed indeces")]
+    pub fn fallback_classes_indeces(&self) -> Vec<i32> {
+        self.fallback_classes_indeces.iter().map(|i| i.0).collect()
+    }
+
     /// Sets fallback classes indeces of a class.
-    pub fn set_fallback_classes_indeces(&mut self, indeces: Vec<i32>) {
+    pub fn set_fallback_class_ids(&mut self, indeces: Vec<ClassNameIndex>) {
         self.fallback_classes_indeces = indeces;
     }
 
+    /// Sets raw fallback classes indeces of a class.
+    #[deprecated(note = "use `ClassName::set_fallback_class_ids()`, which takes typed indeces")]
+    pub fn set_fallback_classes_indeces(&mut self, indeces: Vec<i32>) {
+        self.set_fallback_class_ids(indeces.into_iter().map(ClassNameIndex).collect());
+    }
+
     /// Returns a slice of [ClassNames](ClassName) representing fallback classes.
     ///
     /// Pass the return value of [crate::NIBArchive::class_names()] method for a proper result.
     pub fn fallback_classes<'a>(&self, class_names: &'a [ClassName]) -> Vec<&'a ClassName> {
         let mut fallback_classes = Vec::with_capacity(self.fallback_classes_indeces.len());
-        for i in self.fallback_classes_indeces() {
-            fallback_classes.push(&class_names[*i as usize])
+        for i in self.fallback_class_ids() {
+            fallback_classes.push(&class_names[i.as_usize()])
         }
         fallback_classes
     }
 
-    /// Consumes itself and returns a unit of `name` and `fallback_classes`
+    /// Consumes itself and returns a unit of `name` and raw `fallback_classes`
     pub fn into_inner(self) -> (String, Vec<i32>) {
-        (self.name, self.fallback_classes_indeces)
+        let indeces = self.fallback_classes_indeces.iter().map(|i| i.0).collect();
+        (self.name, indeces)
     }
 }
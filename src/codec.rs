@@ -0,0 +1,223 @@
+//! Low level encoding primitives used by a NIB Archive.
+//!
+//! All fixed-width fields of a NIB Archive are stored in little-endian byte order, while
+//! indeces, counts and lengths are stored as variable integers.
+
+use crate::{io::Read, Error, VarInt};
+use alloc::{format, vec::Vec};
+
+/// Byte order of fixed-width fields.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Endianness {
+    /// Little-endian byte order, used by all known NIB Archives.
+    #[default]
+    Little,
+    /// Big-endian byte order.
+    Big,
+}
+
+/// A fixed-width number that can be read or written in a given byte order.
+pub trait FixedWidth: Sized {
+    /// Size of the number in bytes, up to 8.
+    const SIZE: usize;
+
+    /// Creates a number from bytes. Returns an error if the slice isn't exactly
+    /// [Self::SIZE] bytes long.
+    fn from_bytes(bytes: &[u8], endianness: Endianness) -> Result<Self, Error>;
+
+    /// Appends bytes of the number to a given vector.
+    fn write_bytes(&self, bytes: &mut Vec<u8>, endianness: Endianness);
+}
+
+macro_rules! impl_fixed_width {
+    ($($t:ty),*) => {
+        $(
+            impl FixedWidth for $t {
+                const SIZE: usize = core::mem::size_of::<$t>();
+
+                fn from_bytes(bytes: &[u8], endianness: Endianness) -> Result<Self, Error> {
+                    let bytes = bytes.try_into().map_err(|_| {
+                        Error::FormatError(format!(
+                            "Expected {} bytes for {}, found {}",
+                            Self::SIZE,
+                            stringify!($t),
+                            bytes.len()
+                        ))
+                    })?;
+                    Ok(match endianness {
+                        Endianness::Little => <$t>::from_le_bytes(bytes),
+                        Endianness::Big => <$t>::from_be_bytes(bytes),
+                    })
+                }
+
+                fn write_bytes(&self, bytes: &mut Vec<u8>, endianness: Endianness) {
+                    match endianness {
+                        Endianness::Little => bytes.extend_from_slice(&self.to_le_bytes()),
+                        Endianness::Big => bytes.extend_from_slice(&self.to_be_bytes()),
+                    }
+                }
+            }
+        )*
+    };
+}
+
+impl_fixed_width!(i8, u8, i16, u16, i32, u32, i64, u64, f32, f64);
+
+/// Reads a fixed-width number in a given byte order.
+pub fn read_fixed<T: FixedWidth, R: Read>(
+    reader: &mut R,
+    endianness: Endianness,
+) -> Result<T, Error> {
+    let mut buf = [0; 8];
+    let buf = &mut buf[..T::SIZE];
+    reader.read_exact(buf)?;
+    T::from_bytes(buf, endianness)
+}
+
+/// Appends a fixed-width number in a given byte order to a vector.
+pub fn write_fixed<T: FixedWidth>(value: T, bytes: &mut Vec<u8>, endianness: Endianness) {
+    value.write_bytes(bytes, endianness);
+}
+
+/// Decodes a variable integer ([more info](https://github.com/matsmattsson/nibsqueeze/blob/master/NibArchive.md#varint-coding))
+/// that takes at most `max_len` bytes into a regular i32. Also returns the number
+/// of bytes the variable integer has taken.
+pub(crate) fn decode_var_int_bounded<T: Read>(
+    reader: &mut T,
+    max_len: usize,
+) -> Result<(VarInt, usize), Error> {
+    let mut result: u32 = 0;
+    let mut shift = 0;
+    let mut len = 0;
+    loop {
+        if len == max_len {
+            return Err(Error::FormatError(format!(
+                "Variable integer is longer than {max_len} bytes"
+            )));
+        }
+        let mut current_byte = [0; 1];
+        reader.read_exact(&mut current_byte)?;
+        len += 1;
+        let current_byte = current_byte[0];
+        let group = (current_byte & 0x7F) as u64;
+        if group != 0 {
+            // Groups past 32 bits may only be redundant zeros
+            if shift >= 32 || group << shift > u32::MAX as u64 {
+                return Err(Error::FormatError(
+                    "Variable integer overflows 32 bits".into(),
+                ));
+            }
+            result |= (group << shift) as u32;
+        }
+        shift += 7;
+        if (current_byte & 128) != 0 {
+            break;
+        }
+    }
+    Ok((result as VarInt, len))
+}
+
+/// Returns the number of bytes an i32 takes when encoded as a variable integer.
+pub(crate) fn var_int_len(value: VarInt) -> usize {
+    let mut len = 1;
+    let mut v = (value as u32) >> 7;
+    while v != 0 {
+        len += 1;
+        v >>= 7;
+    }
+    len
+}
+
+/// Encodes an i32 into a variable integer bytes.
+///
+/// Negative values are encoded as their unsigned 32-bit counterparts.
+pub(crate) fn encode_var_int(value: VarInt) -> Vec<u8> {
+    let mut value = value as u32;
+    let mut bytes = Vec::with_capacity(var_int_len(value as VarInt));
+    loop {
+        let digit = (value & 0x7f) as u8;
+        value >>= 7;
+        if value == 0 {
+            bytes.push(digit | 0x80);
+            break;
+        }
+        bytes.push(digit);
+    }
+    bytes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn round_trip<T: FixedWidth + PartialEq + core::fmt::Debug + Copy>(value: T) {
+        for endianness in [Endianness::Little, Endianness::Big] {
+            let mut bytes = Vec::new();
+            write_fixed(value, &mut bytes, endianness);
+            assert_eq!(bytes.len(), T::SIZE);
+            assert_eq!(
+                read_fixed::<T, _>(&mut &bytes[..], endianness).unwrap(),
+                value
+            );
+        }
+    }
+
+    #[test]
+    fn fixed_round_trip() {
+        round_trip(i8::MIN);
+        round_trip(u8::MAX);
+        round_trip(i16::MIN);
+        round_trip(u16::MAX);
+        round_trip(i32::MIN);
+        round_trip(u32::MAX);
+        round_trip(i64::MIN);
+        round_trip(u64::MAX);
+        round_trip(f32::MIN_POSITIVE);
+        round_trip(f64::MAX);
+    }
+
+    #[test]
+    fn fixed_byte_order() {
+        let mut bytes = Vec::new();
+        write_fixed(0x0102_0304u32, &mut bytes, Endianness::Big);
+        assert_eq!(bytes, [1, 2, 3, 4]);
+        assert_eq!(
+            u32::from_bytes(&bytes, Endianness::Little).unwrap(),
+            0x0403_0201
+        );
+    }
+
+    #[test]
+    fn fixed_wrong_length() {
+        assert!(u32::from_bytes(&[1, 2, 3], Endianness::Little).is_err());
+        assert!(u16::from_bytes(&[1, 2, 3], Endianness::Big).is_err());
+        assert!(read_fixed::<u64, _>(&mut &[0u8; 7][..], Endianness::Little).is_err());
+    }
+
+    #[test]
+    fn var_int_round_trip() {
+        for value in [0, 1, 127, 128, 16383, 16384, i32::MAX, -1, i32::MIN] {
+            let bytes = encode_var_int(value);
+            assert_eq!(bytes.len(), var_int_len(value));
+            let (decoded, len) = decode_var_int_bounded(&mut &bytes[..], 5).unwrap();
+            assert_eq!((decoded, len), (value, bytes.len()));
+        }
+    }
+
+    #[test]
+    fn var_int_boundaries() {
+        assert_eq!(encode_var_int(127), [0xFF]);
+        assert_eq!(encode_var_int(128), [0x00, 0x81]);
+        assert_eq!(var_int_len(-1), 5);
+        // Redundant zero groups are allowed, but take more bytes
+        assert_eq!(
+            decode_var_int_bounded(&mut &[0x01, 0x80][..], 5).unwrap(),
+            (1, 2)
+        );
+        // Too long, overflowing and truncated integers
+        assert!(decode_var_int_bounded(&mut &[0x00; 6][..], 5).is_err());
+        assert!(decode_var_int_bounded(&mut &[0x00, 0x00, 0x00, 0x00, 0x10][..], 5).is_err());
+        assert!(decode_var_int_bounded(&mut &[0x00, 0x00][..], 5).is_err());
+        assert!(decode_var_int_bounded(&mut &[0x00, 0x81][..], 1).is_err());
+    }
+}
@@ -0,0 +1,346 @@
+use crate::{consts::keys, Error, IndexKind, NIBArchive, Value, ValueVariant};
+
+/// Keys that describe a color. They're replaced as a whole when a color is written back.
+const COLOR_KEYS: &[&str] = &[
+    keys::UI_COLOR_COMPONENT_COUNT,
+    keys::UI_RED,
+    keys::UI_GREEN,
+    keys::UI_BLUE,
+    keys::UI_ALPHA,
+    keys::UI_WHITE,
+    keys::NS_COLOR_SPACE,
+    keys::NS_RGB,
+    keys::NS_WHITE,
+    keys::NS_CMYK,
+    keys::UI_SYSTEM_COLOR_NAME,
+    keys::NS_COLOR_NAME,
+];
+
+/// A color space of a [Color].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ColorSpace {
+    /// Red, green, blue and alpha components.
+    Rgb,
+    /// White and alpha components.
+    Gray,
+    /// Cyan, magenta, yellow, black and alpha components.
+    Cmyk,
+}
+
+/// A color decoded from a `UIColor` or an `NSColor` object.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Color {
+    color_space: ColorSpace,
+    components: Vec<f64>,
+    name: Option<String>,
+}
+
+impl Color {
+    /// Creates a new color. The last component is alpha.
+    pub fn new(color_space: ColorSpace, components: Vec<f64>) -> Self {
+        Self {
+            color_space,
+            components,
+            name: None,
+        }
+    }
+
+    /// Creates a new RGB color.
+    pub fn rgba(red: f64, green: f64, blue: f64, alpha: f64) -> Self {
+        Self::new(ColorSpace::Rgb, vec![red, green, blue, alpha])
+    }
+
+    /// Returns the color space.
+    pub fn color_space(&self) -> ColorSpace {
+        self.color_space
+    }
+
+    /// Returns color components, the last one of which is alpha.
+    pub fn components(&self) -> &[f64] {
+        &self.components
+    }
+
+    /// Returns the name of a system or a catalog color, e.g. `systemBlueColor`.
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    /// Sets the name of a system or a catalog color.
+    pub fn set_name(&mut self, name: Option<String>) {
+        self.name = name;
+    }
+
+    /// Returns the alpha component.
+    pub fn alpha(&self) -> f64 {
+        self.components.last().copied().unwrap_or(1.0)
+    }
+
+    /// Decodes a color from a `UIColor` or an `NSColor` object of a keyed archive,
+    /// e.g. one returned by [ValueVariant::as_keyed_archive()].
+    ///
+    /// The root object of a keyed archive is searched for a color as well.
+    #[cfg(feature = "keyed-archive")]
+    pub fn from_keyed_value(value: &crate::KeyedValue) -> Option<Self> {
+        use crate::KeyedValue;
+        let KeyedValue::Object { fields, .. } = value else {
+            return None;
+        };
+        let field = |key: &str| fields.iter().find(|(k, _)| k == key).map(|(_, v)| v);
+        let number = |key| match field(key)? {
+            KeyedValue::Real(v) => Some(*v),
+            KeyedValue::Integer(v) => Some(*v as f64),
+            _ => None,
+        };
+        let ascii = |key, count| match field(key)? {
+            KeyedValue::Data(data) => parse_ascii_components(data, count),
+            _ => None,
+        };
+        let mut color = if let (Some(r), Some(g), Some(b)) = (
+            number(keys::UI_RED),
+            number(keys::UI_GREEN),
+            number(keys::UI_BLUE),
+        ) {
+            Color::rgba(r, g, b, number(keys::UI_ALPHA).unwrap_or(1.0))
+        } else if let Some(w) = number(keys::UI_WHITE) {
+            Color::new(
+                ColorSpace::Gray,
+                vec![w, number(keys::UI_ALPHA).unwrap_or(1.0)],
+            )
+        } else if let Some(c) = ascii(keys::NS_RGB, 3) {
+            Color::new(ColorSpace::Rgb, c)
+        } else if let Some(c) = ascii(keys::NS_WHITE, 1) {
+            Color::new(ColorSpace::Gray, c)
+        } else if let Some(c) = ascii(keys::NS_CMYK, 4) {
+            Color::new(ColorSpace::Cmyk, c)
+        } else {
+            return fields.iter().find_map(|(_, v)| Self::from_keyed_value(v));
+        };
+        color.name = [keys::UI_SYSTEM_COLOR_NAME, keys::NS_COLOR_NAME]
+            .iter()
+            .find_map(|key| match field(key)? {
+                KeyedValue::String(s) => Some(s.clone()),
+                _ => None,
+            });
+        Some(color)
+    }
+}
+
+impl NIBArchive {
+    /// Decodes a color from an object at `index`.
+    ///
+    /// Both UIKit (`UIRed`, `UIWhite`, ...) and AppKit (`NSRGB`, `NSWhite`, `NSCMYK`) encodings
+    /// are recognized. Returns `None` if the object doesn't look like a color.
+    pub fn color(&self, index: usize) -> Option<Color> {
+        let number = |key| {
+            self.object_value(index, key)
+                .and_then(|v| v.value().as_f64())
+        };
+        let name = [keys::UI_SYSTEM_COLOR_NAME, keys::NS_COLOR_NAME]
+            .iter()
+            .find_map(|key| self.object_value(index, key))
+            .and_then(|v| v.value().data_as_string());
+
+        let mut color = if let (Some(r), Some(g), Some(b)) = (
+            number(keys::UI_RED),
+            number(keys::UI_GREEN),
+            number(keys::UI_BLUE),
+        ) {
+            Color::rgba(r, g, b, number(keys::UI_ALPHA).unwrap_or(1.0))
+        } else if let Some(w) = number(keys::UI_WHITE) {
+            Color::new(
+                ColorSpace::Gray,
+                vec![w, number(keys::UI_ALPHA).unwrap_or(1.0)],
+            )
+        } else if let Some(c) = self.ascii_components(index, keys::NS_RGB, 3) {
+            Color::new(ColorSpace::Rgb, c)
+        } else if let Some(c) = self.ascii_components(index, keys::NS_WHITE, 1) {
+            Color::new(ColorSpace::Gray, c)
+        } else if let Some(c) = self.ascii_components(index, keys::NS_CMYK, 4) {
+            Color::new(ColorSpace::Cmyk, c)
+        } else {
+            return None;
+        };
+        color.name = name;
+        Some(color)
+    }
+
+    /// Writes a color into an object at `index`.
+    ///
+    /// All color related values of the object, including the name of a system color,
+    /// are replaced with the new ones, other values are kept. Objects of `NS`-prefixed
+    /// classes get the AppKit encoding, others get the UIKit encoding along with
+    /// the AppKit one, the same way `UIColor` encodes itself.
+    ///
+    /// Returns an error if the index is out of bounds.
+    pub fn set_color(&mut self, index: usize, color: &Color) -> Result<(), Error> {
+        let Some(obj) = self.objects.get(index) else {
+            return Err(Error::out_of_bounds(
+                IndexKind::Object,
+                index as i64,
+                self.objects.len(),
+            ));
+        };
+        let is_appkit = self
+            .class_names
+            .get(obj.class_name_id().as_usize())
+            .is_some_and(|c| c.name().starts_with("NS"));
+
+        let mut new_values: Vec<(&str, ValueVariant)> = Vec::new();
+        let c = &color.components;
+        let alpha = color.alpha();
+        if !is_appkit {
+            new_values.push((
+                keys::UI_COLOR_COMPONENT_COUNT,
+                ValueVariant::Int8(c.len() as i8),
+            ));
+            match color.color_space {
+                ColorSpace::Rgb if c.len() >= 3 => {
+                    new_values.push((keys::UI_RED, ValueVariant::Float(c[0] as f32)));
+                    new_values.push((keys::UI_GREEN, ValueVariant::Float(c[1] as f32)));
+                    new_values.push((keys::UI_BLUE, ValueVariant::Float(c[2] as f32)));
+                    new_values.push((keys::UI_ALPHA, ValueVariant::Float(alpha as f32)));
+                }
+                ColorSpace::Gray if !c.is_empty() => {
+                    new_values.push((keys::UI_WHITE, ValueVariant::Float(c[0] as f32)));
+                    new_values.push((keys::UI_ALPHA, ValueVariant::Float(alpha as f32)));
+                }
+                _ => {}
+            }
+        }
+        let (color_space, key) = match color.color_space {
+            ColorSpace::Rgb => (1, keys::NS_RGB),
+            ColorSpace::Gray => (3, keys::NS_WHITE),
+            ColorSpace::Cmyk => (5, keys::NS_CMYK),
+        };
+        let mut ascii = c
+            .iter()
+            .map(|v| v.to_string())
+            .collect::<Vec<_>>()
+            .join(" ")
+            .into_bytes();
+        ascii.push(0);
+        new_values.push((keys::NS_COLOR_SPACE, ValueVariant::Int8(color_space)));
+        new_values.push((key, ValueVariant::Data(ascii)));
+        if let Some(name) = &color.name {
+            let key = if is_appkit {
+                keys::NS_COLOR_NAME
+            } else {
+                keys::UI_SYSTEM_COLOR_NAME
+            };
+            new_values.push((key, ValueVariant::Data(name.as_bytes().to_vec())));
+        }
+
+        let mut values: Vec<Value> = self
+            .object_values(obj)
+            .iter()
+            .filter(|v| {
+                self.keys
+                    .get(v.key_id().as_usize())
+                    .is_none_or(|k| !COLOR_KEYS.contains(&k.as_str()))
+            })
+            .cloned()
+            .collect();
+        for (key, value) in new_values {
+            let key_index = self.intern_key(key);
+            values.push(Value::new(key_index, value));
+        }
+        self.replace_object_values(index, values)?;
+        Ok(())
+    }
+
+    fn ascii_components(&self, index: usize, key: &str, count: usize) -> Option<Vec<f64>> {
+        let ValueVariant::Data(data) = self.object_value(index, key)?.value() else {
+            return None;
+        };
+        parse_ascii_components(data, count)
+    }
+}
+
+/// Parses space separated ASCII components, like the ones of `NSRGB`.
+/// Alpha is optional and defaults to 1.
+fn parse_ascii_components(data: &[u8], count: usize) -> Option<Vec<f64>> {
+    let text = std::str::from_utf8(data).ok()?.trim_end_matches('\0');
+    let mut components = text
+        .split_ascii_whitespace()
+        .map(|c| c.parse().ok())
+        .collect::<Option<Vec<f64>>>()?;
+    if components.len() == count {
+        components.push(1.0);
+    }
+    (components.len() == count + 1).then_some(components)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ClassName, Color, ColorSpace, NIBArchive, Object, Value, ValueVariant};
+
+    fn archive(class_name: &str, values: Vec<(&str, ValueVariant)>) -> NIBArchive {
+        NIBArchive::new(
+            vec![Object::new(0, 0, values.len() as i32)],
+            values.iter().map(|(key, _)| key.to_string()).collect(),
+            values
+                .into_iter()
+                .enumerate()
+                .map(|(i, (_, value))| Value::new(i as i32, value))
+                .collect(),
+            vec![ClassName::with_fallbacks(class_name.into(), Vec::new())],
+        )
+        .unwrap()
+    }
+
+    fn roundtrip(archive: &mut NIBArchive, color: &Color) -> Color {
+        archive.set_color(0, color).unwrap();
+        let bytes = archive.to_bytes();
+        NIBArchive::from_bytes(bytes).unwrap().color(0).unwrap()
+    }
+
+    #[test]
+    fn rgb() {
+        let mut archive = archive(
+            "UIColor",
+            vec![
+                ("UIRed", ValueVariant::Float(1.0)),
+                ("UIGreen", ValueVariant::Float(0.0)),
+                ("UIBlue", ValueVariant::Float(0.0)),
+            ],
+        );
+        assert_eq!(archive.color(0), Some(Color::rgba(1.0, 0.0, 0.0, 1.0)));
+        let color = Color::rgba(0.25, 0.5, 0.75, 0.5);
+        assert_eq!(roundtrip(&mut archive, &color), color);
+    }
+
+    #[test]
+    fn gray() {
+        let mut archive = archive(
+            "NSColor",
+            vec![("NSWhite", ValueVariant::Data(b"1\0".to_vec()))],
+        );
+        let decoded = archive.color(0).unwrap();
+        assert_eq!(decoded.color_space(), ColorSpace::Gray);
+        assert_eq!(decoded.components(), [1.0, 1.0]);
+        let color = Color::new(ColorSpace::Gray, vec![0.5, 0.25]);
+        assert_eq!(roundtrip(&mut archive, &color), color);
+    }
+
+    #[test]
+    fn named() {
+        let mut archive = archive(
+            "UIColor",
+            vec![
+                ("UIWhite", ValueVariant::Float(0.0)),
+                (
+                    "UISystemColorName",
+                    ValueVariant::Data(b"labelColor".to_vec()),
+                ),
+            ],
+        );
+        assert_eq!(archive.color(0).unwrap().name(), Some("labelColor"));
+
+        let mut color = Color::rgba(0.0, 0.5, 1.0, 1.0);
+        color.set_name(Some("systemBlueColor".to_string()));
+        assert_eq!(roundtrip(&mut archive, &color), color);
+
+        let color = Color::rgba(0.0, 0.5, 1.0, 1.0);
+        assert_eq!(roundtrip(&mut archive, &color).name(), None);
+    }
+}
@@ -0,0 +1,42 @@
+use crate::{Error, NIBArchive};
+
+#[cfg(feature = "msgpack")]
+impl NIBArchive {
+    /// Encodes the given archive with MessagePack.
+    ///
+    /// Unlike the NIB Archive encoding, the result also preserves the format
+    /// and the coder versions as is.
+    pub fn to_msgpack(&self) -> Result<Vec<u8>, Error> {
+        rmp_serde::to_vec(self).map_err(|e| Error::IOError(std::io::Error::other(e)))
+    }
+
+    /// Decodes an archive previously encoded with [NIBArchive::to_msgpack()].
+    ///
+    /// This method **does not** check that the decoded elements reference elements
+    /// that are in bounds, same as [NIBArchive::new_unchecked()].
+    pub fn from_msgpack<B: AsRef<[u8]>>(bytes: B) -> Result<Self, Error> {
+        rmp_serde::from_slice(bytes.as_ref()).map_err(|e| Error::FormatError(e.to_string()))
+    }
+}
+
+#[cfg(feature = "cbor")]
+impl NIBArchive {
+    /// Encodes the given archive with CBOR.
+    ///
+    /// Unlike the NIB Archive encoding, the result also preserves the format
+    /// and the coder versions as is.
+    pub fn to_cbor(&self) -> Result<Vec<u8>, Error> {
+        let mut bytes = Vec::new();
+        ciborium::into_writer(self, &mut bytes)
+            .map_err(|e| Error::IOError(std::io::Error::other(e.to_string())))?;
+        Ok(bytes)
+    }
+
+    /// Decodes an archive previously encoded with [NIBArchive::to_cbor()].
+    ///
+    /// This method **does not** check that the decoded elements reference elements
+    /// that are in bounds, same as [NIBArchive::new_unchecked()].
+    pub fn from_cbor<B: AsRef<[u8]>>(bytes: B) -> Result<Self, Error> {
+        ciborium::from_reader(bytes.as_ref()).map_err(|e| Error::FormatError(e.to_string()))
+    }
+}
@@ -0,0 +1,196 @@
+use crate::{
+    consts::{classes, keys},
+    NIBArchive, ValueVariant,
+};
+use std::fmt;
+
+/// An attribute of a [LayoutConstraint]. Mirrors `NSLayoutAttribute`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LayoutAttribute {
+    NotAnAttribute,
+    Left,
+    Right,
+    Top,
+    Bottom,
+    Leading,
+    Trailing,
+    Width,
+    Height,
+    CenterX,
+    CenterY,
+    LastBaseline,
+    FirstBaseline,
+    LeftMargin,
+    RightMargin,
+    TopMargin,
+    BottomMargin,
+    LeadingMargin,
+    TrailingMargin,
+    CenterXWithinMargins,
+    CenterYWithinMargins,
+    /// An attribute this crate doesn't know about.
+    Other(i64),
+}
+
+impl LayoutAttribute {
+    const NAMES: [(LayoutAttribute, &'static str); 21] = [
+        (Self::NotAnAttribute, "notAnAttribute"),
+        (Self::Left, "left"),
+        (Self::Right, "right"),
+        (Self::Top, "top"),
+        (Self::Bottom, "bottom"),
+        (Self::Leading, "leading"),
+        (Self::Trailing, "trailing"),
+        (Self::Width, "width"),
+        (Self::Height, "height"),
+        (Self::CenterX, "centerX"),
+        (Self::CenterY, "centerY"),
+        (Self::LastBaseline, "lastBaseline"),
+        (Self::FirstBaseline, "firstBaseline"),
+        (Self::LeftMargin, "leftMargin"),
+        (Self::RightMargin, "rightMargin"),
+        (Self::TopMargin, "topMargin"),
+        (Self::BottomMargin, "bottomMargin"),
+        (Self::LeadingMargin, "leadingMargin"),
+        (Self::TrailingMargin, "trailingMargin"),
+        (Self::CenterXWithinMargins, "centerXWithinMargins"),
+        (Self::CenterYWithinMargins, "centerYWithinMargins"),
+    ];
+
+    /// Converts a raw `NSLayoutAttribute` value.
+    pub fn from_raw(value: i64) -> Self {
+        usize::try_from(value)
+            .ok()
+            .and_then(|i| Self::NAMES.get(i))
+            .map_or(Self::Other(value), |(attr, _)| *attr)
+    }
+
+    /// Returns a raw `NSLayoutAttribute` value.
+    pub fn to_raw(self) -> i64 {
+        match self {
+            Self::Other(v) => v,
+            attr => Self::NAMES.iter().position(|(a, _)| *a == attr).unwrap() as i64,
+        }
+    }
+}
+
+impl fmt::Display for LayoutAttribute {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Other(v) => write!(f, "attribute{v}"),
+            attr => write!(f, "{}", Self::NAMES[attr.to_raw() as usize].1),
+        }
+    }
+}
+
+/// A relation of a [LayoutConstraint]. Mirrors `NSLayoutRelation`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LayoutRelation {
+    LessThanOrEqual,
+    Equal,
+    GreaterThanOrEqual,
+}
+
+impl fmt::Display for LayoutRelation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::LessThanOrEqual => write!(f, "<="),
+            Self::Equal => write!(f, "=="),
+            Self::GreaterThanOrEqual => write!(f, ">="),
+        }
+    }
+}
+
+/// An Auto Layout constraint decoded from an `NSLayoutConstraint` object.
+///
+/// Items are indeces of the constrained objects. Values missing from the archive get
+/// the same defaults `NSLayoutConstraint` uses.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LayoutConstraint {
+    /// Index of the constraint object itself.
+    pub index: usize,
+    pub first_item: Option<usize>,
+    pub first_attribute: LayoutAttribute,
+    pub relation: LayoutRelation,
+    pub second_item: Option<usize>,
+    pub second_attribute: LayoutAttribute,
+    pub multiplier: f64,
+    pub constant: f64,
+    pub priority: f64,
+    pub identifier: Option<String>,
+}
+
+/// Formats a constraint in a visual-format-like notation, e.g.
+/// `#3.leading == #1.leading + 16 @750`. Items are shown as object indeces,
+/// the default multiplier and priority are omitted.
+impl fmt::Display for LayoutConstraint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let item = |item: Option<usize>| item.map_or("nil".to_string(), |i| format!("#{i}"));
+        write!(
+            f,
+            "{}.{} {}",
+            item(self.first_item),
+            self.first_attribute,
+            self.relation
+        )?;
+        if self.second_item.is_some() && self.second_attribute != LayoutAttribute::NotAnAttribute {
+            write!(f, " {}.{}", item(self.second_item), self.second_attribute)?;
+            if self.multiplier != 1.0 {
+                write!(f, " * {}", self.multiplier)?;
+            }
+            if self.constant > 0.0 {
+                write!(f, " + {}", self.constant)?;
+            } else if self.constant < 0.0 {
+                write!(f, " - {}", -self.constant)?;
+            }
+        } else {
+            write!(f, " {}", self.constant)?;
+        }
+        if self.priority != 1000.0 {
+            write!(f, " @{}", self.priority)?;
+        }
+        Ok(())
+    }
+}
+
+impl NIBArchive {
+    /// Returns all Auto Layout constraints of the archive, i.e. objects of
+    /// `NSLayoutConstraint` class and its subclasses.
+    pub fn layout_constraints(&self) -> Vec<LayoutConstraint> {
+        (0..self.objects.len())
+            .filter(|i| self.is_kind_of(*i, classes::NS_LAYOUT_CONSTRAINT))
+            .map(|i| self.layout_constraint(i))
+            .collect()
+    }
+
+    fn layout_constraint(&self, index: usize) -> LayoutConstraint {
+        let value = |key| self.object_value(index, key).map(|v| v.value());
+        let item = |key| match value(key)? {
+            ValueVariant::ObjectRef(r) if (*r as usize) < self.objects.len() => Some(*r as usize),
+            _ => None,
+        };
+        let attribute = |key| {
+            value(key)
+                .and_then(ValueVariant::as_i64)
+                .map_or(LayoutAttribute::NotAnAttribute, LayoutAttribute::from_raw)
+        };
+        let number = |key, default| value(key).and_then(ValueVariant::as_f64).unwrap_or(default);
+        let relation = match value(keys::NS_RELATION).and_then(ValueVariant::as_i64) {
+            Some(r) if r < 0 => LayoutRelation::LessThanOrEqual,
+            Some(r) if r > 0 => LayoutRelation::GreaterThanOrEqual,
+            _ => LayoutRelation::Equal,
+        };
+        LayoutConstraint {
+            index,
+            first_item: item(keys::NS_FIRST_ITEM),
+            first_attribute: attribute(keys::NS_FIRST_ATTRIBUTE),
+            relation,
+            second_item: item(keys::NS_SECOND_ITEM),
+            second_attribute: attribute(keys::NS_SECOND_ATTRIBUTE),
+            multiplier: number(keys::NS_MULTIPLIER, 1.0),
+            constant: number(keys::NS_CONSTANT, 0.0),
+            priority: number(keys::NS_PRIORITY, 1000.0),
+            identifier: value(keys::NS_LAYOUT_IDENTIFIER).and_then(ValueVariant::data_as_string),
+        }
+    }
+}
@@ -0,0 +1,485 @@
+//! Well-known keys and class names, and a registry of classes describing the keys
+//! their objects are expected to have.
+
+/// Frequently seen keys.
+pub mod keys {
+    /// Array of top level objects of a nib.
+    pub const UI_NIB_TOP_LEVEL_OBJECTS: &str = "UINibTopLevelObjectsKey";
+    /// Array of all objects of a nib.
+    pub const UI_NIB_OBJECTS: &str = "UINibObjectsKey";
+    /// Array of outlet and action connections.
+    pub const UI_NIB_CONNECTIONS: &str = "UINibConnectionsKey";
+    /// Array of windows that are visible at launch.
+    pub const UI_NIB_VISIBLE_WINDOWS: &str = "UINibVisibleWindowsKey";
+    /// Array of accessibility configurations.
+    pub const UI_NIB_ACCESSIBILITY_CONFIGURATIONS: &str = "UINibAccessibilityConfigurationsKey";
+    /// Array of runtime attributes.
+    pub const UI_NIB_KEY_VALUE_PAIRS: &str = "UINibKeyValuePairsKey";
+    /// Key of elements of arrays, sets and dictionaries.
+    pub const UI_NIB_ENCODER_EMPTY: &str = "UINibEncoderEmptyKey";
+    /// Bytes of an `NSString` or an `NSData`.
+    pub const NS_BYTES: &str = "NS.bytes";
+
+    pub const UI_SUBVIEWS: &str = "UISubviews";
+    pub const UI_SUPERVIEW: &str = "UISuperview";
+    pub const UI_FRAME: &str = "UIFrame";
+    pub const UI_BOUNDS: &str = "UIBounds";
+    pub const UI_CENTER: &str = "UICenter";
+    pub const UI_TAG: &str = "UITag";
+    pub const UI_HIDDEN: &str = "UIHidden";
+    pub const UI_TEXT: &str = "UIText";
+    pub const UI_AUTORESIZING_MASK: &str = "UIAutoresizingMask";
+    pub const UI_ACCESSIBILITY_IDENTIFIER: &str = "UIAccessibilityIdentifier";
+    pub const UI_ACCESSIBILITY_CONFIGURATION_VALUE: &str = "UIAccessibilityConfigurationValue";
+    pub const UI_ACCESSIBILITY_HINT: &str = "UIAccessibilityHint";
+    pub const UI_ACCESSIBILITY_LABEL: &str = "UIAccessibilityLabel";
+    pub const UI_ACCESSIBILITY_TRAITS: &str = "UIAccessibilityTraits";
+    pub const UI_ACCESSIBILITY_VALUE: &str = "UIAccessibilityValue";
+    pub const UI_ALPHA: &str = "UIAlpha";
+    pub const UI_BACKGROUND_COLOR: &str = "UIBackgroundColor";
+    pub const UI_BUTTON_STATEFUL_CONTENT: &str = "UIButtonStatefulContent";
+    pub const UI_CLASS_NAME: &str = "UIClassName";
+    pub const UI_CLIPS_TO_BOUNDS: &str = "UIClipsToBounds";
+    pub const UI_CONTENT_HUGGING_PRIORITY: &str = "UIContentHuggingPriority";
+    pub const UI_CONTENT_MODE: &str = "UIContentMode";
+    pub const UI_CONTENT_SCALE_FACTOR: &str = "UIContentScaleFactor";
+    pub const UI_CORNER_RADIUS: &str = "UICornerRadius";
+    pub const UI_DESTINATION_VIEW_CONTROLLER_IDENTIFIER: &str =
+        "UIDestinationViewControllerIdentifier";
+    pub const UI_ENABLED: &str = "UIEnabled";
+    pub const UI_IDENTIFIER: &str = "UIIdentifier";
+    pub const UI_IMAGE: &str = "UIImage";
+    pub const UI_IMAGE_DATA: &str = "UIImageData";
+    pub const UI_IS_ACCESSIBILITY_ELEMENT: &str = "UIIsAccessibilityElement";
+    pub const UI_LAYER: &str = "UILayer";
+    pub const UI_MULTIPLE_TOUCH_ENABLED: &str = "UIMultipleTouchEnabled";
+    pub const UI_NIB_NAME: &str = "UINibName";
+    pub const UI_NUMBER_OF_LINES: &str = "UINumberOfLines";
+    pub const UI_OPAQUE: &str = "UIOpaque";
+    pub const UI_ORIGINAL_CLASS_NAME: &str = "UIOriginalClassName";
+    pub const UI_RESOURCE_DATA: &str = "UIResourceData";
+    pub const UI_RESOURCE_NAME: &str = "UIResourceName";
+    pub const UI_RESTORATION_IDENTIFIER: &str = "UIRestorationIdentifier";
+    pub const UI_SEGUE_CLASS_NAME: &str = "UISegueClassName";
+    pub const UI_SEMANTIC_CONTENT_ATTRIBUTE: &str = "UISemanticContentAttribute";
+    pub const UI_STORYBOARD_IDENTIFIER: &str = "UIStoryboardIdentifier";
+    pub const UI_TEXT_COLOR: &str = "UITextColor";
+    pub const UI_TINT_COLOR: &str = "UITintColor";
+    pub const UI_TITLE: &str = "UITitle";
+    pub const UI_USER_INTERACTION_DISABLED: &str = "UIUserInteractionDisabled";
+    /// View of a view controller.
+    pub const UI_VIEW: &str = "UIView";
+    pub const UI_PLACEHOLDER: &str = "UIPlaceholder";
+    pub const UI_PROMPT: &str = "UIPrompt";
+    pub const UI_LABEL: &str = "UILabel";
+    pub const UI_SOURCE: &str = "UISource";
+    pub const UI_DESTINATION: &str = "UIDestination";
+    pub const UI_CONTENT_OFFSET: &str = "UIContentOffset";
+    pub const UI_CONTENT_SIZE: &str = "UIContentSize";
+    pub const UI_COLOR_COMPONENT_COUNT: &str = "UIColorComponentCount";
+    pub const UI_RED: &str = "UIRed";
+    pub const UI_GREEN: &str = "UIGreen";
+    pub const UI_BLUE: &str = "UIBlue";
+    pub const UI_WHITE: &str = "UIWhite";
+    pub const UI_SYSTEM_COLOR_NAME: &str = "UISystemColorName";
+    pub const UI_OBJECT: &str = "UIObject";
+    pub const UI_KEY_PATH: &str = "UIKeyPath";
+    pub const UI_VALUE: &str = "UIValue";
+    pub const UI_ACCESSIBILITY_CONFIGURATION_OBJECT: &str = "UIAccessibilityConfigurationObject";
+    pub const UI_ACCESSIBILITY_CONFIGURATION_KEY_PATH: &str = "UIAccessibilityConfigurationKeyPath";
+
+    pub const NS_SUBVIEWS: &str = "NSSubviews";
+    pub const NS_SUPERVIEW: &str = "NSSuperview";
+    pub const NS_FRAME: &str = "NSFrame";
+    pub const NS_FRAME_SIZE: &str = "NSFrameSize";
+    pub const NS_TAG: &str = "NSTag";
+    pub const NS_V_FLAGS: &str = "NSvFlags";
+    pub const NS_CONTENTS: &str = "NSContents";
+    pub const NS_CELL: &str = "NSCell";
+    pub const NS_ACCESSIBILITY_IDENTIFIER: &str = "NSAccessibilityIdentifier";
+    pub const NS_CLASS_NAME: &str = "NSClassName";
+    pub const NS_ORIGINAL_CLASS_NAME: &str = "NSOriginalClassName";
+    pub const NS_TITLE: &str = "NSTitle";
+    pub const NS_ALTERNATE_CONTENTS: &str = "NSAlternateContents";
+    pub const NS_PLACEHOLDER_STRING: &str = "NSPlaceholderString";
+    pub const NS_TOOL_TIP: &str = "NSToolTip";
+    pub const NS_LABEL: &str = "NSLabel";
+    pub const NS_SOURCE: &str = "NSSource";
+    pub const NS_DESTINATION: &str = "NSDestination";
+    pub const NS_BOUNDS: &str = "NSBounds";
+    pub const NS_ORIGIN: &str = "NSOrigin";
+    pub const NS_ENABLED: &str = "NSEnabled";
+    pub const NS_KEY_EQUIVALENT: &str = "NSKeyEquivalent";
+    pub const NS_WINDOW_TITLE: &str = "NSWindowTitle";
+    pub const NS_WINDOW_CLASS: &str = "NSWindowClass";
+    pub const NS_WINDOW_RECT: &str = "NSWindowRect";
+    pub const NS_WINDOW_STYLE_MASK: &str = "NSWindowStyleMask";
+    pub const NS_WINDOW_VIEW: &str = "NSWindowView";
+    pub const NS_MIN_SIZE: &str = "NSMinSize";
+    pub const NS_MAX_SIZE: &str = "NSMaxSize";
+    pub const NS_FRAME_AUTOSAVE_NAME: &str = "NSFrameAutosaveName";
+    pub const NS_NIB_NAME: &str = "NSNibName";
+    /// View of a view controller.
+    pub const NS_VIEW: &str = "NSView";
+    pub const NS_COLOR_SPACE: &str = "NSColorSpace";
+    pub const NS_RGB: &str = "NSRGB";
+    pub const NS_WHITE: &str = "NSWhite";
+    pub const NS_CMYK: &str = "NSCMYK";
+    pub const NS_COLOR_NAME: &str = "NSColorName";
+    pub const NS_OBJECT: &str = "NSObject";
+    pub const NS_KEY_PATHS: &str = "NSKeyPaths";
+    pub const NS_VALUES: &str = "NSValues";
+    pub const NS_FIRST_ITEM: &str = "NSFirstItem";
+    pub const NS_FIRST_ATTRIBUTE: &str = "NSFirstAttribute";
+    pub const NS_SECOND_ITEM: &str = "NSSecondItem";
+    pub const NS_SECOND_ATTRIBUTE: &str = "NSSecondAttribute";
+    pub const NS_RELATION: &str = "NSRelation";
+    pub const NS_MULTIPLIER: &str = "NSMultiplier";
+    pub const NS_CONSTANT: &str = "NSConstant";
+    pub const NS_PRIORITY: &str = "NSPriority";
+    pub const NS_LAYOUT_IDENTIFIER: &str = "NSLayoutIdentifier";
+
+    /// Elements of an `NSArray` or `NSSet` of a keyed archive.
+    pub const NS_OBJECTS: &str = "NS.objects";
+    /// Keys of an `NSDictionary` of a keyed archive.
+    pub const NS_KEYS: &str = "NS.keys";
+    /// Contents of an `NSString` of a keyed archive.
+    pub const NS_STRING: &str = "NS.string";
+
+    /// UIKit and AppKit keys of frames of views.
+    pub const FRAME: &[&str] = &[UI_FRAME, NS_FRAME];
+    /// UIKit and AppKit keys of subviews of views.
+    pub const SUBVIEWS: &[&str] = &[UI_SUBVIEWS, NS_SUBVIEWS];
+
+    /// Keys of user-visible text, i.e. strings that are translated.
+    pub const LOCALIZABLE: &[&str] = &[
+        UI_TEXT,
+        UI_TITLE,
+        UI_PLACEHOLDER,
+        UI_PROMPT,
+        UI_ACCESSIBILITY_LABEL,
+        UI_ACCESSIBILITY_HINT,
+        UI_ACCESSIBILITY_VALUE,
+        NS_CONTENTS,
+        NS_TITLE,
+        NS_ALTERNATE_CONTENTS,
+        NS_PLACEHOLDER_STRING,
+        NS_TOOL_TIP,
+    ];
+}
+
+/// Frequently seen class names.
+pub mod classes {
+    pub const NS_OBJECT: &str = "NSObject";
+    pub const NS_ARRAY: &str = "NSArray";
+    pub const NS_STRING: &str = "NSString";
+    pub const NS_MUTABLE_ARRAY: &str = "NSMutableArray";
+    pub const NS_SET: &str = "NSSet";
+    pub const NS_MUTABLE_SET: &str = "NSMutableSet";
+    pub const NS_ORDERED_SET: &str = "NSOrderedSet";
+    pub const NS_MUTABLE_ORDERED_SET: &str = "NSMutableOrderedSet";
+    pub const NS_DICTIONARY: &str = "NSDictionary";
+    pub const NS_MUTABLE_DICTIONARY: &str = "NSMutableDictionary";
+
+    pub const UI_VIEW: &str = "UIView";
+    pub const UI_CONTROL: &str = "UIControl";
+    pub const UI_LABEL: &str = "UILabel";
+    pub const UI_BUTTON: &str = "UIButton";
+    pub const UI_IMAGE_VIEW: &str = "UIImageView";
+    pub const UI_VIEW_CONTROLLER: &str = "UIViewController";
+    pub const UI_PROXY_OBJECT: &str = "UIProxyObject";
+    pub const UI_SCROLL_VIEW: &str = "UIScrollView";
+    pub const UI_STACK_VIEW: &str = "UIStackView";
+    pub const UI_STORYBOARD_SEGUE_TEMPLATE: &str = "UIStoryboardSegueTemplate";
+    pub const UI_SWITCH: &str = "UISwitch";
+    pub const UI_TEXT_FIELD: &str = "UITextField";
+    pub const UI_TEXT_VIEW: &str = "UITextView";
+    pub const UI_TABLE_VIEW: &str = "UITableView";
+    pub const UI_COLLECTION_VIEW: &str = "UICollectionView";
+    pub const UI_SLIDER: &str = "UISlider";
+    pub const UI_ACTIVITY_INDICATOR_VIEW: &str = "UIActivityIndicatorView";
+    pub const UI_COLOR: &str = "UIColor";
+    pub const UI_NIB_KEY_VALUE_PAIR: &str = "UINibKeyValuePair";
+    pub const UI_ACCESSIBILITY_CONFIGURATION: &str = "UIAccessibilityConfiguration";
+
+    pub const NS_VIEW: &str = "NSView";
+    pub const NS_CONTROL: &str = "NSControl";
+    pub const NS_BUTTON: &str = "NSButton";
+    pub const NS_TEXT_FIELD: &str = "NSTextField";
+    pub const NS_WINDOW_TEMPLATE: &str = "NSWindowTemplate";
+    pub const NS_VIEW_CONTROLLER: &str = "NSViewController";
+    pub const NS_IMAGE_VIEW: &str = "NSImageView";
+    pub const NS_SCROLL_VIEW: &str = "NSScrollView";
+    pub const NS_STACK_VIEW: &str = "NSStackView";
+    pub const NS_COLOR: &str = "NSColor";
+    pub const NS_LAYOUT_CONSTRAINT: &str = "NSLayoutConstraint";
+    pub const NS_IB_USER_DEFINED_RUNTIME_ATTRIBUTES_CONNECTOR: &str =
+        "NSIBUserDefinedRuntimeAttributesConnector";
+
+    pub const UI_RUNTIME_CONNECTION: &str = "UIRuntimeConnection";
+    pub const NS_NIB_CONNECTOR: &str = "NSNibConnector";
+}
+
+/// A class known to the registry.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ClassInfo {
+    pub name: &'static str,
+    pub superclass: Option<&'static str>,
+    /// Keys objects of the class may have, besides the ones of its superclasses.
+    pub keys: &'static [&'static str],
+}
+
+impl ClassInfo {
+    /// Returns the registered superclass.
+    pub fn superclass_info(&self) -> Option<&'static ClassInfo> {
+        self.superclass.and_then(class_info)
+    }
+
+    /// Returns `true` if the class is `name` or inherits from it.
+    pub fn is_kind_of(&self, name: &str) -> bool {
+        self.name == name || self.superclass_info().is_some_and(|s| s.is_kind_of(name))
+    }
+
+    /// Returns keys of the class along with keys of its superclasses.
+    pub fn expected_keys(&self) -> impl Iterator<Item = &'static str> + '_ {
+        core::iter::successors(Some(self), |c| c.superclass_info())
+            .flat_map(|c| c.keys.iter().copied())
+    }
+}
+
+/// Returns a registered class by its name.
+pub fn class_info(name: &str) -> Option<&'static ClassInfo> {
+    CLASSES.iter().find(|c| c.name == name)
+}
+
+macro_rules! class {
+    ($name:expr, $superclass:expr, [$($key:expr),* $(,)?]) => {
+        ClassInfo {
+            name: $name,
+            superclass: $superclass,
+            keys: &[$($key),*],
+        }
+    };
+}
+
+/// Registered classes of Foundation, UIKit and AppKit.
+pub static CLASSES: &[ClassInfo] = &[
+    // Foundation
+    class!("NSObject", None, []),
+    class!("NSArray", Some("NSObject"), ["UINibEncoderEmptyKey"]),
+    class!("NSMutableArray", Some("NSArray"), []),
+    class!("NSSet", Some("NSObject"), ["UINibEncoderEmptyKey"]),
+    class!("NSMutableSet", Some("NSSet"), []),
+    class!("NSDictionary", Some("NSObject"), ["UINibEncoderEmptyKey"]),
+    class!("NSMutableDictionary", Some("NSDictionary"), []),
+    class!("NSString", Some("NSObject"), ["NS.bytes"]),
+    class!("NSMutableString", Some("NSString"), []),
+    class!("NSData", Some("NSObject"), ["NS.bytes"]),
+    class!("NSNumber", Some("NSObject"), []),
+    // UIKit
+    class!("UIResponder", Some("NSObject"), []),
+    class!(
+        "UIView",
+        Some("UIResponder"),
+        [
+            "UIBounds",
+            "UICenter",
+            "UIFrame",
+            "UISubviews",
+            "UISuperview",
+            "UITag",
+            "UIHidden",
+            "UIAlpha",
+            "UIOpaque",
+            "UIBackgroundColor",
+            "UITintColor",
+            "UIAutoresizingMask",
+            "UIAutoresizeSubviews",
+            "UIClipsToBounds",
+            "UIContentMode",
+            "UIMultipleTouchEnabled",
+            "UIUserInteractionDisabled",
+            "UISemanticContentAttribute",
+            "UIAccessibilityIdentifier",
+            "UIAccessibilityLabel",
+            "UIAccessibilityHint",
+            "UIIsAccessibilityElement",
+            "UILayer",
+        ]
+    ),
+    class!(
+        "UIControl",
+        Some("UIView"),
+        [
+            "UIEnabled",
+            "UISelected",
+            "UIHighlighted",
+            "UIContentHorizontalAlignment",
+            "UIContentVerticalAlignment",
+        ]
+    ),
+    class!(
+        "UILabel",
+        Some("UIView"),
+        [
+            "UIText",
+            "UITextColor",
+            "UIFont",
+            "UINumberOfLines",
+            "UITextAlignment",
+            "UILineBreakMode",
+        ]
+    ),
+    class!(
+        "UIButton",
+        Some("UIControl"),
+        ["UIButtonStatefulContent", "UIButtonType", "UIFont"]
+    ),
+    class!(
+        "UIImageView",
+        Some("UIView"),
+        ["UIImage", "UIHighlightedImage"]
+    ),
+    class!(
+        "UITextField",
+        Some("UIControl"),
+        [
+            "UIText",
+            "UIPlaceholder",
+            "UITextColor",
+            "UIFont",
+            "UIBorderStyle"
+        ]
+    ),
+    class!("UISwitch", Some("UIControl"), ["UIOn"]),
+    class!(
+        "UISlider",
+        Some("UIControl"),
+        ["UIValue", "UIMinValue", "UIMaxValue"]
+    ),
+    class!(
+        "UIScrollView",
+        Some("UIView"),
+        ["UIContentSize", "UIContentOffset", "UIScrollDisabled"]
+    ),
+    class!("UITableView", Some("UIScrollView"), ["UIRowHeight"]),
+    class!("UICollectionView", Some("UIScrollView"), []),
+    class!("UITextView", Some("UIScrollView"), ["UIText", "UIFont"]),
+    class!("UIStackView", Some("UIView"), []),
+    class!("UIActivityIndicatorView", Some("UIView"), []),
+    class!(
+        "UIViewController",
+        Some("UIResponder"),
+        [
+            "UIView",
+            "UITitle",
+            "UINibName",
+            "UIStoryboardIdentifier",
+            "UIParentViewController",
+            "UIChildViewControllers",
+        ]
+    ),
+    class!(
+        "UIColor",
+        Some("NSObject"),
+        [
+            "UIColorComponentCount",
+            "UIRed",
+            "UIGreen",
+            "UIBlue",
+            "UIAlpha",
+            "UIWhite",
+            "UISystemColorName",
+        ]
+    ),
+    class!(
+        "UIRuntimeConnection",
+        Some("NSObject"),
+        ["UILabel", "UISource", "UIDestination"]
+    ),
+    class!("UIRuntimeOutletConnection", Some("UIRuntimeConnection"), []),
+    class!(
+        "UIRuntimeEventConnection",
+        Some("UIRuntimeConnection"),
+        ["UIEventMask"]
+    ),
+    class!(
+        "UIProxyObject",
+        Some("NSObject"),
+        ["UIProxiedObjectIdentifier"]
+    ),
+    // AppKit
+    class!(
+        "NSNibConnector",
+        Some("NSObject"),
+        ["NSLabel", "NSSource", "NSDestination"]
+    ),
+    class!("NSNibOutletConnector", Some("NSNibConnector"), []),
+    class!("NSNibControlConnector", Some("NSNibConnector"), []),
+    class!("NSResponder", Some("NSObject"), ["NSNextResponder"]),
+    class!(
+        "NSView",
+        Some("NSResponder"),
+        [
+            "NSFrame",
+            "NSFrameSize",
+            "NSSubviews",
+            "NSSuperview",
+            "NSvFlags",
+            "NSAccessibilityIdentifier",
+        ]
+    ),
+    class!(
+        "NSControl",
+        Some("NSView"),
+        ["NSCell", "NSEnabled", "NSTag"]
+    ),
+    class!("NSButton", Some("NSControl"), []),
+    class!("NSTextField", Some("NSControl"), []),
+    class!("NSImageView", Some("NSControl"), []),
+    class!("NSScrollView", Some("NSView"), []),
+    class!("NSStackView", Some("NSView"), []),
+    class!(
+        "NSCell",
+        Some("NSObject"),
+        ["NSContents", "NSCellFlags", "NSCellFlags2", "NSControlView"]
+    ),
+    class!("NSActionCell", Some("NSCell"), []),
+    class!(
+        "NSButtonCell",
+        Some("NSActionCell"),
+        ["NSKeyEquivalent", "NSButtonFlags", "NSButtonFlags2"]
+    ),
+    class!(
+        "NSTextFieldCell",
+        Some("NSActionCell"),
+        ["NSPlaceholderString", "NSTextColor", "NSBackgroundColor"]
+    ),
+    class!(
+        "NSWindowTemplate",
+        Some("NSObject"),
+        [
+            "NSWindowTitle",
+            "NSWindowClass",
+            "NSWindowRect",
+            "NSWindowStyleMask",
+            "NSWindowView",
+            "NSMinSize",
+            "NSMaxSize",
+            "NSFrameAutosaveName",
+            "NSWTFlags",
+            "NSScreenRect",
+        ]
+    ),
+    class!(
+        "NSViewController",
+        Some("NSResponder"),
+        ["NSView", "NSTitle", "NSNibName"]
+    ),
+    class!(
+        "NSColor",
+        Some("NSObject"),
+        ["NSColorSpace", "NSRGB", "NSWhite", "NSCMYK", "NSColorName"]
+    ),
+    class!("NSCustomObject", Some("NSObject"), ["NSClassName"]),
+];
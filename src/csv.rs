@@ -0,0 +1,84 @@
+use crate::{NIBArchive, ValueVariant};
+use std::fmt::Write;
+
+/// Formats values of all objects as CSV with a header row and the columns `object`,
+/// `class`, `key`, `type` and `value`.
+///
+/// Values are written in a readable form: data values that contain strings are written
+/// as strings, other data as hex bytes prefixed with `0x`, object references as `#` followed
+/// by the index of an object, nil values as an empty field. Values that don't belong to any
+/// object are skipped.
+pub fn nib_to_csv(archive: &NIBArchive) -> String {
+    let mut csv = String::from("object,class,key,type,value\r\n");
+    for (i, obj) in archive.objects().iter().enumerate() {
+        let class_name = archive
+            .class_name(obj.class_name_id())
+            .map_or("", |c| c.name());
+        for val in archive.object_values(obj) {
+            let key = archive.key(val.key_id()).unwrap_or_default();
+            let _ = write!(
+                csv,
+                "{i},{},{},{},{}\r\n",
+                field(class_name),
+                field(key),
+                val.value().type_name(),
+                field(&value_text(val.value()))
+            );
+        }
+    }
+    csv
+}
+
+fn value_text(value: &ValueVariant) -> String {
+    match value {
+        ValueVariant::Int8(v) => v.to_string(),
+        ValueVariant::Int16(v) => v.to_string(),
+        ValueVariant::Int32(v) => v.to_string(),
+        ValueVariant::Int64(v) => v.to_string(),
+        ValueVariant::Bool(v) => v.to_string(),
+        ValueVariant::Float(v) => v.to_string(),
+        ValueVariant::Double(v) => v.to_string(),
+        ValueVariant::Data(data) => value.data_as_string().unwrap_or_else(|| hex(data)),
+        ValueVariant::Nil => String::new(),
+        ValueVariant::ObjectRef(v) => format!("#{v}"),
+        ValueVariant::Unknown { raw, .. } => hex(raw),
+    }
+}
+
+fn hex(bytes: &[u8]) -> String {
+    let mut result = String::with_capacity(2 + bytes.len() * 2);
+    result.push_str("0x");
+    for byte in bytes {
+        let _ = write!(result, "{byte:02x}");
+    }
+    result
+}
+
+/// Quotes a field if it contains a comma, a quote or a line break, as RFC 4180 requires.
+fn field(s: &str) -> String {
+    if s.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::nib_to_csv;
+    use crate::{ClassName, NIBArchive, Object, Value, ValueVariant};
+
+    #[test]
+    fn invalid_value_range() {
+        let archive = NIBArchive::new_unchecked(
+            vec![Object::new(0, -1, 2), Object::new(0, i32::MAX, i32::MAX)],
+            vec!["UIText".into()],
+            vec![Value::new(0, ValueVariant::Nil)],
+            vec![ClassName::with_fallbacks("UILabel".into(), Vec::new())],
+        );
+        assert_eq!(nib_to_csv(&archive), "object,class,key,type,value\r\n");
+        assert_eq!(archive.object_encoded_size(0), None);
+        assert_eq!(archive.object_encoded_size(1), None);
+        assert_eq!(archive.size_report().classes.len(), 1);
+    }
+}
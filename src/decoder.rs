@@ -0,0 +1,299 @@
+use crate::{
+    codec::{decode_var_int_bounded, read_fixed, var_int_len, Endianness, FixedWidth},
+    io::{self, Cursor, ErrorKind, Read, Seek, SeekFrom},
+    DecodeOptions, DecodeWarning, Error, LimitKind, Quirks, Section, VarInt,
+};
+use alloc::{format, vec::Vec};
+
+/// Wraps a reader and keeps decoding options and found warnings.
+///
+/// The stream position is tracked by the decoder itself, so it's cheap to get
+/// for error reporting.
+pub(crate) struct Decoder<'a, R> {
+    reader: &'a mut R,
+    position: u64,
+    options: &'a DecodeOptions,
+    warnings: Vec<DecodeWarning>,
+    /// Total length of keys, class names and data values decoded so far.
+    data_length: u64,
+    /// End of the values section, the count of values following the current one
+    /// and the count of keys. Used to find the length of values of unknown types.
+    values_left: Option<(u64, u32, u32)>,
+    quirks: Quirks,
+}
+
+impl<'a, R: Read + Seek> Decoder<'a, R> {
+    pub fn new(reader: &'a mut R, options: &'a DecodeOptions) -> Result<Self, Error> {
+        Ok(Self {
+            position: reader.stream_position()?,
+            reader,
+            options,
+            warnings: Vec::new(),
+            data_length: 0,
+            values_left: None,
+            quirks: Quirks::for_versions(1, 9),
+        })
+    }
+
+    pub fn options(&self) -> &DecodeOptions {
+        self.options
+    }
+
+    /// Reports progress of decoding a section to the hook of the options, if any.
+    pub fn progress(&self, section: Section, done: u32, total: u32) {
+        if let Some(hook) = self.options.progress() {
+            hook.report(section, done.into(), total.into());
+        }
+    }
+
+    /// Sets quirks of the versions of the archive being decoded. Unknown versions are
+    /// reported as a warning.
+    pub fn set_quirks(&mut self, quirks: Quirks) {
+        if !quirks.is_known() {
+            self.warnings.push(DecodeWarning::UnknownVersion {
+                format_version: quirks.format_version(),
+                coder_version: quirks.coder_version(),
+            });
+        }
+        self.quirks = quirks;
+    }
+
+    /// Sets the end of the values section, the count of values following
+    /// the one that is about to be decoded and the count of keys.
+    pub fn set_values_left(&mut self, end: u64, count: u32, key_count: u32) {
+        self.values_left = Some((end, count, key_count));
+    }
+
+    pub fn into_warnings(self) -> Vec<DecodeWarning> {
+        self.warnings
+    }
+
+    pub fn position(&self) -> u64 {
+        self.position
+    }
+
+    pub fn seek(&mut self, position: u64) -> Result<(), Error> {
+        self.position = self.reader.seek(SeekFrom::Start(position))?;
+        Ok(())
+    }
+
+    pub fn u8(&mut self) -> Result<u8, Error> {
+        self.fixed()
+    }
+
+    pub fn fixed<T: FixedWidth>(&mut self) -> Result<T, Error> {
+        read_fixed(self, Endianness::Little)
+    }
+
+    pub fn var_int(&mut self) -> Result<VarInt, Error> {
+        let max_len = self.options.limits().max_var_int_bytes();
+        let offset = self.position();
+        let (value, len) = match decode_var_int_bounded(self, max_len) {
+            Err(Error::FormatError(_)) => return Err(Error::InvalidVarInt { offset }),
+            result => result?,
+        };
+        if len > var_int_len(value) && self.quirks.minimal_var_ints() {
+            if self.options.strict() {
+                return Err(Error::NonMinimalVarInt { offset });
+            }
+            self.warnings
+                .push(DecodeWarning::NonMinimalVarInt { offset });
+        }
+        Ok(value)
+    }
+
+    /// Decodes a variable integer that is used as an index or a count,
+    /// so it can't be negative.
+    pub fn index(&mut self, what: &'static str) -> Result<VarInt, Error> {
+        let offset = self.position();
+        let value = self.var_int()?;
+        if value < 0 {
+            return Err(Error::NegativeVarInt {
+                what,
+                value,
+                offset,
+            });
+        }
+        Ok(value)
+    }
+
+    /// Decodes a length of a key, a class name or a data value and checks it against limits.
+    pub fn length(&mut self) -> Result<usize, Error> {
+        let length = self.index("length")?;
+        let max = self.options.limits().max_data_length();
+        self.check_limit(LimitKind::DataLength, length as u64, max as u64)?;
+        self.data_length += length as u64;
+        self.check_size(self.data_length)?;
+        Ok(length as usize)
+    }
+
+    /// Checks a size of an archive, or a part of it, against limits.
+    pub fn check_size(&mut self, size: u64) -> Result<(), Error> {
+        let max = self.options.limits().max_total_size();
+        self.check_limit(LimitKind::TotalSize, size, max)
+    }
+
+    /// Checks a count of elements against limits.
+    pub fn check_count(&mut self, count: u32, limit: LimitKind) -> Result<(), Error> {
+        let max = self.options.limits().max_count();
+        self.check_limit(limit, count as u64, max as u64)
+    }
+
+    fn check_limit(&mut self, limit: LimitKind, value: u64, max: u64) -> Result<(), Error> {
+        if value > max {
+            return Err(Error::LimitExceeded {
+                limit,
+                value,
+                max,
+                offset: self.position,
+            });
+        }
+        Ok(())
+    }
+}
+
+/// Decoding of keys, class names and data values, which borrow from the decoded bytes.
+impl<'b> Decoder<'_, Cursor<&'b [u8]>> {
+    /// Returns `length` bytes at the current position and skips them.
+    pub fn take(&mut self, length: usize) -> Result<&'b [u8], Error> {
+        let bytes: &'b [u8] = self.reader.get_ref();
+        let start = self.position() as usize;
+        let end = start
+            .checked_add(length)
+            .filter(|end| *end <= bytes.len())
+            .ok_or_else(|| io::Error::from(ErrorKind::UnexpectedEof))?;
+        self.seek(end as u64)?;
+        Ok(&bytes[start..end])
+    }
+
+    /// Decodes a single key.
+    pub fn key(&mut self) -> Result<&'b str, Error> {
+        let offset = self.position();
+        let length = self.length()?;
+        validate_utf8(self.take(length)?).map_err(|_| Error::InvalidUtf8 {
+            section: Section::Keys,
+            offset,
+        })
+    }
+
+    /// Reads a payload of a value of an unknown type. Its length is the first one
+    /// that lets the following values have valid keys and end exactly at the end
+    /// of the values section.
+    ///
+    /// The search is bounded: the payload may be at most [Limits::max_data_length()] long
+    /// and at most [Limits::max_count()] values are decoded while trying its possible lengths.
+    ///
+    /// [Limits::max_data_length()]: crate::Limits::max_data_length()
+    /// [Limits::max_count()]: crate::Limits::max_count()
+    pub fn unknown_payload(&mut self) -> Result<&'b [u8], Error> {
+        let Some((end, count, key_count)) = self.values_left.take() else {
+            return Err(Error::FormatError(
+                "Can't find the length of a value of an unknown type".into(),
+            ));
+        };
+        let start = self.position();
+        let limits = self.options.limits();
+        let last = end.min(start.saturating_add(limits.max_data_length().into()));
+        let mut budget = u64::from(limits.max_count());
+        let (warnings, data_length) = (self.warnings.len(), self.data_length);
+        for payload_end in start..=last {
+            if budget == 0 {
+                break;
+            }
+            self.seek(payload_end)?;
+            let fits = (0..count).all(|_| {
+                budget = budget.saturating_sub(1);
+                crate::ValueRef::try_from_decoder(self)
+                    .is_ok_and(|val| (val.key_id().0 as u32) < key_count)
+            }) && self.position() == end;
+            self.warnings.truncate(warnings);
+            self.data_length = data_length;
+            if fits {
+                self.seek(start)?;
+                return self.take((payload_end - start) as usize);
+            }
+        }
+        Err(Error::FormatError(format!(
+            "Can't find the length of a value of an unknown type at offset {start}"
+        )))
+    }
+}
+
+/// Validates a UTF-8 string. On failure returns the position of the first invalid byte.
+#[cfg(feature = "simdutf8")]
+pub(crate) fn validate_utf8(bytes: &[u8]) -> Result<&str, usize> {
+    // The fast validator doesn't report positions, so the slow one is used to find it
+    simdutf8::basic::from_utf8(bytes)
+        .map_err(|_| core::str::from_utf8(bytes).map_or_else(|e| e.valid_up_to(), |_| 0))
+}
+
+#[cfg(not(feature = "simdutf8"))]
+pub(crate) fn validate_utf8(bytes: &[u8]) -> Result<&str, usize> {
+    core::str::from_utf8(bytes).map_err(|e| e.valid_up_to())
+}
+
+impl<R: Read> Read for Decoder<'_, R> {
+    fn read(&mut self, buf: &mut [u8]) -> crate::io::Result<usize> {
+        let read = self.reader.read(buf)?;
+        self.position += read as u64;
+        Ok(read)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        io::Cursor, ClassName, DecodeOptions, Limits, NIBArchive, Object, Value, ValueVariant,
+    };
+    use alloc::{string::ToString, vec, vec::Vec};
+
+    /// An archive whose first value has an unknown type and a payload that looks like
+    /// a run of nil values, so every other possible length of the payload has to be tried.
+    fn archive_with_unknown_value(nils: usize) -> Vec<u8> {
+        let payload = [0x80, crate::value::TYPE_NIL].repeat(nils);
+        let mut values = vec![Value::new(0, ValueVariant::Data(payload))];
+        values.extend((0..nils).map(|_| Value::new(0, ValueVariant::Nil)));
+        let archive = NIBArchive::new(
+            vec![Object::new(0, 0, values.len() as i32)],
+            vec!["UIValue".into()],
+            values,
+            vec![ClassName::with_fallbacks("NSObject".into(), Vec::new())],
+        )
+        .unwrap();
+        let mut bytes = archive.to_bytes();
+        let values_offset = u32::from_le_bytes(bytes[38..42].try_into().unwrap()) as usize;
+        bytes[values_offset + 1] = 0xEE;
+        bytes
+    }
+
+    fn decode(bytes: &[u8], limits: Limits) -> Result<NIBArchive, crate::Error> {
+        let mut options = DecodeOptions::new();
+        options.set_lenient_value_types(true);
+        options.set_limits(limits);
+        NIBArchive::from_reader_with_options(&mut Cursor::new(bytes), &options).map(|(a, _)| a)
+    }
+
+    #[test]
+    fn unknown_payload() {
+        let bytes = archive_with_unknown_value(32);
+        let archive = decode(&bytes, Limits::new()).unwrap();
+        assert!(matches!(
+            archive.values()[0].value(),
+            ValueVariant::Unknown { type_byte: 0xEE, raw } if raw.len() == 65
+        ));
+    }
+
+    #[test]
+    fn unknown_payload_budget() {
+        let bytes = archive_with_unknown_value(32);
+        let mut limits = Limits::new();
+        limits.set_max_count(33);
+        let err = decode(&bytes, limits.clone()).unwrap_err();
+        assert!(err.to_string().contains("unknown type"), "{err}");
+
+        limits.set_max_count(64);
+        limits.set_max_data_length(16);
+        let err = decode(&bytes, limits).unwrap_err();
+        assert!(err.to_string().contains("unknown type"), "{err}");
+    }
+}
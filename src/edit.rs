@@ -0,0 +1,747 @@
+use crate::{
+    preserve::unique_keys, ClassName, ClassNameIndex, Error, IndexKind, NIBArchive, Object, Value,
+    ValueIndex, ValueVariant, VarInt,
+};
+use std::{collections::HashMap, ops::Range};
+
+impl NIBArchive {
+    /// Removes an object at `index` along with its [values](Value) and returns them.
+    ///
+    /// Values' indeces of the following objects are shifted accordingly. Object references
+    /// pointing to the following objects are decremented and ones pointing to the removed
+    /// object are replaced with [ValueVariant::Nil].
+    ///
+    /// Returns an error if the index or the object's value range is out of bounds.
+    pub fn remove_object(&mut self, index: usize) -> Result<(Object, Vec<Value>), Error> {
+        let Some(obj) = self.objects.get(index) else {
+            return Err(Error::out_of_bounds(
+                IndexKind::Object,
+                index as i64,
+                self.objects.len(),
+            ));
+        };
+        let Range { start, end } = self.checked_value_range(obj)?;
+
+        let obj = self.objects.remove(index);
+        let values: Vec<Value> = self.values.drain(start..end).collect();
+        for other in &mut self.objects {
+            if other.first_value_id().as_usize() >= end {
+                other.set_values_index(other.first_value_id() - obj.value_count());
+            }
+        }
+        let index = index as u32;
+        for val in &mut self.values {
+            if let ValueVariant::ObjectRef(r) = val.value() {
+                if *r == index {
+                    val.set_value(ValueVariant::Nil);
+                } else if *r > index {
+                    val.set_value(ValueVariant::ObjectRef(r - 1));
+                }
+            }
+        }
+        Ok((obj, values))
+    }
+
+    /// Removes a single [value](Value) at `index` and returns it.
+    ///
+    /// The value count of the object owning the value is decremented and values' indeces
+    /// of the following objects are shifted accordingly.
+    ///
+    /// Returns an error if the index is out of bounds.
+    pub fn remove_value(&mut self, index: ValueIndex) -> Result<Value, Error> {
+        if index.as_usize() >= self.values.len() {
+            return Err(Error::out_of_bounds(
+                IndexKind::Value,
+                index.0 as i64,
+                self.values.len(),
+            ));
+        }
+        let value = self.values.remove(index.as_usize());
+        for obj in &mut self.objects {
+            if obj.first_value_id() > index {
+                obj.set_values_index(obj.first_value_id() - 1);
+            } else if obj.first_value_id().0 as i64 + obj.value_count() as i64 > index.0 as i64 {
+                obj.set_value_count(obj.value_count() - 1);
+            }
+        }
+        Ok(value)
+    }
+
+    /// Inserts a new object of a class with `class_name_index` and `values` at `index`,
+    /// shifting all objects after it.
+    ///
+    /// The values are placed right before the values of the object that previously
+    /// occupied `index`. Object references pointing to the shifted objects are incremented.
+    /// References inside of the given values aren't changed, so they have to be
+    /// relative to the archive after insertion.
+    ///
+    /// Returns an error if `index` is greater than the count of objects, or if the class name,
+    /// one of the keys or one of the object references is out of bounds.
+    pub fn insert_object(
+        &mut self,
+        index: usize,
+        class_name_index: VarInt,
+        values: Vec<Value>,
+    ) -> Result<(), Error> {
+        if index > self.objects.len() {
+            return Err(Error::out_of_bounds(
+                IndexKind::Object,
+                index as i64,
+                self.objects.len() + 1,
+            ));
+        }
+        if class_name_index < 0 || class_name_index as usize >= self.class_names.len() {
+            return Err(Error::out_of_bounds(
+                IndexKind::ClassName,
+                class_name_index as i64,
+                self.class_names.len(),
+            ));
+        }
+        for val in &values {
+            Self::check_key_index(val.key_id(), self.keys.len() as u32, None)?;
+            if let ValueVariant::ObjectRef(r) = val.value() {
+                if *r as usize > self.objects.len() {
+                    return Err(Error::out_of_bounds(
+                        IndexKind::Object,
+                        *r as i64,
+                        self.objects.len() + 1,
+                    ));
+                }
+            }
+        }
+
+        let position = match self.objects.get(index) {
+            Some(obj) => obj.first_value_id().as_usize(),
+            None => self.values.len(),
+        }
+        .min(self.values.len());
+        let count = values.len() as VarInt;
+        for other in &mut self.objects {
+            if other.first_value_id().as_usize() >= position {
+                other.set_values_index(other.first_value_id() + count);
+            }
+        }
+        let shifted_from = index as u32;
+        for val in &mut self.values {
+            if let ValueVariant::ObjectRef(r) = val.value() {
+                if *r >= shifted_from {
+                    val.set_value(ValueVariant::ObjectRef(r + 1));
+                }
+            }
+        }
+
+        self.values.splice(position..position, values);
+        self.objects.insert(
+            index,
+            Object::new(class_name_index, position as VarInt, count),
+        );
+        Ok(())
+    }
+
+    /// Appends a new object of a class with `class_name_index` and `values` to the end
+    /// of the archive and returns its index.
+    ///
+    /// Look at [NIBArchive::insert_object()] for more details.
+    pub fn append_object_with_values(
+        &mut self,
+        class_name_index: VarInt,
+        values: Vec<Value>,
+    ) -> Result<usize, Error> {
+        let index = self.objects.len();
+        self.insert_object(index, class_name_index, values)?;
+        Ok(index)
+    }
+
+    /// Replaces [values](Value) of an object at `index` and returns the old ones.
+    ///
+    /// Values' indeces of the following objects are shifted accordingly.
+    ///
+    /// Returns an error if the index or the object's value range is out of bounds, or if one
+    /// of the new values references a key that is out of bounds.
+    pub fn replace_object_values(
+        &mut self,
+        index: usize,
+        values: Vec<Value>,
+    ) -> Result<Vec<Value>, Error> {
+        let Some(obj) = self.objects.get(index) else {
+            return Err(Error::out_of_bounds(
+                IndexKind::Object,
+                index as i64,
+                self.objects.len(),
+            ));
+        };
+        let Range { start, end } = self.checked_value_range(obj)?;
+        for val in &values {
+            Self::check_key_index(val.key_id(), self.keys.len() as u32, None)?;
+        }
+
+        let diff = values.len() as VarInt - obj.value_count();
+        let count = values.len() as VarInt;
+        let old_values = self.values.splice(start..end, values).collect();
+        for (i, other) in self.objects.iter_mut().enumerate() {
+            if i != index && other.first_value_id().as_usize() >= end {
+                other.set_values_index(other.first_value_id() + diff);
+            }
+        }
+        self.objects[index].set_value_count(count);
+        Ok(old_values)
+    }
+
+    /// Returns the range of an object's values. Returns an error if it's invalid
+    /// or out of bounds.
+    fn checked_value_range(&self, obj: &Object) -> Result<Range<usize>, Error> {
+        match obj.value_range() {
+            Some(range) if range.end <= self.values.len() => Ok(range),
+            _ => {
+                let start = obj.first_value_id().0 as i64;
+                let index = if start < 0 {
+                    start
+                } else {
+                    start + obj.value_count() as i64 - 1
+                };
+                Err(Error::out_of_bounds(
+                    IndexKind::Value,
+                    index,
+                    self.values.len(),
+                ))
+            }
+        }
+    }
+
+    /// Returns the index of a given key, adding the key to the archive if it's not there yet.
+    pub(crate) fn intern_key(&mut self, key: &str) -> VarInt {
+        match self.keys.iter().position(|k| k == key) {
+            Some(i) => i as VarInt,
+            None => {
+                self.keys.push(key.to_string());
+                (self.keys.len() - 1) as VarInt
+            }
+        }
+    }
+
+    /// Brings the archive to a canonical form, so equal archives are always encoded into
+    /// identical bytes: duplicate keys and class names are merged, keys and class names
+    /// are sorted alphabetically. All indeces are remapped accordingly.
+    ///
+    /// If [value widths are minimized](NIBArchive::set_minimize_value_widths()), values
+    /// are replaced with their minimized forms as well, so e.g. `Int8(5)` and `Int64(5)`
+    /// become the same value. Otherwise value types are kept as is.
+    ///
+    /// Objects and values keep their order, since it's meaningful.
+    pub fn canonicalize(&mut self) {
+        self.dedupe_keys();
+        self.dedupe_class_names();
+        if self.minimize_value_widths {
+            for val in &mut self.values {
+                if let Some(value) = val.value().minimized() {
+                    val.set_value(value);
+                }
+            }
+        }
+
+        let mut order: Vec<usize> = (0..self.keys.len()).collect();
+        order.sort_by(|a, b| self.keys[*a].cmp(&self.keys[*b]));
+        let key_map = inverse_permutation(&order);
+        for val in &mut self.values {
+            if let Some(index) = key_map.get(val.key_id().as_usize()) {
+                val.set_key_index(*index);
+            }
+        }
+        self.keys = order.iter().map(|i| self.keys[*i].clone()).collect();
+
+        let mut order: Vec<usize> = (0..self.class_names.len()).collect();
+        order.sort_by(|a, b| self.class_names[*a].name().cmp(self.class_names[*b].name()));
+        let class_map = inverse_permutation(&order);
+        let remap = |i: ClassNameIndex| class_map.get(i.as_usize()).map_or(i, |i| (*i).into());
+        for obj in &mut self.objects {
+            obj.set_class_name_index(remap(obj.class_name_id()));
+        }
+        self.class_names = order
+            .iter()
+            .map(|i| {
+                let mut cls = self.class_names[*i].clone();
+                let fallbacks = cls.fallback_class_ids().iter().map(|i| remap(*i)).collect();
+                cls.set_fallback_class_ids(fallbacks);
+                cls
+            })
+            .collect();
+    }
+
+    /// Renames a key `old` to `new` and returns `true` if the key was found.
+    ///
+    /// If `new` is already a key of the archive, values of both keys are pointed
+    /// at the existing one.
+    pub fn rename_key(&mut self, old: &str, new: &str) -> bool {
+        let mut found = false;
+        for key in &mut self.keys {
+            if key == old {
+                *key = new.to_string();
+                found = true;
+            }
+        }
+        if found {
+            self.dedupe_keys();
+        }
+        found
+    }
+
+    /// Merges duplicate keys, keeping the first one of them.
+    pub(crate) fn dedupe_keys(&mut self) {
+        if let Some((keys, key_map)) = unique_keys(&self.keys) {
+            for val in &mut self.values {
+                if let Some(index) = key_map.get(val.key_id().as_usize()) {
+                    val.set_key_index(*index);
+                }
+            }
+            self.keys = keys;
+        }
+    }
+
+    /// Renames a class named `old` to `new` and returns `true` if the class was found.
+    ///
+    /// Look at [NIBArchive::rename_classes()] for more details.
+    pub fn rename_class(&mut self, old: &str, new: &str) -> bool {
+        let renames = HashMap::from([(old.to_string(), new.to_string())]);
+        self.rename_classes(&renames) > 0
+    }
+
+    /// Renames classes according to a map of old names to new ones and returns the count
+    /// of renamed class names. Both class names of objects and fallback classes are renamed.
+    ///
+    /// If a new name is already used by another class name, the two are merged into one:
+    /// objects and fallback lists are pointed at the first of them. Fallback lists are
+    /// cleared of duplicates and of references to the class itself.
+    pub fn rename_classes(&mut self, renames: &HashMap<String, String>) -> usize {
+        let mut renamed = 0;
+        for cls in &mut self.class_names {
+            if let Some(new) = renames.get(cls.name()) {
+                cls.set_name(new.clone());
+                renamed += 1;
+            }
+        }
+        if renamed > 0 {
+            self.dedupe_class_names();
+        }
+        renamed
+    }
+
+    /// Merges class names with the same name, keeping the first one of them.
+    fn dedupe_class_names(&mut self) {
+        let mut first_index: HashMap<&str, VarInt> = HashMap::new();
+        let mut class_map = Vec::with_capacity(self.class_names.len());
+        let mut unique = Vec::new();
+        for (i, cls) in self.class_names.iter().enumerate() {
+            let next_index = unique.len() as VarInt;
+            let index = *first_index.entry(cls.name()).or_insert_with(|| {
+                unique.push(i);
+                next_index
+            });
+            class_map.push(index);
+        }
+        if unique.len() == self.class_names.len() {
+            return;
+        }
+        let remap = |i: ClassNameIndex| class_map.get(i.as_usize()).map_or(i, |i| (*i).into());
+
+        let mut class_names: Vec<ClassName> = unique
+            .into_iter()
+            .map(|i| self.class_names[i].clone())
+            .collect();
+        for (i, cls) in class_names.iter_mut().enumerate() {
+            let mut fallbacks = Vec::new();
+            for fallback in cls.fallback_class_ids() {
+                let fallback = remap(*fallback);
+                if fallback != i as VarInt && !fallbacks.contains(&fallback) {
+                    fallbacks.push(fallback);
+                }
+            }
+            cls.set_fallback_class_ids(fallbacks);
+        }
+        for obj in &mut self.objects {
+            obj.set_class_name_index(remap(obj.class_name_id()));
+        }
+        self.class_names = class_names;
+    }
+
+    /// Appends objects, keys, values and class names of `other` archive to the current one.
+    ///
+    /// All indeces and object references of `other` are remapped. Keys that are
+    /// already present in the current archive are reused, and so are class names with
+    /// the same name and the same fallback classes.
+    ///
+    /// Returns an error if one of the elements of `other` references an element that is out
+    /// of bounds, i.e. a value range of an object, a class name, a key or an object reference,
+    /// or if indeces of the merged archive would overflow. The current archive is left
+    /// untouched in this case.
+    pub fn merge(&mut self, other: NIBArchive) -> Result<(), Error> {
+        let (objects, keys, values, class_names) = other.into_inner();
+        let overflow = || Error::FormatError("Merged archive indeces overflow".into());
+        let objects_offset = u32::try_from(self.objects.len()).map_err(|_| overflow())?;
+        let values_offset = VarInt::try_from(self.values.len()).map_err(|_| overflow())?;
+        let value_count = u32::try_from(values.len()).map_err(|_| overflow())?;
+        let class_name_count = u32::try_from(class_names.len()).map_err(|_| overflow())?;
+        for obj in &objects {
+            Self::check_object(obj, value_count, class_name_count, None)?;
+            if obj.first_value_id().checked_add(values_offset).is_none() {
+                return Err(overflow());
+            }
+        }
+        for val in &values {
+            if val.key_id() < 0.into() || val.key_id().as_usize() >= keys.len() {
+                return Err(Error::out_of_bounds(
+                    IndexKind::Key,
+                    val.key_id().0 as i64,
+                    keys.len(),
+                ));
+            }
+            if let ValueVariant::ObjectRef(r) = val.value() {
+                if *r as usize >= objects.len() {
+                    return Err(Error::out_of_bounds(
+                        IndexKind::Object,
+                        *r as i64,
+                        objects.len(),
+                    ));
+                }
+                if r.checked_add(objects_offset).is_none() {
+                    return Err(overflow());
+                }
+            }
+        }
+        for cls in &class_names {
+            if let Some(index) = cls
+                .fallback_class_ids()
+                .iter()
+                .find(|i| i.0 < 0 || i.as_usize() >= class_names.len())
+            {
+                return Err(Error::out_of_bounds(
+                    IndexKind::ClassName,
+                    index.0 as i64,
+                    class_names.len(),
+                ));
+            }
+        }
+
+        let mut existing_keys: HashMap<String, VarInt> = HashMap::new();
+        for (i, key) in self.keys.iter().enumerate() {
+            existing_keys.entry(key.clone()).or_insert(i as VarInt);
+        }
+        let mut key_map = Vec::with_capacity(keys.len());
+        let mut new_keys = Vec::new();
+        for key in keys {
+            let next_index = (self.keys.len() + new_keys.len()) as VarInt;
+            let index = *existing_keys.entry(key.clone()).or_insert_with(|| {
+                new_keys.push(key);
+                next_index
+            });
+            key_map.push(index);
+        }
+
+        let class_signature = |cls: &ClassName, all: &[ClassName]| {
+            let fallbacks: Vec<Option<String>> = cls
+                .fallback_class_ids()
+                .iter()
+                .map(|i| all.get(i.as_usize()).map(|c| c.name().to_string()))
+                .collect();
+            (cls.name().to_string(), fallbacks)
+        };
+        let mut existing_classes = HashMap::new();
+        for (i, cls) in self.class_names.iter().enumerate() {
+            existing_classes
+                .entry(class_signature(cls, &self.class_names))
+                .or_insert(i as VarInt);
+        }
+        let mut class_map = Vec::with_capacity(class_names.len());
+        let mut new_classes = Vec::new();
+        for cls in &class_names {
+            let next_index = (self.class_names.len() + new_classes.len()) as VarInt;
+            let index = *existing_classes
+                .entry(class_signature(cls, &class_names))
+                .or_insert_with(|| {
+                    new_classes.push(cls.clone());
+                    next_index
+                });
+            class_map.push(index);
+        }
+        for cls in &mut new_classes {
+            let fallbacks = cls
+                .fallback_class_ids()
+                .iter()
+                .map(|i| class_map[i.as_usize()].into())
+                .collect();
+            cls.set_fallback_class_ids(fallbacks);
+        }
+
+        self.keys.append(&mut new_keys);
+        self.class_names.append(&mut new_classes);
+        for mut obj in objects {
+            obj.set_class_name_index(class_map[obj.class_name_id().as_usize()]);
+            obj.set_values_index(obj.first_value_id() + values_offset);
+            self.objects.push(obj);
+        }
+        for mut val in values {
+            val.set_key_index(key_map[val.key_id().as_usize()]);
+            if let ValueVariant::ObjectRef(r) = val.value() {
+                val.set_value(ValueVariant::ObjectRef(r + objects_offset));
+            }
+            self.values.push(val);
+        }
+        Ok(())
+    }
+}
+
+/// Maps old indeces to new ones given new positions of old elements.
+fn inverse_permutation(order: &[usize]) -> Vec<VarInt> {
+    let mut map = vec![0; order.len()];
+    for (new, old) in order.iter().enumerate() {
+        map[*old] = new as VarInt;
+    }
+    map
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ClassName, Error, IndexKind, NIBArchive, Object, Value, ValueVariant};
+
+    /// Three objects with two, one and two values. References point at the objects
+    /// following the owners, except the last one, which points at the first object.
+    fn archive() -> NIBArchive {
+        NIBArchive::new(
+            vec![
+                Object::new(0, 0, 2),
+                Object::new(1, 2, 1),
+                Object::new(0, 3, 2),
+            ],
+            vec!["UIRef".into(), "UITag".into()],
+            vec![
+                Value::new(0, ValueVariant::ObjectRef(2)),
+                Value::new(1, ValueVariant::Int8(1)),
+                Value::new(0, ValueVariant::ObjectRef(1)),
+                Value::new(0, ValueVariant::ObjectRef(1)),
+                Value::new(0, ValueVariant::ObjectRef(0)),
+            ],
+            vec![
+                ClassName::with_fallbacks("UIView".into(), Vec::new()),
+                ClassName::with_fallbacks("UILabel".into(), Vec::new()),
+            ],
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn remove_object() {
+        let mut archive = archive();
+        let (obj, values) = archive.remove_object(1).unwrap();
+        assert_eq!(obj, Object::new(1, 2, 1));
+        assert_eq!(values, [Value::new(0, ValueVariant::ObjectRef(1))]);
+        assert_eq!(
+            archive.objects(),
+            [Object::new(0, 0, 2), Object::new(0, 2, 2)]
+        );
+        assert_eq!(
+            archive.values(),
+            [
+                Value::new(0, ValueVariant::ObjectRef(1)),
+                Value::new(1, ValueVariant::Int8(1)),
+                Value::new(0, ValueVariant::Nil),
+                Value::new(0, ValueVariant::ObjectRef(0)),
+            ]
+        );
+    }
+
+    #[test]
+    fn remove_value() {
+        let mut archive = archive();
+        let value = archive.remove_value(1.into()).unwrap();
+        assert_eq!(value, Value::new(1, ValueVariant::Int8(1)));
+        assert_eq!(
+            archive.objects(),
+            [
+                Object::new(0, 0, 1),
+                Object::new(1, 1, 1),
+                Object::new(0, 2, 2)
+            ]
+        );
+    }
+
+    #[test]
+    fn insert_object() {
+        let mut archive = archive();
+        let tag = Value::new(1, ValueVariant::Int8(7));
+        archive.insert_object(0, 1, vec![tag.clone()]).unwrap();
+        assert_eq!(
+            archive.objects(),
+            [
+                Object::new(1, 0, 1),
+                Object::new(0, 1, 2),
+                Object::new(1, 3, 1),
+                Object::new(0, 4, 2),
+            ]
+        );
+        assert_eq!(
+            archive.values(),
+            [
+                tag.clone(),
+                Value::new(0, ValueVariant::ObjectRef(3)),
+                Value::new(1, ValueVariant::Int8(1)),
+                Value::new(0, ValueVariant::ObjectRef(2)),
+                Value::new(0, ValueVariant::ObjectRef(2)),
+                Value::new(0, ValueVariant::ObjectRef(1)),
+            ]
+        );
+
+        let mut archive = self::archive();
+        let values = archive.values().to_vec();
+        let reference = Value::new(0, ValueVariant::ObjectRef(0));
+        let index = archive
+            .append_object_with_values(0, vec![reference.clone()])
+            .unwrap();
+        assert_eq!(index, 3);
+        assert_eq!(archive.objects()[3], Object::new(0, 5, 1));
+        assert_eq!(archive.values()[..5], values);
+        assert_eq!(archive.values()[5], reference);
+    }
+
+    #[test]
+    fn replace_object_values() {
+        let mut archive = archive();
+        let new_values = vec![Value::new(1, ValueVariant::Int8(2)); 3];
+        let old_values = archive.replace_object_values(0, new_values).unwrap();
+        assert_eq!(old_values.len(), 2);
+        assert_eq!(
+            archive.objects(),
+            [
+                Object::new(0, 0, 3),
+                Object::new(1, 3, 1),
+                Object::new(0, 4, 2)
+            ]
+        );
+
+        archive.replace_object_values(1, Vec::new()).unwrap();
+        assert_eq!(
+            archive.objects(),
+            [
+                Object::new(0, 0, 3),
+                Object::new(1, 3, 0),
+                Object::new(0, 3, 2)
+            ]
+        );
+        assert_eq!(archive.values().len(), 5);
+        assert_eq!(
+            archive.values()[3..],
+            [
+                Value::new(0, ValueVariant::ObjectRef(1)),
+                Value::new(0, ValueVariant::ObjectRef(0)),
+            ]
+        );
+    }
+
+    #[test]
+    fn negative_values_index() {
+        let mut archive = NIBArchive::new_unchecked(
+            vec![Object::new(0, -1, 2)],
+            vec!["UIText".into()],
+            vec![Value::new(0, ValueVariant::Nil)],
+            vec![ClassName::with_fallbacks("UILabel".into(), Vec::new())],
+        );
+        let err = archive.replace_object_values(0, Vec::new()).unwrap_err();
+        assert!(matches!(err, Error::IndexOutOfBounds { .. }));
+        let err = archive.remove_object(0).unwrap_err();
+        assert!(matches!(err, Error::IndexOutOfBounds { .. }));
+    }
+
+    #[test]
+    fn merge_overflow() {
+        let class_names = vec![ClassName::with_fallbacks("UIView".into(), Vec::new())];
+        let mut archive = NIBArchive::new_unchecked(
+            vec![Object::new(0, 0, 0)],
+            Vec::new(),
+            Vec::new(),
+            class_names.clone(),
+        );
+        let other = NIBArchive::new_unchecked(
+            vec![Object::new(0, 0, 1)],
+            vec!["UISubviews".into()],
+            vec![Value::new(0, ValueVariant::ObjectRef(u32::MAX))],
+            class_names,
+        );
+        let before = archive.clone();
+        // References are checked before they're offset, so they can't overflow
+        let err = archive.merge(other).unwrap_err();
+        assert!(matches!(err, Error::IndexOutOfBounds { .. }), "{err}");
+        assert_eq!(archive, before);
+    }
+
+    #[test]
+    fn merge_out_of_bounds() {
+        let class_names = vec![ClassName::with_fallbacks("UIView".into(), Vec::new())];
+        let mut archive = NIBArchive::new_unchecked(
+            vec![Object::new(0, 0, 0)],
+            Vec::new(),
+            Vec::new(),
+            class_names.clone(),
+        );
+        let before = archive.clone();
+        let other = |objects, value| {
+            NIBArchive::new_unchecked(
+                objects,
+                vec!["UISubviews".into()],
+                vec![Value::new(0, value)],
+                class_names.clone(),
+            )
+        };
+        let cases = [
+            (Object::new(0, -1, 1), ValueVariant::Nil, IndexKind::Value),
+            (Object::new(0, 1, 1), ValueVariant::Nil, IndexKind::Value),
+            (Object::new(0, 0, 2), ValueVariant::Nil, IndexKind::Value),
+            (
+                Object::new(1, 0, 1),
+                ValueVariant::Nil,
+                IndexKind::ClassName,
+            ),
+            (
+                Object::new(0, 0, 1),
+                ValueVariant::ObjectRef(1),
+                IndexKind::Object,
+            ),
+        ];
+        for (obj, value, expected) in cases {
+            let err = archive.merge(other(vec![obj], value)).unwrap_err();
+            assert!(
+                matches!(err, Error::IndexOutOfBounds { kind, .. } if kind == expected),
+                "{err}"
+            );
+            assert_eq!(archive, before);
+        }
+        archive
+            .merge(other(
+                vec![Object::new(0, 0, 1)],
+                ValueVariant::ObjectRef(0),
+            ))
+            .unwrap();
+        assert_eq!(archive.values()[0].value(), &ValueVariant::ObjectRef(1));
+    }
+
+    #[test]
+    fn canonicalize_value_widths() {
+        let archive = |value| {
+            NIBArchive::new_unchecked(
+                vec![Object::new(0, 0, 1)],
+                vec!["UITag".into()],
+                vec![Value::new(0, value)],
+                vec![ClassName::with_fallbacks("UIView".into(), Vec::new())],
+            )
+        };
+        let (mut a, mut b) = (
+            archive(ValueVariant::Int8(5)),
+            archive(ValueVariant::Int64(5)),
+        );
+        a.canonicalize();
+        b.canonicalize();
+        assert_ne!(a.to_bytes(), b.to_bytes());
+
+        b.set_minimize_value_widths(true);
+        b.canonicalize();
+        assert_eq!(b.values()[0].value(), &ValueVariant::Int8(5));
+        assert_eq!(a.to_bytes(), b.to_bytes());
+    }
+}
@@ -1,33 +1,261 @@
+use crate::FileKind;
+use alloc::string::{String, ToString};
+
+/// A section of a NIB Archive.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Section {
+    Header,
+    Objects,
+    Keys,
+    Values,
+    ClassNames,
+}
+
+impl core::fmt::Display for Section {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Section::Header => write!(f, "header"),
+            Section::Objects => write!(f, "objects"),
+            Section::Keys => write!(f, "keys"),
+            Section::Values => write!(f, "values"),
+            Section::ClassNames => write!(f, "class names"),
+        }
+    }
+}
+
+/// A kind of element an index points to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum IndexKind {
+    Object,
+    Key,
+    Value,
+    ClassName,
+}
+
+impl core::fmt::Display for IndexKind {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            IndexKind::Object => write!(f, "object"),
+            IndexKind::Key => write!(f, "key"),
+            IndexKind::Value => write!(f, "value"),
+            IndexKind::ClassName => write!(f, "class name"),
+        }
+    }
+}
+
+/// A limit of [Limits](crate::Limits) that may be exceeded during decoding.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LimitKind {
+    ObjectCount,
+    KeyCount,
+    ValueCount,
+    ClassNameCount,
+    FallbackClassCount,
+    DataLength,
+    TotalSize,
+    /// Objects aren't decoded from a stream, so the offset of the error is always 0.
+    DecodedObjectCount,
+}
+
+impl core::fmt::Display for LimitKind {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            LimitKind::ObjectCount => write!(f, "count of objects"),
+            LimitKind::KeyCount => write!(f, "count of keys"),
+            LimitKind::ValueCount => write!(f, "count of values"),
+            LimitKind::ClassNameCount => write!(f, "count of class names"),
+            LimitKind::FallbackClassCount => write!(f, "count of fallback classes"),
+            LimitKind::DataLength => write!(f, "length"),
+            LimitKind::TotalSize => write!(f, "size of the archive"),
+            LimitKind::DecodedObjectCount => write!(f, "count of decoded objects"),
+        }
+    }
+}
+
 /// Variants of error that may occur during encoding/decoding a NIB Archive.
+///
+/// Offsets are positions in the decoded stream.
 #[derive(Debug)]
 pub enum Error {
     /// An IO error that may occur during working with a file/stream.
-    IOError(std::io::Error),
+    IOError(crate::io::Error),
 
-    /// A format error that may occur only during decoding a NIB Archive.
+    /// A format error that doesn't fall into any of the more specific variants.
     /// Usually it indicates a malformed file.
     FormatError(String),
+
+    /// An error that may occur during parsing a query, e.g. a malformed key path.
+    QueryError(String),
+
+    /// The file is a nib of a format that predates NIB Archives.
+    UnsupportedLegacyFormat(FileKind),
+
+    /// The file doesn't start with the `NIBArchive` magic bytes.
+    UnexpectedMagic,
+
+    /// A section doesn't start at the offset specified in the header,
+    /// i.e. the previous section ended at `found`.
+    OffsetMismatch {
+        section: Section,
+        expected: u64,
+        found: u64,
+    },
+
+    /// Counts of elements specified in the header don't fit into a section.
+    SectionTooShort { section: Section, count: u32 },
+
+    /// An element points to another element that doesn't exist. `max` is the count
+    /// of elements of `kind`. `offset` is the position of the pointing element
+    /// if it was being decoded.
+    IndexOutOfBounds {
+        kind: IndexKind,
+        index: i64,
+        max: u64,
+        offset: Option<u64>,
+    },
+
+    /// A key or a class name at `offset` isn't a valid UTF-8 string.
+    InvalidUtf8 { section: Section, offset: u64 },
+
+    /// A value has a type byte at `offset` that doesn't match any known type.
+    UnknownValueType { type_byte: u8, offset: u64 },
+
+    /// A variable integer at `offset` is too long or overflows 32 bits.
+    InvalidVarInt { offset: u64 },
+
+    /// A variable integer at `offset` isn't minimally encoded.
+    /// Only returned when decoding in [strict](crate::DecodeOptions::set_strict()) mode.
+    NonMinimalVarInt { offset: u64 },
+
+    /// A variable integer at `offset` describing `what` is negative.
+    NegativeVarInt {
+        what: &'static str,
+        value: i32,
+        offset: u64,
+    },
+
+    /// A count, a length or a size exceeds a limit of [DecodeOptions](crate::DecodeOptions).
+    LimitExceeded {
+        limit: LimitKind,
+        value: u64,
+        max: u64,
+        offset: u64,
+    },
+
+    /// Recursive structures are nested deeper than [Limits::max_depth()] allows,
+    /// usually because of a reference cycle.
+    ///
+    /// [Limits::max_depth()]: crate::Limits::max_depth()
+    DepthExceeded { max: u32 },
+}
+
+impl Error {
+    /// Returns an [Error::IndexOutOfBounds] error without an offset.
+    #[cfg(feature = "std")]
+    pub(crate) fn out_of_bounds(kind: IndexKind, index: i64, max: usize) -> Self {
+        Self::IndexOutOfBounds {
+            kind,
+            index,
+            max: max as u64,
+            offset: None,
+        }
+    }
 }
 
-impl std::fmt::Display for Error {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+impl core::fmt::Display for Error {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        const PREFIX: &str = "NIB Archive format error";
         match self {
             Error::IOError(e) => f.write_fmt(format_args!("IOError: {e}")),
-            Error::FormatError(e) => f.write_fmt(format_args!("NIB Archive format error: {e}")),
+            Error::FormatError(e) => f.write_fmt(format_args!("{PREFIX}: {e}")),
+            Error::QueryError(e) => f.write_fmt(format_args!("Query error: {e}")),
+            Error::UnsupportedLegacyFormat(kind) => {
+                f.write_fmt(format_args!("Unsupported legacy nib format: {kind}"))
+            }
+            Error::UnexpectedMagic => write!(f, "{PREFIX}: Magic bytes don't match"),
+            Error::OffsetMismatch {
+                section,
+                expected,
+                found,
+            } => write!(
+                f,
+                "{PREFIX}: Expected {section} section at offset {expected} - got {found}"
+            ),
+            Error::SectionTooShort { section, count } => write!(
+                f,
+                "{PREFIX}: Count of {section} {count} doesn't fit into its section"
+            ),
+            Error::IndexOutOfBounds {
+                kind,
+                index,
+                max,
+                offset,
+            } => {
+                write!(
+                    f,
+                    "{PREFIX}: {} index {index} out of bounds of {max}",
+                    capitalize(&kind.to_string())
+                )?;
+                if let Some(offset) = offset {
+                    write!(f, " at offset {offset}")?;
+                }
+                Ok(())
+            }
+            Error::InvalidUtf8 { section, offset } => write!(
+                f,
+                "{PREFIX}: Invalid UTF-8 string in {section} section at offset {offset}"
+            ),
+            Error::UnknownValueType { type_byte, offset } => write!(
+                f,
+                "{PREFIX}: Unknown value type {type_byte:#04x} at offset {offset}"
+            ),
+            Error::InvalidVarInt { offset } => {
+                write!(f, "{PREFIX}: Invalid variable integer at offset {offset}")
+            }
+            Error::NonMinimalVarInt { offset } => write!(
+                f,
+                "{PREFIX}: Non-minimal variable integer at offset {offset}"
+            ),
+            Error::NegativeVarInt {
+                what,
+                value,
+                offset,
+            } => write!(f, "{PREFIX}: Negative {what} {value} at offset {offset}"),
+            Error::LimitExceeded {
+                limit,
+                value,
+                max,
+                offset,
+            } => write!(
+                f,
+                "{PREFIX}: {} {value} exceeds the limit of {max} at offset {offset}",
+                capitalize(&limit.to_string())
+            ),
+            Error::DepthExceeded { max } => {
+                write!(f, "{PREFIX}: Nesting depth exceeds the limit of {max}")
+            }
         }
     }
 }
 
-impl std::error::Error for Error {}
+fn capitalize(s: &str) -> String {
+    let mut chars = s.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+impl core::error::Error for Error {}
 
-impl From<std::io::Error> for Error {
-    fn from(value: std::io::Error) -> Self {
+impl From<crate::io::Error> for Error {
+    fn from(value: crate::io::Error) -> Self {
         Self::IOError(value)
     }
 }
 
-impl From<std::string::FromUtf8Error> for Error {
-    fn from(value: std::string::FromUtf8Error) -> Self {
+impl From<alloc::string::FromUtf8Error> for Error {
+    fn from(value: alloc::string::FromUtf8Error) -> Self {
         Self::FormatError(value.to_string())
     }
 }
@@ -0,0 +1,154 @@
+use crate::{consts, Error, NIBArchive, Object, Value};
+
+impl NIBArchive {
+    /// Returns indeces and [values](Value) of all objects whose class name is `name`.
+    pub fn find_by_class(&self, name: &str) -> Vec<(usize, &[Value])> {
+        self.find_where(|obj, _| {
+            self.class_names
+                .get(obj.class_name_id().as_usize())
+                .is_some_and(|cls| cls.name() == name)
+        })
+    }
+
+    /// Returns indeces and [values](Value) of all objects that contain a value with a given key.
+    pub fn find_by_key(&self, key: &str) -> Vec<(usize, &[Value])> {
+        self.find_where(|_, values| {
+            values.iter().any(|val| {
+                self.keys
+                    .get(val.key_id().as_usize())
+                    .is_some_and(|k| k == key)
+            })
+        })
+    }
+
+    /// Returns indeces and [values](Value) of all objects that satisfy a given predicate.
+    ///
+    /// The predicate receives an object and a slice of its values.
+    pub fn find_where<F>(&self, mut predicate: F) -> Vec<(usize, &[Value])>
+    where
+        F: FnMut(&Object, &[Value]) -> bool,
+    {
+        let mut result = Vec::new();
+        for (i, obj) in self.objects.iter().enumerate() {
+            let values = self.object_values(obj);
+            if predicate(obj, values) {
+                result.push((i, values));
+            }
+        }
+        result
+    }
+
+    /// Returns indeces of objects along with archives embedded into their data values.
+    /// See [ValueVariant::as_embedded_archive()](crate::ValueVariant::as_embedded_archive()).
+    ///
+    /// Values that look like embedded archives but fail to decode are returned as errors.
+    pub fn find_embedded_archives(&self) -> Vec<(usize, Result<NIBArchive, Error>)> {
+        let mut result = Vec::new();
+        for (i, obj) in self.objects.iter().enumerate() {
+            for val in self.object_values(obj) {
+                if let Some(archive) = val.as_embedded_archive() {
+                    result.push((i, archive));
+                }
+            }
+        }
+        result
+    }
+
+    /// Returns the first value of an object at `index` with a given key.
+    pub fn object_value(&self, index: usize, key: &str) -> Option<&Value> {
+        let obj = self.objects.get(index)?;
+        self.object_values(obj).iter().find(|val| {
+            self.keys
+                .get(val.key_id().as_usize())
+                .is_some_and(|k| k == key)
+        })
+    }
+
+    /// Returns `true` if an object at `index` is an instance of a class named `name`
+    /// or the class falls back to it.
+    pub fn is_kind_of(&self, index: usize, name: &str) -> bool {
+        let Some(class_name) = self
+            .objects
+            .get(index)
+            .and_then(|obj| self.class_names.get(obj.class_name_id().as_usize()))
+        else {
+            return false;
+        };
+        class_name.name() == name
+            || class_name
+                .fallback_class_ids()
+                .iter()
+                .filter_map(|i| self.class_names.get(i.as_usize()))
+                .any(|c| c.name() == name)
+    }
+
+    /// Returns indeces of objects along with keys their classes aren't expected to have
+    /// according to the [registry](consts::CLASSES).
+    ///
+    /// An object is checked against its class or, if the class isn't registered, against
+    /// the first registered fallback class. Objects of unregistered classes are skipped.
+    pub fn unexpected_keys(&self) -> Vec<(usize, &str)> {
+        let mut result = Vec::new();
+        for (i, obj) in self.objects.iter().enumerate() {
+            let Some(class_name) = self.class_names.get(obj.class_name_id().as_usize()) else {
+                continue;
+            };
+            let info = consts::class_info(class_name.name()).or_else(|| {
+                class_name
+                    .fallback_class_ids()
+                    .iter()
+                    .filter_map(|i| self.class_names.get(i.as_usize()))
+                    .find_map(|c| consts::class_info(c.name()))
+            });
+            let Some(info) = info else {
+                continue;
+            };
+            for val in self.object_values(obj) {
+                if let Some(key) = self.keys.get(val.key_id().as_usize()) {
+                    if !info.expected_keys().any(|k| k == key) {
+                        result.push((i, key.as_str()));
+                    }
+                }
+            }
+        }
+        result
+    }
+
+    /// Same as [Object::values()], but returns an empty slice instead of
+    /// panicking if the object's value range is out of bounds.
+    pub(crate) fn object_values(&self, obj: &Object) -> &[Value] {
+        self.try_object_values(obj).unwrap_or(&[])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{consts::keys, ClassName, ClassNameIndex, NIBArchive, Object, Value, ValueVariant};
+
+    #[test]
+    fn unexpected_keys() {
+        let archive = NIBArchive::new_unchecked(
+            vec![
+                Object::new(0, 0, 2),
+                Object::new(1, 2, 1),
+                Object::new(2, 3, 1),
+            ],
+            vec![keys::UI_TAG.to_string(), "UIUnexpected".to_string()],
+            vec![
+                Value::new(0, ValueVariant::Int8(1)),
+                Value::new(1, ValueVariant::Int8(1)),
+                Value::new(1, ValueVariant::Int8(1)),
+                Value::new(1, ValueVariant::Int8(1)),
+            ],
+            vec![
+                ClassName::with_fallbacks("UILabel".to_string(), vec![]),
+                ClassName::with_fallbacks("MyView".to_string(), vec![ClassNameIndex(0)]),
+                ClassName::with_fallbacks("MyObject".to_string(), vec![]),
+            ],
+        );
+        assert_eq!(
+            archive.unexpected_keys(),
+            vec![(0, "UIUnexpected"), (1, "UIUnexpected")]
+        );
+    }
+}
@@ -0,0 +1,76 @@
+use crate::NIBArchive;
+
+const FNV_OFFSET_BASIS: u128 = 0x6c62272e07bb014262b821756295c58d;
+const FNV_PRIME: u128 = 0x0000000001000000000000000000013B;
+
+impl NIBArchive {
+    /// Returns a stable hash of the archive's logical content.
+    ///
+    /// The hash is computed over the encoding of a [pruned](NIBArchive::prune()) and
+    /// [canonicalized](NIBArchive::canonicalize()) copy of the archive, so it doesn't depend
+    /// on encoding details like widths of variable integers, unused or duplicate keys and
+    /// class names, or their order. Objects, values and their types, as well as format
+    /// and coder versions, are taken into account.
+    ///
+    /// The hash is 128-bit FNV-1a, which is stable across platforms and versions of the crate,
+    /// but isn't cryptographic.
+    pub fn fingerprint(&self) -> u128 {
+        let mut canonical = NIBArchive::new_unchecked(
+            self.objects.clone(),
+            self.keys.clone(),
+            self.values.clone(),
+            self.class_names.clone(),
+        );
+        canonical.format_version = self.format_version;
+        canonical.coder_version = self.coder_version;
+        canonical.prune();
+        canonical.canonicalize();
+        canonical
+            .to_bytes()
+            .iter()
+            .fold(FNV_OFFSET_BASIS, |hash, byte| {
+                (hash ^ *byte as u128).wrapping_mul(FNV_PRIME)
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ClassName, NIBArchive, Object, Value, ValueVariant};
+
+    fn class(name: &str) -> ClassName {
+        ClassName::with_fallbacks(name.into(), Vec::new())
+    }
+
+    #[test]
+    fn reordering() {
+        let archive = |tag| {
+            NIBArchive::new(
+                vec![Object::new(0, 0, 1), Object::new(1, 1, 2)],
+                vec!["UITag".into(), "UIText".into()],
+                vec![
+                    Value::new(0, ValueVariant::Int8(1)),
+                    Value::new(0, ValueVariant::Int8(tag)),
+                    Value::new(1, ValueVariant::Data(b"Title".to_vec())),
+                ],
+                vec![class("UIView"), class("UILabel")],
+            )
+            .unwrap()
+        };
+        // Same content with keys and class names in reverse order and an unused key
+        let reordered = NIBArchive::new(
+            vec![Object::new(1, 0, 1), Object::new(0, 1, 2)],
+            vec!["UIText".into(), "UIHidden".into(), "UITag".into()],
+            vec![
+                Value::new(2, ValueVariant::Int8(1)),
+                Value::new(2, ValueVariant::Int8(2)),
+                Value::new(0, ValueVariant::Data(b"Title".to_vec())),
+            ],
+            vec![class("UILabel"), class("UIView")],
+        )
+        .unwrap();
+
+        assert_eq!(archive(2).fingerprint(), reordered.fingerprint());
+        assert_ne!(archive(2).fingerprint(), archive(3).fingerprint());
+    }
+}
@@ -0,0 +1,448 @@
+use crate::{
+    consts::keys, Error, IndexKind, LimitKind, Limits, NIBArchive, Object, ObjectIndex, Point,
+    Quirks, Rect, Size, Value, ValueVariant,
+};
+use std::{any::type_name, cell::Cell};
+
+/// Maps an object of an archive onto a Rust type, like `initWithCoder:` of `NSCoding`.
+///
+/// Fields are usually read with the helpers of [NibContext]:
+///
+/// ```
+/// use nibarchive::{Error, FromNib, NibContext, Object, Rect};
+///
+/// struct View {
+///     frame: Rect,
+///     tag: i64,
+///     subviews: Vec<View>,
+/// }
+///
+/// impl FromNib for View {
+///     fn from_nib(obj: &Object, ctx: &NibContext) -> Result<Self, Error> {
+///         Ok(View {
+///             frame: ctx.field_or_default(obj, "UIFrame")?,
+///             tag: ctx.field_or_default(obj, "UITag")?,
+///             subviews: ctx.array(obj, "UISubviews")?,
+///         })
+///     }
+/// }
+/// ```
+pub trait FromNib: Sized {
+    fn from_nib(obj: &Object, ctx: &NibContext) -> Result<Self, Error>;
+}
+
+/// Converts a single value of an archive into a Rust type.
+///
+/// Returns `None` if the value has a different type.
+pub trait FromNibValue: Sized {
+    fn from_nib_value(value: &ValueVariant, ctx: &NibContext) -> Option<Self>;
+}
+
+/// An archive that objects are decoded from. It's passed to [FromNib::from_nib()]
+/// and provides helpers for reading fields by their keys.
+///
+/// Objects may be decoded only as deep as [Limits::max_depth()] allows, so reference
+/// cycles result in an [Error::DepthExceeded] error. The total count of decoded objects
+/// is limited by [Limits::max_decoded_objects()].
+#[derive(Debug, Clone)]
+pub struct NibContext<'a> {
+    archive: &'a NIBArchive,
+    depth: u32,
+    max_depth: u32,
+    /// Count of objects that may still be decoded.
+    budget: Cell<u32>,
+    max_decoded_objects: u32,
+}
+
+impl<'a> NibContext<'a> {
+    /// Creates a context with default limits.
+    pub fn new(archive: &'a NIBArchive) -> Self {
+        Self::with_limits(archive, &Limits::default())
+    }
+
+    /// Creates a context with given limits.
+    pub fn with_limits(archive: &'a NIBArchive, limits: &Limits) -> Self {
+        Self {
+            archive,
+            depth: 0,
+            max_depth: limits.max_depth(),
+            budget: Cell::new(limits.max_decoded_objects()),
+            max_decoded_objects: limits.max_decoded_objects(),
+        }
+    }
+
+    pub fn archive(&self) -> &'a NIBArchive {
+        self.archive
+    }
+
+    /// Returns quirks of the versions of the archive.
+    pub fn quirks(&self) -> Quirks {
+        self.archive.quirks()
+    }
+
+    /// Returns the class name of an object or an empty string if its index is out of bounds.
+    pub fn class_name(&self, obj: &Object) -> &'a str {
+        self.archive
+            .class_names()
+            .get(obj.class_name_id().as_usize())
+            .map_or("", |cls| cls.name())
+    }
+
+    /// Returns all values of an object.
+    pub fn values(&self, obj: &Object) -> &'a [Value] {
+        self.archive.object_values(obj)
+    }
+
+    /// Returns the first value of an object with a given key.
+    pub fn value(&self, obj: &Object, key: &str) -> Option<&'a ValueVariant> {
+        self.values_with_key(obj, key).next()
+    }
+
+    /// Reads a required field. Returns an error if it's missing or has a different type.
+    pub fn field<T: FromNibValue>(&self, obj: &Object, key: &str) -> Result<T, Error> {
+        self.field_opt(obj, key)?.ok_or_else(|| {
+            Error::FormatError(format!(
+                "{} has no value with key {key}",
+                self.class_name(obj)
+            ))
+        })
+    }
+
+    /// Reads an optional field. Returns an error if it has a different type.
+    pub fn field_opt<T: FromNibValue>(&self, obj: &Object, key: &str) -> Result<Option<T>, Error> {
+        self.value(obj, key)
+            .map(|value| self.convert(obj, key, value))
+            .transpose()
+    }
+
+    /// Reads an optional field and falls back to the default value if it's missing.
+    /// Returns an error if it has a different type.
+    pub fn field_or_default<T: FromNibValue + Default>(
+        &self,
+        obj: &Object,
+        key: &str,
+    ) -> Result<T, Error> {
+        Ok(self.field_opt(obj, key)?.unwrap_or_default())
+    }
+
+    /// Reads all values of an object with a given key, e.g. elements of an array.
+    pub fn fields<T: FromNibValue>(&self, obj: &Object, key: &str) -> Result<Vec<T>, Error> {
+        self.values_with_key(obj, key)
+            .map(|value| self.convert(obj, key, value))
+            .collect()
+    }
+
+    /// Decodes an object at a given index.
+    pub fn decode<T: FromNib>(&self, index: ObjectIndex) -> Result<T, Error> {
+        let obj = self.object_at(index)?;
+        if self.depth >= self.max_depth {
+            return Err(Error::DepthExceeded {
+                max: self.max_depth,
+            });
+        }
+        let budget = self
+            .budget
+            .get()
+            .checked_sub(1)
+            .ok_or(Error::LimitExceeded {
+                limit: LimitKind::DecodedObjectCount,
+                value: self.max_decoded_objects as u64 + 1,
+                max: self.max_decoded_objects as u64,
+                offset: 0,
+            })?;
+        let nested = Self {
+            depth: self.depth + 1,
+            budget: Cell::new(budget),
+            ..self.clone()
+        };
+        let result = T::from_nib(obj, &nested);
+        self.budget.set(nested.budget.get());
+        result
+    }
+
+    /// Decodes an object referenced by a required field.
+    pub fn object<T: FromNib>(&self, obj: &Object, key: &str) -> Result<T, Error> {
+        self.decode(self.field(obj, key)?)
+    }
+
+    /// Decodes an object referenced by an optional field.
+    pub fn object_opt<T: FromNib>(&self, obj: &Object, key: &str) -> Result<Option<T>, Error> {
+        self.field_opt(obj, key)?
+            .map(|index| self.decode(index))
+            .transpose()
+    }
+
+    /// Decodes elements of an array or a set referenced by a field.
+    ///
+    /// Returns an empty vector if the field is missing.
+    pub fn array<T: FromNib>(&self, obj: &Object, key: &str) -> Result<Vec<T>, Error> {
+        let Some(index) = self.field_opt::<ObjectIndex>(obj, key)? else {
+            return Ok(Vec::new());
+        };
+        let array = self.object_at(index)?;
+        self.fields::<ObjectIndex>(array, keys::UI_NIB_ENCODER_EMPTY)?
+            .into_iter()
+            .map(|i| self.decode(i))
+            .collect()
+    }
+
+    fn object_at(&self, index: ObjectIndex) -> Result<&'a Object, Error> {
+        let objects = self.archive.objects();
+        objects
+            .get(index.as_usize())
+            .ok_or_else(|| Error::out_of_bounds(IndexKind::Object, index.0.into(), objects.len()))
+    }
+
+    fn values_with_key<'k>(
+        &self,
+        obj: &Object,
+        key: &'k str,
+    ) -> impl Iterator<Item = &'a ValueVariant> + 'k
+    where
+        'a: 'k,
+    {
+        let keys = self.archive.keys();
+        self.values(obj)
+            .iter()
+            .filter(move |val| keys.get(val.key_id().as_usize()).is_some_and(|k| k == key))
+            .map(|val| val.value())
+    }
+
+    fn convert<T: FromNibValue>(
+        &self,
+        obj: &Object,
+        key: &str,
+        value: &ValueVariant,
+    ) -> Result<T, Error> {
+        T::from_nib_value(value, self).ok_or_else(|| {
+            Error::FormatError(format!(
+                "Value {key} of {} is {}, which can't be read as {}",
+                self.class_name(obj),
+                value.type_name(),
+                type_name::<T>()
+            ))
+        })
+    }
+}
+
+impl NIBArchive {
+    /// Decodes an object at `index` into a Rust type implementing [FromNib].
+    pub fn decode_object<T: FromNib>(&self, index: usize) -> Result<T, Error> {
+        let index = i32::try_from(index).map_err(|_| {
+            Error::out_of_bounds(
+                IndexKind::Object,
+                i64::try_from(index).unwrap_or(i64::MAX),
+                self.objects.len(),
+            )
+        })?;
+        NibContext::new(self).decode(ObjectIndex(index))
+    }
+}
+
+macro_rules! impl_from_nib_value_int {
+    ($($t:ty),*) => {
+        $(
+            impl FromNibValue for $t {
+                fn from_nib_value(value: &ValueVariant, _ctx: &NibContext) -> Option<Self> {
+                    value.as_i64()?.try_into().ok()
+                }
+            }
+        )*
+    };
+}
+
+impl_from_nib_value_int!(i8, i16, i32, i64, u8, u16, u32, u64, usize);
+
+impl FromNibValue for f64 {
+    fn from_nib_value(value: &ValueVariant, _ctx: &NibContext) -> Option<Self> {
+        value.as_f64()
+    }
+}
+
+impl FromNibValue for f32 {
+    fn from_nib_value(value: &ValueVariant, _ctx: &NibContext) -> Option<Self> {
+        value.as_f64().map(|v| v as f32)
+    }
+}
+
+impl FromNibValue for bool {
+    fn from_nib_value(value: &ValueVariant, _ctx: &NibContext) -> Option<Self> {
+        match value {
+            ValueVariant::Bool(v) => Some(*v),
+            _ => value.as_i64().map(|v| v != 0),
+        }
+    }
+}
+
+impl FromNibValue for String {
+    /// Reads a string stored either in a data value or in an `NSString` object.
+    /// Empty data is an empty string.
+    fn from_nib_value(value: &ValueVariant, ctx: &NibContext) -> Option<Self> {
+        match value {
+            ValueVariant::Data(data) if data.is_empty() => Some(String::new()),
+            _ => ctx.archive.resolve_string(value),
+        }
+    }
+}
+
+impl FromNibValue for Vec<u8> {
+    fn from_nib_value(value: &ValueVariant, _ctx: &NibContext) -> Option<Self> {
+        match value {
+            ValueVariant::Data(data) => Some(data.clone()),
+            _ => None,
+        }
+    }
+}
+
+impl FromNibValue for ObjectIndex {
+    fn from_nib_value(value: &ValueVariant, _ctx: &NibContext) -> Option<Self> {
+        value.as_object_ref()
+    }
+}
+
+impl FromNibValue for Point {
+    fn from_nib_value(value: &ValueVariant, _ctx: &NibContext) -> Option<Self> {
+        value.as_point()
+    }
+}
+
+impl FromNibValue for Size {
+    fn from_nib_value(value: &ValueVariant, _ctx: &NibContext) -> Option<Self> {
+        value.as_size()
+    }
+}
+
+impl FromNibValue for Rect {
+    fn from_nib_value(value: &ValueVariant, _ctx: &NibContext) -> Option<Self> {
+        value.as_rect()
+    }
+}
+
+impl FromNibValue for ValueVariant {
+    fn from_nib_value(value: &ValueVariant, _ctx: &NibContext) -> Option<Self> {
+        Some(value.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ClassName, ClassNameIndex, KeyIndex};
+
+    struct View {
+        subviews: Vec<View>,
+    }
+
+    impl FromNib for View {
+        fn from_nib(obj: &Object, ctx: &NibContext) -> Result<Self, Error> {
+            Ok(Self {
+                subviews: ctx.array(obj, "UISubviews")?,
+            })
+        }
+    }
+
+    /// A view whose subviews array contains the view itself.
+    fn cyclic_archive() -> NIBArchive {
+        NIBArchive::new(
+            vec![
+                Object::new(ClassNameIndex(0), 0, 1),
+                Object::new(ClassNameIndex(1), 1, 1),
+            ],
+            vec!["UISubviews".into(), keys::UI_NIB_ENCODER_EMPTY.into()],
+            vec![
+                Value::new(KeyIndex(0), ValueVariant::ObjectRef(1)),
+                Value::new(KeyIndex(1), ValueVariant::ObjectRef(0)),
+            ],
+            vec![
+                ClassName::with_fallbacks("UIView".into(), Vec::new()),
+                ClassName::with_fallbacks("NSArray".into(), Vec::new()),
+            ],
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn reference_cycle() {
+        let archive = cyclic_archive();
+        assert!(matches!(
+            archive.decode_object::<View>(0),
+            Err(Error::DepthExceeded { max: 256 })
+        ));
+    }
+
+    /// Views of `levels` levels whose subviews arrays contain the view of the next level twice.
+    fn diamond_archive(levels: i32) -> NIBArchive {
+        let mut objects = Vec::new();
+        let mut values = Vec::new();
+        for level in 0..levels {
+            let array = 2 * level as u32 + 1;
+            objects.push(Object::new(ClassNameIndex(0), values.len() as i32, 1));
+            values.push(Value::new(KeyIndex(0), ValueVariant::ObjectRef(array)));
+            let count = if level + 1 < levels { 2 } else { 0 };
+            objects.push(Object::new(ClassNameIndex(1), values.len() as i32, count));
+            for _ in 0..count {
+                values.push(Value::new(KeyIndex(1), ValueVariant::ObjectRef(array + 1)));
+            }
+        }
+        NIBArchive::new(
+            objects,
+            vec!["UISubviews".into(), keys::UI_NIB_ENCODER_EMPTY.into()],
+            values,
+            vec![
+                ClassName::with_fallbacks("UIView".into(), Vec::new()),
+                ClassName::with_fallbacks("NSArray".into(), Vec::new()),
+            ],
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn shared_objects() {
+        // 1 + 2 + 4 + 8 views are decoded
+        let archive = diamond_archive(4);
+        let mut limits = Limits::default();
+        limits.set_max_decoded_objects(15);
+        let ctx = NibContext::with_limits(&archive, &limits);
+        let view: View = ctx.decode(ObjectIndex(0)).unwrap();
+        assert_eq!(view.subviews.len(), 2);
+        assert_eq!(view.subviews[1].subviews.len(), 2);
+
+        limits.set_max_decoded_objects(14);
+        let ctx = NibContext::with_limits(&archive, &limits);
+        assert!(matches!(
+            ctx.decode::<View>(ObjectIndex(0)),
+            Err(Error::LimitExceeded {
+                limit: LimitKind::DecodedObjectCount,
+                max: 14,
+                ..
+            })
+        ));
+
+        // Without the limit it would take 2^64 decodes
+        let archive = diamond_archive(64);
+        assert!(matches!(
+            archive.decode_object::<View>(0),
+            Err(Error::LimitExceeded {
+                limit: LimitKind::DecodedObjectCount,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn truncated_index() {
+        let archive = cyclic_archive();
+        assert!(archive
+            .decode_object::<View>(1)
+            .unwrap()
+            .subviews
+            .is_empty());
+        assert!(matches!(
+            archive.decode_object::<View>((1 << 32) + 1),
+            Err(Error::IndexOutOfBounds {
+                kind: IndexKind::Object,
+                ..
+            })
+        ));
+    }
+}
@@ -0,0 +1,49 @@
+use crate::{
+    generator::{
+        ArchiveParts, MAX_CLASS_NAMES, MAX_FALLBACK_CLASSES, MAX_KEYS, MAX_OBJECTS,
+        MAX_VALUES_PER_OBJECT,
+    },
+    NIBArchive, ValueVariant,
+};
+use arbitrary::{Arbitrary, Result, Unstructured};
+
+/// Generates an internally consistent archive: keys are unique, every index points
+/// to an existing element, values of objects are contiguous and object references
+/// point to existing objects, so the archive survives an encoding/decoding round trip.
+///
+/// [Unknown](ValueVariant::Unknown) values are never generated, since they can't be
+/// decoded back.
+impl<'a> Arbitrary<'a> for NIBArchive {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        let mut parts = ArchiveParts::default();
+
+        let class_names_count = u.int_in_range(1..=MAX_CLASS_NAMES)?;
+        for _ in 0..class_names_count {
+            let name = String::arbitrary(u)?;
+            let fallbacks_count = u.int_in_range(0..=MAX_FALLBACK_CLASSES)?;
+            let fallbacks = (0..fallbacks_count)
+                .map(|_| u.choose_index(class_names_count))
+                .collect::<Result<Vec<_>>>()?;
+            parts.class_names.push((name, fallbacks));
+        }
+
+        let keys_count = u.int_in_range(1..=MAX_KEYS)?;
+        for _ in 0..keys_count {
+            parts.keys.push(String::arbitrary(u)?);
+        }
+
+        let objects_count = u.int_in_range(1..=MAX_OBJECTS)?;
+        for _ in 0..objects_count {
+            let class_name_index = u.choose_index(class_names_count)?;
+            let value_count = u.int_in_range(0..=MAX_VALUES_PER_OBJECT)?;
+            let values = (0..value_count)
+                .map(|_| Ok((u.choose_index(keys_count)?, ValueVariant::arbitrary(u)?)))
+                .collect::<Result<Vec<_>>>()?;
+            parts.objects.push((class_name_index, values));
+        }
+
+        parts.format_version = u32::arbitrary(u)?;
+        parts.coder_version = u32::arbitrary(u)?;
+        Ok(parts.build())
+    }
+}
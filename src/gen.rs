@@ -0,0 +1,392 @@
+//! Generation of random, but plausible archives.
+//!
+//! Generated archives resemble UIKit nibs: a root object references an array of top
+//! level views, views reference arrays of their subviews and have frames, tags, texts
+//! and other properties. They're useful for seeding fuzzing corpora, benchmarks and
+//! fixtures without shipping real nibs. The same seed and parameters always produce
+//! the same archive.
+
+use crate::{
+    consts::{classes, keys},
+    ClassName, KeyIndex, NIBArchive, Object, Value, ValueVariant, VarInt,
+};
+
+const VIEW_CLASSES: &[&str] = &[
+    classes::UI_VIEW,
+    classes::UI_LABEL,
+    classes::UI_BUTTON,
+    classes::UI_IMAGE_VIEW,
+    classes::UI_STACK_VIEW,
+    classes::UI_SCROLL_VIEW,
+    classes::UI_TEXT_FIELD,
+    classes::UI_SWITCH,
+];
+const TEXT_CLASSES: &[&str] = &[
+    classes::UI_LABEL,
+    classes::UI_BUTTON,
+    classes::UI_TEXT_FIELD,
+];
+const WORDS: &[&str] = &[
+    "Title", "Cancel", "OK", "Settings", "Done", "Next", "Back", "Hello", "Welcome", "Search",
+    "Name", "Password", "Sign In", "More",
+];
+
+const INTEGER_KEYS: &[&str] = &[
+    keys::UI_CONTENT_MODE,
+    keys::UI_AUTORESIZING_MASK,
+    keys::UI_SEMANTIC_CONTENT_ATTRIBUTE,
+    keys::UI_CONTENT_HUGGING_PRIORITY,
+];
+const FLOAT_KEYS: &[&str] = &[
+    keys::UI_ALPHA,
+    keys::UI_CORNER_RADIUS,
+    keys::UI_CONTENT_SCALE_FACTOR,
+];
+const BOOL_KEYS: &[&str] = &[
+    keys::UI_HIDDEN,
+    keys::UI_OPAQUE,
+    keys::UI_CLIPS_TO_BOUNDS,
+    keys::UI_USER_INTERACTION_DISABLED,
+    keys::UI_MULTIPLE_TOUCH_ENABLED,
+];
+const STRING_KEYS: &[&str] = &[
+    keys::UI_ACCESSIBILITY_LABEL,
+    keys::UI_ACCESSIBILITY_HINT,
+    keys::UI_ACCESSIBILITY_IDENTIFIER,
+    keys::UI_RESTORATION_IDENTIFIER,
+];
+const DATA_KEYS: &[&str] = &[keys::UI_IMAGE_DATA, keys::UI_RESOURCE_DATA];
+const NIL_KEYS: &[&str] = &[
+    keys::UI_BACKGROUND_COLOR,
+    keys::UI_TINT_COLOR,
+    keys::UI_LAYER,
+];
+
+/// Relative weights of types of additional values of generated views.
+///
+/// Every view gets a frame and, depending on its class, a tag, a text and subviews.
+/// Up to [GenerateParams::max_extra_values()] additional values are added, and
+/// their types are picked with these weights. A zero weight disables a type.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ValueMix {
+    pub integers: u32,
+    pub floats: u32,
+    pub bools: u32,
+    /// UTF-8 strings stored in data values.
+    pub strings: u32,
+    /// Random bytes stored in data values.
+    pub data: u32,
+    pub nils: u32,
+}
+
+impl Default for ValueMix {
+    fn default() -> Self {
+        Self {
+            integers: 4,
+            floats: 2,
+            bools: 3,
+            strings: 3,
+            data: 1,
+            nils: 1,
+        }
+    }
+}
+
+/// Parameters of [random_archive()].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct GenerateParams {
+    views: usize,
+    max_depth: usize,
+    max_extra_values: usize,
+    value_mix: ValueMix,
+}
+
+impl Default for GenerateParams {
+    fn default() -> Self {
+        Self {
+            views: 32,
+            max_depth: 4,
+            max_extra_values: 4,
+            value_mix: ValueMix::default(),
+        }
+    }
+}
+
+impl GenerateParams {
+    /// Creates default parameters: 32 views nested up to 4 levels deep with up to
+    /// 4 additional values each.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the count of generated views.
+    pub fn views(&self) -> usize {
+        self.views
+    }
+
+    /// Sets the count of generated views. Root and array objects come on top of them.
+    pub fn set_views(&mut self, value: usize) {
+        self.views = value;
+    }
+
+    /// Returns the maximum nesting depth of views.
+    pub fn max_depth(&self) -> usize {
+        self.max_depth
+    }
+
+    /// Sets the maximum nesting depth of views. Top level views have a depth of 0,
+    /// so `0` produces views without subviews.
+    pub fn set_max_depth(&mut self, value: usize) {
+        self.max_depth = value;
+    }
+
+    /// Returns the maximum count of additional values of a view.
+    pub fn max_extra_values(&self) -> usize {
+        self.max_extra_values
+    }
+
+    /// Sets the maximum count of additional values of a view.
+    pub fn set_max_extra_values(&mut self, value: usize) {
+        self.max_extra_values = value;
+    }
+
+    /// Returns weights of types of additional values.
+    pub fn value_mix(&self) -> ValueMix {
+        self.value_mix
+    }
+
+    /// Sets weights of types of additional values.
+    pub fn set_value_mix(&mut self, value: ValueMix) {
+        self.value_mix = value;
+    }
+}
+
+/// Generates a random archive with a given seed.
+///
+/// Object 0 is the root object, which references an array of top level views.
+/// Views follow it, and arrays of subviews come last.
+pub fn random_archive(seed: u64, params: &GenerateParams) -> NIBArchive {
+    let mut rng = Rng(seed);
+    let mut builder = Builder::default();
+
+    // Attach every view either to a random view that isn't nested too deeply
+    // or to the top level
+    let mut depths: Vec<usize> = Vec::with_capacity(params.views);
+    let mut children: Vec<Vec<usize>> = vec![Vec::new(); params.views];
+    let mut top_level = Vec::new();
+    for i in 0..params.views {
+        let parent = (i > 0 && rng.below(8) != 0)
+            .then(|| rng.below(i))
+            .filter(|p| depths[*p] < params.max_depth);
+        match parent {
+            Some(p) => {
+                depths.push(depths[p] + 1);
+                children[p].push(i);
+            }
+            None => {
+                depths.push(0);
+                top_level.push(i);
+            }
+        }
+    }
+
+    let first_view = 2;
+    let mut next_array = first_view + params.views;
+    let object_ref = |i: usize| ValueVariant::ObjectRef((first_view + i) as u32);
+
+    let root_values =
+        vec![builder.value(keys::UI_NIB_TOP_LEVEL_OBJECTS, ValueVariant::ObjectRef(1))];
+    builder.push_object(classes::NS_OBJECT, root_values);
+    let top_level_values = top_level
+        .iter()
+        .map(|i| builder.value(keys::UI_NIB_ENCODER_EMPTY, object_ref(*i)))
+        .collect();
+    builder.push_object(classes::NS_ARRAY, top_level_values);
+
+    for view_children in &children {
+        let class_name = VIEW_CLASSES[rng.below(VIEW_CLASSES.len())];
+        let mut values = vec![
+            builder.value(keys::UI_BOUNDS, rect(&mut rng)),
+            builder.value(keys::UI_CENTER, point(&mut rng)),
+        ];
+        if rng.below(3) == 0 {
+            values.push(builder.value(keys::UI_TAG, ValueVariant::Int32(rng.below(1000) as i32)));
+        }
+        if TEXT_CLASSES.contains(&class_name) {
+            values.push(builder.value(keys::UI_TEXT, string(&mut rng)));
+        }
+        if !view_children.is_empty() {
+            values.push(builder.value(
+                keys::UI_SUBVIEWS,
+                ValueVariant::ObjectRef(next_array as u32),
+            ));
+            next_array += 1;
+        }
+        let extra_count = rng.below(params.max_extra_values + 1);
+        for _ in 0..extra_count {
+            let Some((key, value)) = extra_value(&mut rng, &params.value_mix) else {
+                break;
+            };
+            // A key is used once per object, like in real archives
+            let key_index = builder.key(key);
+            if values.iter().all(|v| v.key_id() != key_index) {
+                values.push(Value::new(key_index, value));
+            }
+        }
+        builder.push_object(class_name, values);
+    }
+
+    for view_children in children.iter().filter(|c| !c.is_empty()) {
+        let values = view_children
+            .iter()
+            .map(|i| builder.value(keys::UI_NIB_ENCODER_EMPTY, object_ref(*i)))
+            .collect();
+        builder.push_object(classes::NS_ARRAY, values);
+    }
+
+    NIBArchive::new_unchecked(
+        builder.objects,
+        builder.keys,
+        builder.values,
+        builder.class_names,
+    )
+}
+
+fn extra_value(rng: &mut Rng, mix: &ValueMix) -> Option<(&'static str, ValueVariant)> {
+    let weights = [
+        mix.integers,
+        mix.floats,
+        mix.bools,
+        mix.strings,
+        mix.data,
+        mix.nils,
+    ];
+    let total: u64 = weights.iter().map(|w| *w as u64).sum();
+    if total == 0 {
+        return None;
+    }
+    let mut pick = rng.next_u64() % total;
+    let kind = weights
+        .iter()
+        .position(|w| {
+            let found = pick < *w as u64;
+            pick = pick.saturating_sub(*w as u64);
+            found
+        })
+        .unwrap_or_default();
+    Some(match kind {
+        0 => {
+            let value = match rng.below(4) {
+                0 => ValueVariant::Int8(rng.next_u64() as i8),
+                1 => ValueVariant::Int16(rng.next_u64() as i16),
+                2 => ValueVariant::Int32(rng.next_u64() as i32),
+                _ => ValueVariant::Int64(rng.next_u64() as i64),
+            };
+            (INTEGER_KEYS[rng.below(INTEGER_KEYS.len())], value)
+        }
+        1 => {
+            let value = rng.below(10_000) as f64 / 100.0;
+            let value = match rng.below(2) {
+                0 => ValueVariant::Float(value as f32),
+                _ => ValueVariant::Double(value),
+            };
+            (FLOAT_KEYS[rng.below(FLOAT_KEYS.len())], value)
+        }
+        2 => (
+            BOOL_KEYS[rng.below(BOOL_KEYS.len())],
+            ValueVariant::Bool(rng.below(2) == 0),
+        ),
+        3 => (STRING_KEYS[rng.below(STRING_KEYS.len())], string(rng)),
+        4 => {
+            let data = (0..rng.below(64) + 1)
+                .map(|_| rng.next_u64() as u8)
+                .collect();
+            (
+                DATA_KEYS[rng.below(DATA_KEYS.len())],
+                ValueVariant::Data(data),
+            )
+        }
+        _ => (NIL_KEYS[rng.below(NIL_KEYS.len())], ValueVariant::Nil),
+    })
+}
+
+fn rect(rng: &mut Rng) -> ValueVariant {
+    let numbers = [0.0, 0.0, rng.below(400) as f64, rng.below(800) as f64];
+    ValueVariant::Data(numbers.iter().flat_map(|n| n.to_le_bytes()).collect())
+}
+
+fn point(rng: &mut Rng) -> ValueVariant {
+    let numbers = [rng.below(400) as f64, rng.below(800) as f64];
+    ValueVariant::Data(numbers.iter().flat_map(|n| n.to_le_bytes()).collect())
+}
+
+fn string(rng: &mut Rng) -> ValueVariant {
+    let word = WORDS[rng.below(WORDS.len())];
+    let s = match rng.below(4) {
+        0 => format!("{word} {}", rng.below(100)),
+        _ => word.to_string(),
+    };
+    ValueVariant::Data(s.into_bytes())
+}
+
+/// Collects elements of an archive, interning keys and class names.
+#[derive(Default)]
+struct Builder {
+    objects: Vec<Object>,
+    keys: Vec<String>,
+    values: Vec<Value>,
+    class_names: Vec<ClassName>,
+}
+
+impl Builder {
+    fn key(&mut self, key: &str) -> KeyIndex {
+        match self.keys.iter().position(|k| k == key) {
+            Some(i) => KeyIndex(i as VarInt),
+            None => {
+                self.keys.push(key.to_string());
+                KeyIndex((self.keys.len() - 1) as VarInt)
+            }
+        }
+    }
+
+    fn value(&mut self, key: &str, value: ValueVariant) -> Value {
+        Value::new(self.key(key), value)
+    }
+
+    fn push_object(&mut self, class_name: &str, values: Vec<Value>) {
+        let class_name_index = match self.class_names.iter().position(|c| c.name() == class_name) {
+            Some(i) => i,
+            None => {
+                self.class_names.push(ClassName::with_fallbacks(
+                    class_name.to_string(),
+                    Vec::new(),
+                ));
+                self.class_names.len() - 1
+            }
+        };
+        self.objects.push(Object::new(
+            class_name_index as VarInt,
+            self.values.len() as VarInt,
+            values.len() as VarInt,
+        ));
+        self.values.extend(values);
+    }
+}
+
+/// A SplitMix64 generator, which is good enough for generating test data.
+struct Rng(u64);
+
+impl Rng {
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Returns a number in `0..n`. `n` has to be greater than zero.
+    fn below(&mut self, n: usize) -> usize {
+        (self.next_u64() % n as u64) as usize
+    }
+}
@@ -0,0 +1,92 @@
+//! Generation of internally consistent archives, shared by the `Arbitrary` implementation
+//! and the strategies of the `testing` module.
+
+use crate::{ClassName, ClassNameIndex, NIBArchive, Object, Value, ValueVariant};
+
+pub(crate) const MAX_CLASS_NAMES: usize = 16;
+pub(crate) const MAX_FALLBACK_CLASSES: usize = 2;
+pub(crate) const MAX_KEYS: usize = 32;
+pub(crate) const MAX_OBJECTS: usize = 64;
+pub(crate) const MAX_VALUES_PER_OBJECT: usize = 8;
+
+/// Raw parts of a generated archive. Indeces don't have to be in bounds, they're wrapped
+/// when the archive is built.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct ArchiveParts {
+    /// Class names along with indeces of their fallback classes.
+    pub class_names: Vec<(String, Vec<usize>)>,
+    pub keys: Vec<String>,
+    /// Class name indeces of objects along with their values and indeces of their keys.
+    pub objects: Vec<(usize, Vec<(usize, ValueVariant)>)>,
+    pub format_version: u32,
+    pub coder_version: u32,
+}
+
+impl ArchiveParts {
+    /// Builds an archive that survives an encoding/decoding round trip: keys are unique,
+    /// every index points to an existing element, values of objects are contiguous,
+    /// object references point to existing objects and [unknown](ValueVariant::Unknown)
+    /// values, which can't be decoded back, are replaced with nil.
+    ///
+    /// Empty class names, keys or objects are replaced with a single default element.
+    pub fn build(mut self) -> NIBArchive {
+        if self.class_names.is_empty() {
+            self.class_names.push((String::new(), Vec::new()));
+        }
+        if self.keys.is_empty() {
+            self.keys.push(String::new());
+        }
+        if self.objects.is_empty() {
+            self.objects.push((0, Vec::new()));
+        }
+        let class_names_count = self.class_names.len();
+        let keys_count = self.keys.len();
+        let objects_count = self.objects.len();
+
+        let class_names = self
+            .class_names
+            .into_iter()
+            .map(|(name, fallbacks)| {
+                let fallbacks = fallbacks
+                    .into_iter()
+                    .map(|i| ClassNameIndex((i % class_names_count) as i32))
+                    .collect();
+                ClassName::with_fallbacks(name, fallbacks)
+            })
+            .collect();
+
+        // Duplicate keys are merged during encoding, which would change key indeces
+        let mut keys: Vec<String> = Vec::with_capacity(keys_count);
+        for (i, mut key) in self.keys.into_iter().enumerate() {
+            while keys.contains(&key) {
+                key.push_str(&i.to_string());
+            }
+            keys.push(key);
+        }
+
+        let mut objects = Vec::with_capacity(objects_count);
+        let mut values = Vec::new();
+        for (class_name_index, object_values) in self.objects {
+            objects.push(Object::new(
+                (class_name_index % class_names_count) as i32,
+                values.len() as i32,
+                object_values.len() as i32,
+            ));
+            values.extend(object_values.into_iter().map(|(key_index, value)| {
+                let value = match value {
+                    ValueVariant::ObjectRef(index) => {
+                        ValueVariant::ObjectRef(index % objects_count as u32)
+                    }
+                    ValueVariant::Unknown { .. } => ValueVariant::Nil,
+                    value => value,
+                };
+                Value::new((key_index % keys_count) as i32, value)
+            }));
+        }
+
+        let mut archive = NIBArchive::new_unchecked(objects, keys, values, class_names);
+        archive.set_format_version(self.format_version);
+        archive.set_coder_version(self.coder_version);
+        archive
+    }
+}
@@ -0,0 +1,150 @@
+use crate::{consts::keys, ValueVariant};
+
+/// Keys of well-known values that store rectangles.
+const RECT_KEYS: &[&str] = &[
+    keys::UI_FRAME,
+    keys::UI_BOUNDS,
+    keys::NS_FRAME,
+    keys::NS_BOUNDS,
+    keys::NS_WINDOW_RECT,
+];
+/// Keys of well-known values that store points.
+const POINT_KEYS: &[&str] = &[keys::UI_CENTER, keys::UI_CONTENT_OFFSET, keys::NS_ORIGIN];
+/// Keys of well-known values that store sizes.
+const SIZE_KEYS: &[&str] = &[
+    keys::UI_CONTENT_SIZE,
+    keys::NS_FRAME_SIZE,
+    keys::NS_MIN_SIZE,
+    keys::NS_MAX_SIZE,
+];
+
+/// A point in a two-dimensional coordinate system, same as `CGPoint`.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Point {
+    pub x: f64,
+    pub y: f64,
+}
+
+/// Width and height values, same as `CGSize`.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Size {
+    pub width: f64,
+    pub height: f64,
+}
+
+/// Location and dimensions of a rectangle, same as `CGRect`.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Rect {
+    pub origin: Point,
+    pub size: Size,
+}
+
+/// A geometry value decoded from a value with a well-known key.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Geometry {
+    Point(Point),
+    Size(Size),
+    Rect(Rect),
+}
+
+impl std::fmt::Display for Point {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_fmt(format_args!("{{{}, {}}}", self.x, self.y))
+    }
+}
+
+impl std::fmt::Display for Size {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_fmt(format_args!("{{{}, {}}}", self.width, self.height))
+    }
+}
+
+impl std::fmt::Display for Rect {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_fmt(format_args!("{{{}, {}}}", self.origin, self.size))
+    }
+}
+
+impl std::fmt::Display for Geometry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Geometry::Point(v) => v.fmt(f),
+            Geometry::Size(v) => v.fmt(f),
+            Geometry::Rect(v) => v.fmt(f),
+        }
+    }
+}
+
+impl Geometry {
+    /// Decodes a geometry value if `key` is a well-known key of a rectangle
+    /// (e.g. `UIFrame`, `UIBounds`, `NSFrame`), a point (e.g. `UICenter`)
+    /// or a size (e.g. `UIContentSize`).
+    pub fn from_value(key: &str, value: &ValueVariant) -> Option<Self> {
+        if RECT_KEYS.contains(&key) {
+            value.as_rect().map(Geometry::Rect)
+        } else if POINT_KEYS.contains(&key) {
+            value.as_point().map(Geometry::Point)
+        } else if SIZE_KEYS.contains(&key) {
+            value.as_size().map(Geometry::Size)
+        } else {
+            None
+        }
+    }
+}
+
+impl ValueVariant {
+    /// Interprets a [data value](ValueVariant::Data) as a rectangle.
+    ///
+    /// The data has to contain 4 little-endian floats or doubles (x, y, width, height),
+    /// optionally prefixed with a type byte.
+    pub fn as_rect(&self) -> Option<Rect> {
+        let [x, y, width, height] = self.data_as_numbers()?;
+        Some(Rect {
+            origin: Point { x, y },
+            size: Size { width, height },
+        })
+    }
+
+    /// Interprets a [data value](ValueVariant::Data) as a point.
+    ///
+    /// The data has to contain 2 little-endian floats or doubles (x, y),
+    /// optionally prefixed with a type byte.
+    pub fn as_point(&self) -> Option<Point> {
+        let [x, y] = self.data_as_numbers()?;
+        Some(Point { x, y })
+    }
+
+    /// Interprets a [data value](ValueVariant::Data) as a size.
+    ///
+    /// The data has to contain 2 little-endian floats or doubles (width, height),
+    /// optionally prefixed with a type byte.
+    pub fn as_size(&self) -> Option<Size> {
+        let [width, height] = self.data_as_numbers()?;
+        Some(Size { width, height })
+    }
+
+    fn data_as_numbers<const N: usize>(&self) -> Option<[f64; N]> {
+        let ValueVariant::Data(data) = self else {
+            return None;
+        };
+        // Skip a type byte if there's one
+        let data = if data.len() % 4 == 1 {
+            &data[1..]
+        } else {
+            data
+        };
+        let mut result = [0.0; N];
+        if data.len() == N * 4 {
+            for (r, c) in result.iter_mut().zip(data.chunks_exact(4)) {
+                *r = f32::from_le_bytes(c.try_into().unwrap()).into();
+            }
+        } else if data.len() == N * 8 {
+            for (r, c) in result.iter_mut().zip(data.chunks_exact(8)) {
+                *r = f64::from_le_bytes(c.try_into().unwrap());
+            }
+        } else {
+            return None;
+        }
+        Some(result)
+    }
+}
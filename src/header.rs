@@ -1,8 +1,12 @@
-use crate::Error;
-use std::io::{Read, Seek};
+use crate::{
+    codec::{read_fixed, write_fixed, Endianness},
+    io::{Read, Seek},
+    Error, Section,
+};
+use alloc::vec::Vec;
 
 /// Represents a header of a NIB Archive.
-#[derive(Debug)]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
 pub(crate) struct Header {
     pub format_version: u32,
     pub coder_version: u32,
@@ -19,11 +23,9 @@ pub(crate) struct Header {
 impl Header {
     pub(crate) fn try_from_reader<T: Read + Seek>(reader: &mut T) -> Result<Self, Error> {
         // Reads 40 bytes of a header
-        let mut buf = [0; 4];
         let mut values = [0; 10];
         for item in &mut values {
-            reader.read_exact(&mut buf)?;
-            *item = u32::from_le_bytes(buf);
+            *item = read_fixed(reader, Endianness::Little)?;
         }
         Ok(Self {
             format_version: values[0],
@@ -39,18 +41,125 @@ impl Header {
         })
     }
 
+    /// Returns `true` if sections follow the header in order and don't exceed
+    /// a given file length.
+    pub(crate) fn is_sane(&self, length: u64) -> bool {
+        self.offset_objects == 50
+            && self.offset_objects <= self.offset_keys
+            && self.offset_keys <= self.offset_values
+            && self.offset_values <= self.offset_class_names
+            && self.offset_class_names as u64 <= length
+    }
+
+    /// Checks that sections are large enough to hold the counts of elements, so counts
+    /// can be safely used to preallocate memory. Every object takes at least 3 bytes,
+    /// every key takes at least 1 byte and every value takes at least 2 bytes.
+    pub(crate) fn check_counts(&self) -> Result<(), Error> {
+        let sections = [
+            (
+                Section::Objects,
+                self.object_count,
+                3,
+                self.offset_objects,
+                self.offset_keys,
+            ),
+            (
+                Section::Keys,
+                self.key_count,
+                1,
+                self.offset_keys,
+                self.offset_values,
+            ),
+            (
+                Section::Values,
+                self.value_count,
+                2,
+                self.offset_values,
+                self.offset_class_names,
+            ),
+        ];
+        for (section, count, min_size, start, end) in sections {
+            if count as u64 * min_size > end.saturating_sub(start) as u64 {
+                return Err(Error::SectionTooShort { section, count });
+            }
+        }
+        Ok(())
+    }
+
     pub(crate) fn to_bytes(&self) -> Vec<u8> {
         let mut result = Vec::with_capacity(40);
-        result.extend(&self.format_version.to_le_bytes());
-        result.extend(&self.coder_version.to_le_bytes());
-        result.extend(&self.object_count.to_le_bytes());
-        result.extend(&self.offset_objects.to_le_bytes());
-        result.extend(&self.key_count.to_le_bytes());
-        result.extend(&self.offset_keys.to_le_bytes());
-        result.extend(&self.value_count.to_le_bytes());
-        result.extend(&self.offset_values.to_le_bytes());
-        result.extend(&self.class_name_count.to_le_bytes());
-        result.extend(&self.offset_class_names.to_le_bytes());
+        write_fixed(self.format_version, &mut result, Endianness::Little);
+        write_fixed(self.coder_version, &mut result, Endianness::Little);
+        write_fixed(self.object_count, &mut result, Endianness::Little);
+        write_fixed(self.offset_objects, &mut result, Endianness::Little);
+        write_fixed(self.key_count, &mut result, Endianness::Little);
+        write_fixed(self.offset_keys, &mut result, Endianness::Little);
+        write_fixed(self.value_count, &mut result, Endianness::Little);
+        write_fixed(self.offset_values, &mut result, Endianness::Little);
+        write_fixed(self.class_name_count, &mut result, Endianness::Little);
+        write_fixed(self.offset_class_names, &mut result, Endianness::Little);
         result
     }
 }
+
+/// Counts and offsets of sections stored in a header of a NIB Archive.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HeaderInfo(Header);
+
+impl From<Header> for HeaderInfo {
+    fn from(header: Header) -> Self {
+        Self(header)
+    }
+}
+
+impl HeaderInfo {
+    /// Returns the format version.
+    pub fn format_version(&self) -> u32 {
+        self.0.format_version
+    }
+
+    /// Returns the coder version.
+    pub fn coder_version(&self) -> u32 {
+        self.0.coder_version
+    }
+
+    /// Returns the count of objects.
+    pub fn object_count(&self) -> u32 {
+        self.0.object_count
+    }
+
+    /// Returns the offset of the objects section.
+    pub fn offset_objects(&self) -> u32 {
+        self.0.offset_objects
+    }
+
+    /// Returns the count of keys.
+    pub fn key_count(&self) -> u32 {
+        self.0.key_count
+    }
+
+    /// Returns the offset of the keys section.
+    pub fn offset_keys(&self) -> u32 {
+        self.0.offset_keys
+    }
+
+    /// Returns the count of values.
+    pub fn value_count(&self) -> u32 {
+        self.0.value_count
+    }
+
+    /// Returns the offset of the values section.
+    pub fn offset_values(&self) -> u32 {
+        self.0.offset_values
+    }
+
+    /// Returns the count of class names.
+    pub fn class_name_count(&self) -> u32 {
+        self.0.class_name_count
+    }
+
+    /// Returns the offset of the class names section.
+    pub fn offset_class_names(&self) -> u32 {
+        self.0.offset_class_names
+    }
+}
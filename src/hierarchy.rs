@@ -0,0 +1,408 @@
+use crate::{
+    consts::{self, classes, keys},
+    DecodeOptions, NIBArchive, Point, Rect, ValueVariant,
+};
+use std::{collections::HashMap, fmt};
+
+const TAG_KEYS: &[&str] = &[keys::UI_TAG, keys::NS_TAG];
+const IDENTIFIER_KEYS: &[&str] = &[
+    keys::UI_ACCESSIBILITY_IDENTIFIER,
+    keys::NS_ACCESSIBILITY_IDENTIFIER,
+];
+
+/// A view and its subviews reconstructed from an archive.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ViewNode {
+    /// Index of the view object.
+    pub index: usize,
+    pub class_name: String,
+    pub frame: Option<Rect>,
+    pub tag: Option<i64>,
+    pub accessibility_identifier: Option<String>,
+    pub subviews: Vec<ViewNode>,
+    /// Top level views of archives embedded into the view or into objects it owns.
+    /// Their indeces refer to objects of the embedded archive.
+    ///
+    /// Only filled if [HierarchyOptions::set_embedded_archives()] is enabled.
+    pub embedded: Vec<ViewNode>,
+}
+
+/// Options that control [NIBArchive::view_hierarchy_with_options()].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct HierarchyOptions {
+    embedded_archives: bool,
+    decode_options: DecodeOptions,
+}
+
+impl HierarchyOptions {
+    /// Creates default options, which don't recurse into embedded archives.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns whether views of embedded archives are reconstructed.
+    pub fn embedded_archives(&self) -> bool {
+        self.embedded_archives
+    }
+
+    /// Enables or disables reconstruction of views of
+    /// [embedded archives](crate::ValueVariant::as_embedded_archive()).
+    pub fn set_embedded_archives(&mut self, value: bool) {
+        self.embedded_archives = value;
+    }
+
+    /// Returns options that embedded archives are decoded with.
+    pub fn decode_options(&self) -> &DecodeOptions {
+        &self.decode_options
+    }
+
+    /// Sets options that embedded archives are decoded with.
+    ///
+    /// Their [limits](DecodeOptions::limits()) also cap the hierarchy: views and embedded
+    /// archives may be nested only as deep as [Limits::max_depth()](crate::Limits::max_depth())
+    /// allows, and embedded archives are decoded only until their total size reaches
+    /// [Limits::max_total_size()](crate::Limits::max_total_size()).
+    pub fn set_decode_options(&mut self, options: DecodeOptions) {
+        self.decode_options = options;
+    }
+}
+
+/// Formats a view and its subviews as an indented tree, similar to
+/// `-[UIView recursiveDescription]`.
+impl fmt::Display for ViewNode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.fmt_with_depth(f, 0)
+    }
+}
+
+impl ViewNode {
+    fn fmt_with_depth(&self, f: &mut fmt::Formatter<'_>, depth: usize) -> fmt::Result {
+        write!(
+            f,
+            "{}<{}: #{}",
+            "   | ".repeat(depth),
+            self.class_name,
+            self.index
+        )?;
+        if let Some(frame) = &self.frame {
+            write!(f, "; frame = {frame}")?;
+        }
+        if let Some(tag) = self.tag {
+            write!(f, "; tag = {tag}")?;
+        }
+        if let Some(identifier) = &self.accessibility_identifier {
+            write!(f, "; identifier = {identifier:?}")?;
+        }
+        writeln!(f, ">")?;
+        for subview in &self.subviews {
+            subview.fmt_with_depth(f, depth + 1)?;
+        }
+        if !self.embedded.is_empty() {
+            writeln!(f, "{}<embedded archive>", "   | ".repeat(depth + 1))?;
+            for view in &self.embedded {
+                view.fmt_with_depth(f, depth + 2)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl NIBArchive {
+    /// Reconstructs view hierarchies of the archive by following `UISubviews` and
+    /// `NSSubviews` references. Returns top level views, i.e. views that aren't
+    /// subviews of any other view.
+    pub fn view_hierarchy(&self) -> Vec<ViewNode> {
+        self.view_hierarchy_with_options(&HierarchyOptions::default())
+    }
+
+    /// Same as [NIBArchive::view_hierarchy()], but uses given options.
+    ///
+    /// Views of embedded archives are attached to the [nearest view](ViewNode::embedded)
+    /// that references the object containing the archive. If there's no such view,
+    /// they're returned after top level views of this archive. Embedded archives that
+    /// fail to decode or exceed [limits](HierarchyOptions::set_decode_options()) are skipped.
+    pub fn view_hierarchy_with_options(&self, options: &HierarchyOptions) -> Vec<ViewNode> {
+        let mut budget = options.decode_options().limits().max_total_size();
+        self.nested_view_hierarchy(options, 0, &mut budget)
+    }
+
+    /// Reconstructs view hierarchies of an archive embedded `depth` levels deep.
+    /// `budget` is the size of embedded archives that may still be decoded.
+    fn nested_view_hierarchy(
+        &self,
+        options: &HierarchyOptions,
+        depth: u32,
+        budget: &mut u64,
+    ) -> Vec<ViewNode> {
+        let views: Vec<usize> = (0..self.objects.len())
+            .filter(|i| self.is_view(*i))
+            .collect();
+        let mut is_subview = vec![false; self.objects.len()];
+        for view in &views {
+            for subview in self.subview_indeces(*view) {
+                is_subview[subview] = true;
+            }
+        }
+        // Identifiers set in Interface Builder are usually stored as runtime attributes
+        let identifiers: HashMap<usize, String> = self
+            .runtime_attributes()
+            .into_iter()
+            .filter(|attr| attr.key_path == "accessibilityIdentifier")
+            .filter_map(|attr| Some((attr.object, self.resolve_string(&attr.value)?)))
+            .collect();
+        let (embedded, unowned) = if options.embedded_archives() {
+            self.embedded_views(options, depth, budget)
+        } else {
+            Default::default()
+        };
+        let mut builder = Builder {
+            archive: self,
+            identifiers,
+            embedded,
+            visited: vec![false; self.objects.len()],
+            max_depth: options.decode_options().limits().max_depth(),
+        };
+        let mut result: Vec<ViewNode> = views
+            .into_iter()
+            .filter(|i| !is_subview[*i])
+            .filter_map(|i| builder.view_node(i, 0))
+            .collect();
+        result.extend(unowned);
+        result
+    }
+
+    /// Reconstructs views of embedded archives. Returns them grouped by indeces
+    /// of views owning them, and views that have no owner.
+    fn embedded_views(
+        &self,
+        options: &HierarchyOptions,
+        depth: u32,
+        budget: &mut u64,
+    ) -> (HashMap<usize, Vec<ViewNode>>, Vec<ViewNode>) {
+        let decode_options = options.decode_options();
+        if depth >= decode_options.limits().max_depth() {
+            return Default::default();
+        }
+        // The first object referencing each object
+        let mut owners: Vec<Option<usize>> = vec![None; self.objects.len()];
+        for (i, obj) in self.objects.iter().enumerate() {
+            for val in self.object_values(obj) {
+                if let ValueVariant::ObjectRef(r) = val.value() {
+                    if let Some(owner @ None) = owners.get_mut(*r as usize) {
+                        *owner = Some(i);
+                    }
+                }
+            }
+        }
+        let mut owned: HashMap<usize, Vec<ViewNode>> = HashMap::new();
+        let mut unowned = Vec::new();
+        let embedded = self.objects.iter().enumerate().flat_map(|(i, obj)| {
+            self.object_values(obj)
+                .iter()
+                .filter(|val| val.value().is_embedded_archive())
+                .map(move |val| (i, val.value()))
+        });
+        for (index, value) in embedded {
+            let ValueVariant::Data(data) = value else {
+                continue;
+            };
+            // Every level holds a copy of the levels below it, so their sizes add up
+            let Some(rest) = budget.checked_sub(data.len() as u64) else {
+                continue;
+            };
+            *budget = rest;
+            let Some(Ok(archive)) = value.as_embedded_archive_with_options(decode_options) else {
+                continue;
+            };
+            let views = archive.nested_view_hierarchy(options, depth + 1, budget);
+            // Walk up the owners until a view is found, guarding against cycles
+            let owner = std::iter::successors(Some(index), |i| owners[*i])
+                .take(self.objects.len())
+                .find(|i| self.is_view(*i));
+            match owner {
+                Some(owner) => owned.entry(owner).or_default().extend(views),
+                None => unowned.extend(views),
+            }
+        }
+        (owned, unowned)
+    }
+
+    /// UIKit views are often encoded with `UIBounds` and `UICenter` instead of a frame.
+    fn frame_from_bounds(&self, index: usize) -> Option<Rect> {
+        let bounds = self
+            .object_value(index, keys::UI_BOUNDS)?
+            .value()
+            .as_rect()?;
+        let center = self
+            .object_value(index, keys::UI_CENTER)?
+            .value()
+            .as_point()?;
+        Some(Rect {
+            origin: Point {
+                x: center.x - bounds.size.width / 2.0,
+                y: center.y - bounds.size.height / 2.0,
+            },
+            size: bounds.size,
+        })
+    }
+
+    pub(crate) fn is_view(&self, index: usize) -> bool {
+        self.is_kind_of(index, classes::UI_VIEW)
+            || self.is_kind_of(index, classes::NS_VIEW)
+            || self.is_registered_view(index)
+            || [keys::FRAME, keys::SUBVIEWS]
+                .iter()
+                .flat_map(|keys| keys.iter())
+                .chain(&[keys::UI_BOUNDS])
+                .any(|key| self.object_value(index, key).is_some())
+    }
+
+    /// Checks the class of an object and its fallback classes against
+    /// the [registry](consts::CLASSES), which knows subclasses of views like `UILabel`.
+    fn is_registered_view(&self, index: usize) -> bool {
+        let Some(class_name) = self
+            .objects
+            .get(index)
+            .and_then(|obj| self.class_names.get(obj.class_name_id().as_usize()))
+        else {
+            return false;
+        };
+        let fallbacks = class_name
+            .fallback_class_ids()
+            .iter()
+            .filter_map(|i| self.class_names.get(i.as_usize()));
+        std::iter::once(class_name)
+            .chain(fallbacks)
+            .filter_map(|c| consts::class_info(c.name()))
+            .any(|c| c.is_kind_of(classes::UI_VIEW) || c.is_kind_of(classes::NS_VIEW))
+    }
+
+    fn subview_indeces(&self, index: usize) -> Vec<usize> {
+        let Some(key) = keys::SUBVIEWS
+            .iter()
+            .find(|key| self.object_value(index, key).is_some())
+        else {
+            return Vec::new();
+        };
+        self.array_values(index, key)
+            .into_iter()
+            .filter_map(|v| match v {
+                ValueVariant::ObjectRef(r) if (*r as usize) < self.objects.len() => {
+                    Some(*r as usize)
+                }
+                _ => None,
+            })
+            .collect()
+    }
+}
+
+/// State of reconstruction of view hierarchies.
+struct Builder<'a> {
+    archive: &'a NIBArchive,
+    identifiers: HashMap<usize, String>,
+    /// Views of embedded archives by indeces of views owning them.
+    embedded: HashMap<usize, Vec<ViewNode>>,
+    visited: Vec<bool>,
+    max_depth: u32,
+}
+
+impl Builder<'_> {
+    /// Returns a view and its subviews. Subviews nested deeper than
+    /// [Limits::max_depth()] are left out.
+    fn view_node(&mut self, index: usize, depth: u32) -> Option<ViewNode> {
+        if self.visited[index] {
+            return None;
+        }
+        self.visited[index] = true;
+        let archive = self.archive;
+        let find = |keys: &[&str]| {
+            keys.iter()
+                .find_map(|key| archive.object_value(index, key))
+                .map(|v| v.value())
+        };
+        let class_name = archive
+            .class_names
+            .get(archive.objects[index].class_name_id().as_usize())
+            .map_or_else(String::new, |c| c.name().to_string());
+        let accessibility_identifier = IDENTIFIER_KEYS
+            .iter()
+            .find_map(|key| archive.string_value(index, key))
+            .or_else(|| self.identifiers.get(&index).cloned());
+        let subviews = if depth < self.max_depth {
+            archive
+                .subview_indeces(index)
+                .into_iter()
+                .filter_map(|i| self.view_node(i, depth + 1))
+                .collect()
+        } else {
+            Vec::new()
+        };
+        Some(ViewNode {
+            index,
+            class_name,
+            frame: find(keys::FRAME)
+                .and_then(|v| v.as_rect())
+                .or_else(|| archive.frame_from_bounds(index)),
+            tag: find(TAG_KEYS).and_then(ValueVariant::as_i64),
+            accessibility_identifier,
+            subviews,
+            embedded: self.embedded.remove(&index).unwrap_or_default(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ClassName, ClassNameIndex, KeyIndex, Limits, Object, Value};
+
+    /// A view that embeds an archive, if there's one.
+    fn view(embedded: Option<&NIBArchive>) -> NIBArchive {
+        let values = embedded
+            .map(|archive| Value::new(KeyIndex(0), ValueVariant::Data(archive.to_bytes())))
+            .into_iter()
+            .collect::<Vec<_>>();
+        NIBArchive::new(
+            vec![Object::new(ClassNameIndex(0), 0, values.len() as i32)],
+            vec!["archiveData".into()],
+            values,
+            vec![ClassName::with_fallbacks("UIView".into(), Vec::new())],
+        )
+        .unwrap()
+    }
+
+    fn depth(node: &ViewNode) -> usize {
+        node.embedded.first().map_or(0, |node| depth(node) + 1)
+    }
+
+    #[test]
+    fn embedded_archives() {
+        let mut archive = view(None);
+        for _ in 0..10 {
+            archive = view(Some(&archive));
+        }
+        let mut options = HierarchyOptions::new();
+        assert_eq!(depth(&archive.view_hierarchy_with_options(&options)[0]), 0);
+
+        options.set_embedded_archives(true);
+        assert_eq!(depth(&archive.view_hierarchy_with_options(&options)[0]), 10);
+
+        let mut limits = Limits::new();
+        limits.set_max_depth(3);
+        let mut decode_options = DecodeOptions::new();
+        decode_options.set_limits(limits.clone());
+        options.set_decode_options(decode_options.clone());
+        assert_eq!(depth(&archive.view_hierarchy_with_options(&options)[0]), 3);
+
+        // The outermost embedded archive alone takes the whole budget
+        let embedded = archive.values()[0].value();
+        let ValueVariant::Data(data) = embedded else {
+            unreachable!();
+        };
+        limits.set_max_depth(256);
+        limits.set_max_total_size(data.len() as u64);
+        decode_options.set_limits(limits);
+        options.set_decode_options(decode_options);
+        assert_eq!(depth(&archive.view_hierarchy_with_options(&options)[0]), 1);
+    }
+}
@@ -0,0 +1,118 @@
+use crate::{ClassName, NIBArchive, Object, Value, VarInt};
+use alloc::string::String;
+
+/// Defines a newtype around a raw [VarInt] index, so indeces of different arrays
+/// can't be mixed up.
+macro_rules! index_type {
+    ($(#[$attr:meta])* $name:ident) => {
+        $(#[$attr])*
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+        #[cfg_attr(
+            feature = "serde",
+            derive(serde::Serialize, serde::Deserialize),
+            serde(transparent)
+        )]
+        #[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+        pub struct $name(pub VarInt);
+
+        impl $name {
+            /// Returns the index as `usize`. Negative indeces become huge,
+            /// so they're always out of bounds. Use [Object::value_range()](crate::Object::value_range())
+            /// or checked arithmetic when adding to it.
+            pub fn as_usize(self) -> usize {
+                self.0 as usize
+            }
+
+            /// Adds an offset to the index. Returns `None` if the result overflows.
+            pub fn checked_add(self, rhs: VarInt) -> Option<Self> {
+                self.0.checked_add(rhs).map(Self)
+            }
+
+            /// Subtracts an offset from the index. Returns `None` if the result overflows.
+            pub fn checked_sub(self, rhs: VarInt) -> Option<Self> {
+                self.0.checked_sub(rhs).map(Self)
+            }
+        }
+
+        impl From<VarInt> for $name {
+            fn from(value: VarInt) -> Self {
+                Self(value)
+            }
+        }
+
+        impl From<$name> for VarInt {
+            fn from(value: $name) -> Self {
+                value.0
+            }
+        }
+
+        /// Saturates instead of overflowing, so an overflowing index stays out of bounds.
+        impl core::ops::Add<VarInt> for $name {
+            type Output = Self;
+
+            fn add(self, rhs: VarInt) -> Self {
+                Self(self.0.saturating_add(rhs))
+            }
+        }
+
+        /// Saturates instead of overflowing, so an overflowing index stays out of bounds.
+        impl core::ops::Sub<VarInt> for $name {
+            type Output = Self;
+
+            fn sub(self, rhs: VarInt) -> Self {
+                Self(self.0.saturating_sub(rhs))
+            }
+        }
+
+        impl PartialEq<VarInt> for $name {
+            fn eq(&self, other: &VarInt) -> bool {
+                self.0 == *other
+            }
+        }
+
+        impl core::fmt::Display for $name {
+            fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+                self.0.fmt(f)
+            }
+        }
+    };
+}
+
+index_type!(
+    /// An index of an [Object](crate::Object) of an archive.
+    ObjectIndex
+);
+index_type!(
+    /// An index of a key of an archive.
+    KeyIndex
+);
+index_type!(
+    /// An index of a [Value](crate::Value) of an archive.
+    ValueIndex
+);
+index_type!(
+    /// An index of a [ClassName](crate::ClassName) of an archive.
+    ClassNameIndex
+);
+
+impl NIBArchive {
+    /// Returns an object at a given index.
+    pub fn object(&self, index: ObjectIndex) -> Option<&Object> {
+        self.objects.get(index.as_usize())
+    }
+
+    /// Returns a key at a given index.
+    pub fn key(&self, index: KeyIndex) -> Option<&str> {
+        self.keys.get(index.as_usize()).map(String::as_str)
+    }
+
+    /// Returns a value at a given index.
+    pub fn value(&self, index: ValueIndex) -> Option<&Value> {
+        self.values.get(index.as_usize())
+    }
+
+    /// Returns a class name at a given index.
+    pub fn class_name(&self, index: ClassNameIndex) -> Option<&ClassName> {
+        self.class_names.get(index.as_usize())
+    }
+}
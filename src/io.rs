@@ -0,0 +1,223 @@
+//! Readers and writers used for decoding and encoding.
+//!
+//! With the `std` feature these are re-exports of [std::io]. Without it the module
+//! provides minimal replacements, so archives can be decoded from and encoded into
+//! byte slices and vectors in `no_std` environments.
+
+#[cfg(feature = "std")]
+pub use std::io::{Cursor, Error, ErrorKind, Read, Result, Seek, SeekFrom, Write};
+
+#[cfg(not(feature = "std"))]
+pub use no_std::*;
+
+#[cfg(not(feature = "std"))]
+mod no_std {
+    use alloc::vec::Vec;
+    use core::fmt;
+
+    /// A kind of [Error].
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    pub enum ErrorKind {
+        /// A reader ended before all requested bytes were read.
+        UnexpectedEof,
+        /// A seek went before the start of a stream.
+        InvalidInput,
+        /// A writer couldn't take all given bytes.
+        WriteZero,
+    }
+
+    /// An error of a reader or a writer.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    pub struct Error {
+        kind: ErrorKind,
+    }
+
+    impl Error {
+        pub fn new(kind: ErrorKind) -> Self {
+            Self { kind }
+        }
+
+        pub fn kind(&self) -> ErrorKind {
+            self.kind
+        }
+    }
+
+    impl From<ErrorKind> for Error {
+        fn from(kind: ErrorKind) -> Self {
+            Self::new(kind)
+        }
+    }
+
+    impl fmt::Display for Error {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            match self.kind {
+                ErrorKind::UnexpectedEof => write!(f, "failed to fill whole buffer"),
+                ErrorKind::InvalidInput => write!(f, "invalid seek to a negative position"),
+                ErrorKind::WriteZero => write!(f, "failed to write whole buffer"),
+            }
+        }
+    }
+
+    impl core::error::Error for Error {}
+
+    pub type Result<T> = core::result::Result<T, Error>;
+
+    /// A source of bytes.
+    pub trait Read {
+        /// Reads bytes into `buf` and returns their count. `0` means the end of the source.
+        fn read(&mut self, buf: &mut [u8]) -> Result<usize>;
+
+        /// Reads exactly enough bytes to fill `buf`.
+        fn read_exact(&mut self, mut buf: &mut [u8]) -> Result<()> {
+            while !buf.is_empty() {
+                match self.read(buf)? {
+                    0 => return Err(ErrorKind::UnexpectedEof.into()),
+                    n => buf = &mut buf[n..],
+                }
+            }
+            Ok(())
+        }
+    }
+
+    /// A position to seek to.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    pub enum SeekFrom {
+        Start(u64),
+        End(i64),
+        Current(i64),
+    }
+
+    /// A source of bytes with a position that can be moved.
+    pub trait Seek {
+        /// Moves the position and returns the new one from the start.
+        fn seek(&mut self, pos: SeekFrom) -> Result<u64>;
+
+        /// Returns the current position from the start.
+        fn stream_position(&mut self) -> Result<u64> {
+            self.seek(SeekFrom::Current(0))
+        }
+    }
+
+    /// A destination of bytes.
+    pub trait Write {
+        /// Writes bytes from `buf` and returns their count.
+        fn write(&mut self, buf: &[u8]) -> Result<usize>;
+
+        /// Writes buffered bytes, if any, to their destination.
+        fn flush(&mut self) -> Result<()>;
+
+        /// Writes all bytes from `buf`.
+        fn write_all(&mut self, mut buf: &[u8]) -> Result<()> {
+            while !buf.is_empty() {
+                match self.write(buf)? {
+                    0 => return Err(ErrorKind::WriteZero.into()),
+                    n => buf = &buf[n..],
+                }
+            }
+            Ok(())
+        }
+    }
+
+    /// Wraps bytes in memory and provides [Read] and [Seek] for them.
+    #[derive(Debug, Clone, Default, PartialEq, Eq)]
+    pub struct Cursor<T> {
+        inner: T,
+        position: u64,
+    }
+
+    impl<T> Cursor<T> {
+        pub fn new(inner: T) -> Self {
+            Self { inner, position: 0 }
+        }
+
+        pub fn position(&self) -> u64 {
+            self.position
+        }
+
+        pub fn set_position(&mut self, position: u64) {
+            self.position = position;
+        }
+
+        pub fn get_ref(&self) -> &T {
+            &self.inner
+        }
+
+        pub fn into_inner(self) -> T {
+            self.inner
+        }
+    }
+
+    impl<T: AsRef<[u8]>> Read for Cursor<T> {
+        fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+            let bytes = self.inner.as_ref();
+            let start = (self.position as usize).min(bytes.len());
+            let count = buf.len().min(bytes.len() - start);
+            buf[..count].copy_from_slice(&bytes[start..start + count]);
+            self.position += count as u64;
+            Ok(count)
+        }
+    }
+
+    impl<T: AsRef<[u8]>> Seek for Cursor<T> {
+        fn seek(&mut self, pos: SeekFrom) -> Result<u64> {
+            let (base, offset) = match pos {
+                SeekFrom::Start(position) => {
+                    self.position = position;
+                    return Ok(position);
+                }
+                SeekFrom::End(offset) => (self.inner.as_ref().len() as u64, offset),
+                SeekFrom::Current(offset) => (self.position, offset),
+            };
+            match base.checked_add_signed(offset) {
+                Some(position) => {
+                    self.position = position;
+                    Ok(position)
+                }
+                None => Err(ErrorKind::InvalidInput.into()),
+            }
+        }
+    }
+
+    impl Read for &[u8] {
+        fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+            let count = buf.len().min(self.len());
+            let (head, tail) = self.split_at(count);
+            buf[..count].copy_from_slice(head);
+            *self = tail;
+            Ok(count)
+        }
+    }
+
+    impl Write for Vec<u8> {
+        fn write(&mut self, buf: &[u8]) -> Result<usize> {
+            self.extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> Result<()> {
+            Ok(())
+        }
+    }
+
+    impl<R: Read + ?Sized> Read for &mut R {
+        fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+            (**self).read(buf)
+        }
+    }
+
+    impl<S: Seek + ?Sized> Seek for &mut S {
+        fn seek(&mut self, pos: SeekFrom) -> Result<u64> {
+            (**self).seek(pos)
+        }
+    }
+
+    impl<W: Write + ?Sized> Write for &mut W {
+        fn write(&mut self, buf: &[u8]) -> Result<usize> {
+            (**self).write(buf)
+        }
+
+        fn flush(&mut self) -> Result<()> {
+            (**self).flush()
+        }
+    }
+}
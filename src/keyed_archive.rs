@@ -0,0 +1,586 @@
+use crate::{
+    consts::{classes, keys},
+    ClassName, ClassNameIndex, DecodeOptions, Error, KeyIndex, NIBArchive, Object, Value,
+    ValueVariant, VarInt,
+};
+use plist::{Dictionary, Value as PlistValue};
+use std::collections::HashMap;
+
+const BPLIST_MAGIC: &[u8] = b"bplist00";
+
+/// A value decoded from an `NSKeyedArchiver` archive.
+#[derive(Debug, Clone, PartialEq)]
+pub enum KeyedValue {
+    Null,
+    Bool(bool),
+    Integer(i64),
+    Real(f64),
+    String(String),
+    Data(Vec<u8>),
+    Array(Vec<KeyedValue>),
+    /// An archived object with its class name and encoded fields.
+    ///
+    /// The root of a decoded archive is an object without a class whose fields are
+    /// the archive's top-level values.
+    Object {
+        class_name: Option<String>,
+        fields: Vec<(String, KeyedValue)>,
+    },
+    /// A reference to an object with a given UID that is already being decoded
+    /// higher in the tree. Used to break reference cycles.
+    Ref(u64),
+}
+
+impl ValueVariant {
+    /// Tries to decode a [data value](ValueVariant::Data) as a binary property list
+    /// `NSKeyedArchiver` archive.
+    ///
+    /// Returns `None` if the value isn't a data value or doesn't start with `bplist00`.
+    pub fn as_keyed_archive(&self) -> Option<Result<KeyedValue, Error>> {
+        match self {
+            ValueVariant::Data(data) if data.starts_with(BPLIST_MAGIC) => {
+                Some(decode_keyed_archive(data))
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Decodes an `NSKeyedArchiver` archive stored as a binary or XML property list.
+///
+/// Common Foundation collections (`NSArray`, `NSSet`, `NSDictionary`) and their mutable
+/// variants are decoded as arrays and objects, and `NSString`/`NSData` wrappers as strings
+/// and data. Other objects keep their class name and raw fields.
+pub fn decode_keyed_archive(bytes: &[u8]) -> Result<KeyedValue, Error> {
+    decode_keyed_archive_with_options(bytes, &DecodeOptions::default())
+}
+
+/// Same as [decode_keyed_archive()], but objects may be nested only as deep as
+/// [limits](crate::Limits::max_depth()) of given options allow.
+pub fn decode_keyed_archive_with_options(
+    bytes: &[u8],
+    options: &DecodeOptions,
+) -> Result<KeyedValue, Error> {
+    let root = parse_keyed_archive(bytes)?;
+    let (objects, top) = keyed_archive_parts(&root)?;
+    let mut decoder = KeyedDecoder {
+        objects,
+        nesting: Nesting::new(options),
+        decoded: HashMap::new(),
+    };
+    let fields = decoder.fields(top)?;
+    Ok(KeyedValue::Object {
+        class_name: None,
+        fields,
+    })
+}
+
+fn parse_keyed_archive(bytes: &[u8]) -> Result<PlistValue, Error> {
+    PlistValue::from_reader(std::io::Cursor::new(bytes))
+        .map_err(|e| Error::FormatError(format!("Invalid property list: {e}")))
+}
+
+/// Returns `$objects` and `$top` of a keyed archive.
+fn keyed_archive_parts(root: &PlistValue) -> Result<(&[PlistValue], &Dictionary), Error> {
+    let root = root
+        .as_dictionary()
+        .ok_or_else(|| Error::FormatError("Keyed archive root is not a dictionary".into()))?;
+    let objects = root
+        .get("$objects")
+        .and_then(PlistValue::as_array)
+        .ok_or_else(|| Error::FormatError("Keyed archive has no $objects".into()))?;
+    let top = root
+        .get("$top")
+        .and_then(PlistValue::as_dictionary)
+        .ok_or_else(|| Error::FormatError("Keyed archive has no $top".into()))?;
+    Ok((objects, top))
+}
+
+impl NIBArchive {
+    /// Converts a nib serialized by `NSKeyedArchiver`, the format used before NIB Archives,
+    /// into a NIB Archive. Both binary and XML property lists are accepted.
+    ///
+    /// The conversion is best-effort. The root object is an `NSObject` whose values are
+    /// the archive's top-level values. Strings become data values, arrays become `NSArray`
+    /// objects with `UINibEncoderEmptyKey` values, and superclasses of a class become
+    /// its fallback classes.
+    pub fn from_keyed_archive(bytes: &[u8]) -> Result<Self, Error> {
+        Self::from_keyed_archive_with_options(bytes, &DecodeOptions::default())
+    }
+
+    /// Same as [NIBArchive::from_keyed_archive()], but values may be nested only as deep as
+    /// [limits](crate::Limits::max_depth()) of given options allow.
+    pub fn from_keyed_archive_with_options(
+        bytes: &[u8],
+        options: &DecodeOptions,
+    ) -> Result<Self, Error> {
+        let root = parse_keyed_archive(bytes)?;
+        let (objects, top) = keyed_archive_parts(&root)?;
+
+        // Dictionaries describing classes aren't objects
+        let mut object_map = vec![None; objects.len()];
+        let mut count = 1;
+        for (uid, object) in objects.iter().enumerate() {
+            if let PlistValue::Dictionary(dict) = object {
+                if !dict.contains_key("$classname") {
+                    object_map[uid] = Some(count);
+                    count += 1;
+                }
+            }
+        }
+        let mut converter = KeyedConverter {
+            objects,
+            object_map,
+            keys: Vec::new(),
+            class_names: Vec::new(),
+            nib_objects: vec![(ClassNameIndex(0), Vec::new()); count as usize],
+            nesting: Nesting::new(options),
+            converted: HashMap::new(),
+        };
+        converter.nib_objects[0].0 = converter.class_name(classes::NS_OBJECT, &[]);
+        converter.nib_objects[0].1 = converter.fields(top)?;
+        for (uid, object) in objects.iter().enumerate() {
+            let (Some(index), PlistValue::Dictionary(dict)) = (converter.object_map[uid], object)
+            else {
+                continue;
+            };
+            let class_name = converter.object_class_name(dict);
+            converter.nib_objects[index as usize] = (class_name, converter.fields(dict)?);
+        }
+
+        let mut nib_objects = Vec::with_capacity(converter.nib_objects.len());
+        let mut values = Vec::new();
+        for (class_name_index, object_values) in converter.nib_objects {
+            nib_objects.push(Object::new(
+                class_name_index,
+                values.len() as VarInt,
+                object_values.len() as VarInt,
+            ));
+            values.extend(object_values);
+        }
+        Self::new(nib_objects, converter.keys, values, converter.class_names)
+    }
+}
+
+/// Returns a UID of a binary property list or a `CF$UID` dictionary
+/// an XML property list stores UIDs as.
+fn uid(value: &PlistValue) -> Option<u64> {
+    match value {
+        PlistValue::Uid(uid) => Some(uid.get()),
+        PlistValue::Dictionary(dict) if dict.len() == 1 => {
+            dict.get("CF$UID").and_then(PlistValue::as_unsigned_integer)
+        }
+        _ => None,
+    }
+}
+
+/// Converts keyed archive objects into NIB Archive elements.
+struct KeyedConverter<'a> {
+    objects: &'a [PlistValue],
+    /// Maps UIDs to indeces of objects of a NIB Archive.
+    object_map: Vec<Option<u32>>,
+    keys: Vec<String>,
+    class_names: Vec<ClassName>,
+    /// Class name indeces and values of objects.
+    nib_objects: Vec<(ClassNameIndex, Vec<Value>)>,
+    nesting: Nesting,
+    /// Values of UIDs that aren't objects and are already converted.
+    converted: HashMap<usize, ValueVariant>,
+}
+
+impl KeyedConverter<'_> {
+    fn fields(&mut self, dict: &Dictionary) -> Result<Vec<Value>, Error> {
+        let mut values = Vec::with_capacity(dict.len());
+        for (key, value) in dict {
+            if key == "$class" {
+                continue;
+            }
+            let value = self.value(value)?;
+            values.push(Value::new(self.key(key), value));
+        }
+        Ok(values)
+    }
+
+    fn value(&mut self, value: &PlistValue) -> Result<ValueVariant, Error> {
+        if let Some(uid) = uid(value) {
+            let uid = uid as usize;
+            return match (self.objects.get(uid), self.object_map.get(uid)) {
+                (_, Some(Some(index))) => Ok(ValueVariant::ObjectRef(*index)),
+                (Some(PlistValue::String(s)), _) if s == "$null" => Ok(ValueVariant::Nil),
+                (Some(PlistValue::Uid(_)) | None, _) => Err(Error::FormatError(format!(
+                    "Keyed archive UID {uid} is invalid"
+                ))),
+                (Some(other), _) => self.uid_value(uid, other),
+            };
+        }
+        Ok(match value {
+            PlistValue::Boolean(v) => ValueVariant::Bool(*v),
+            PlistValue::Integer(v) => match v.as_signed() {
+                Some(v) => ValueVariant::Int64(v),
+                None => ValueVariant::Double(v.as_unsigned().unwrap_or_default() as f64),
+            },
+            PlistValue::Real(v) => ValueVariant::Double(*v),
+            PlistValue::String(v) => ValueVariant::Data(v.as_bytes().to_vec()),
+            PlistValue::Data(v) => ValueVariant::Data(v.clone()),
+            PlistValue::Date(v) => ValueVariant::Data(v.to_xml_format().into_bytes()),
+            PlistValue::Array(array) => {
+                let key = self.key(keys::UI_NIB_ENCODER_EMPTY);
+                let values = array
+                    .iter()
+                    .map(|v| Ok(Value::new(key, self.value(v)?)))
+                    .collect::<Result<_, Error>>()?;
+                let class_name = self.class_name(classes::NS_ARRAY, &[]);
+                self.push_object(class_name, values)
+            }
+            PlistValue::Dictionary(dict) => {
+                let values = self.fields(dict)?;
+                let class_name = self.class_name(classes::NS_DICTIONARY, &[]);
+                self.push_object(class_name, values)
+            }
+            _ => ValueVariant::Nil,
+        })
+    }
+
+    /// Converts a value referenced by a UID that isn't mapped to an object, e.g. an array.
+    fn uid_value(&mut self, uid: usize, value: &PlistValue) -> Result<ValueVariant, Error> {
+        if let Some(value) = self.converted.get(&uid) {
+            return Ok(value.clone());
+        }
+        if !self.nesting.enter(uid as u64)? {
+            return Err(Error::FormatError(format!(
+                "Keyed archive UID {uid} references itself"
+            )));
+        }
+        let result = self.value(value);
+        self.nesting.leave();
+        let result = result?;
+        self.converted.insert(uid, result.clone());
+        Ok(result)
+    }
+
+    fn push_object(&mut self, class_name: ClassNameIndex, values: Vec<Value>) -> ValueVariant {
+        self.nib_objects.push((class_name, values));
+        ValueVariant::ObjectRef((self.nib_objects.len() - 1) as u32)
+    }
+
+    fn key(&mut self, key: &str) -> KeyIndex {
+        match self.keys.iter().position(|k| k == key) {
+            Some(i) => KeyIndex(i as VarInt),
+            None => {
+                self.keys.push(key.to_string());
+                KeyIndex((self.keys.len() - 1) as VarInt)
+            }
+        }
+    }
+
+    fn class_name(&mut self, name: &str, superclasses: &[&str]) -> ClassNameIndex {
+        if let Some(i) = self.class_names.iter().position(|c| c.name() == name) {
+            return ClassNameIndex(i as VarInt);
+        }
+        let fallbacks = superclasses
+            .iter()
+            .map(|name| self.class_name(name, &[]))
+            .collect();
+        self.class_names
+            .push(ClassName::with_fallbacks(name.to_string(), fallbacks));
+        ClassNameIndex((self.class_names.len() - 1) as VarInt)
+    }
+
+    fn object_class_name(&mut self, dict: &Dictionary) -> ClassNameIndex {
+        let class = dict
+            .get("$class")
+            .and_then(uid)
+            .and_then(|uid| self.objects.get(uid as usize))
+            .and_then(PlistValue::as_dictionary);
+        let name = class
+            .and_then(|c| c.get("$classname"))
+            .and_then(PlistValue::as_string)
+            .unwrap_or(classes::NS_OBJECT);
+        // The first class is the class itself
+        let superclasses: Vec<&str> = class
+            .and_then(|c| c.get("$classes"))
+            .and_then(PlistValue::as_array)
+            .map(|classes| {
+                classes
+                    .iter()
+                    .skip(1)
+                    .filter_map(PlistValue::as_string)
+                    .collect()
+            })
+            .unwrap_or_default();
+        self.class_name(name, &superclasses)
+    }
+}
+
+/// Guards recursion through UIDs against reference cycles and deep nesting.
+struct Nesting {
+    /// UIDs of objects being decoded.
+    stack: Vec<u64>,
+    max_depth: u32,
+}
+
+impl Nesting {
+    fn new(options: &DecodeOptions) -> Self {
+        Self {
+            stack: Vec::new(),
+            max_depth: options.limits().max_depth(),
+        }
+    }
+
+    /// Starts decoding an object with a given UID. Returns `false` if it's already
+    /// being decoded higher in the tree.
+    fn enter(&mut self, uid: u64) -> Result<bool, Error> {
+        if self.stack.contains(&uid) {
+            return Ok(false);
+        }
+        if self.stack.len() >= self.max_depth as usize {
+            return Err(Error::DepthExceeded {
+                max: self.max_depth,
+            });
+        }
+        self.stack.push(uid);
+        Ok(true)
+    }
+
+    fn leave(&mut self) {
+        self.stack.pop();
+    }
+}
+
+struct KeyedDecoder<'a> {
+    objects: &'a [PlistValue],
+    nesting: Nesting,
+    /// Objects that are already decoded by their UIDs.
+    decoded: HashMap<u64, KeyedValue>,
+}
+
+impl KeyedDecoder<'_> {
+    fn fields(&mut self, dict: &Dictionary) -> Result<Vec<(String, KeyedValue)>, Error> {
+        let mut fields = Vec::with_capacity(dict.len());
+        for (key, value) in dict {
+            if key == "$class" {
+                continue;
+            }
+            fields.push((key.clone(), self.value(value)?));
+        }
+        Ok(fields)
+    }
+
+    fn value(&mut self, value: &PlistValue) -> Result<KeyedValue, Error> {
+        if let Some(uid) = uid(value) {
+            return self.object(uid);
+        }
+        Ok(match value {
+            PlistValue::Boolean(v) => KeyedValue::Bool(*v),
+            PlistValue::Integer(v) => match v.as_signed() {
+                Some(v) => KeyedValue::Integer(v),
+                None => KeyedValue::Real(v.as_unsigned().unwrap_or_default() as f64),
+            },
+            PlistValue::Real(v) => KeyedValue::Real(*v),
+            PlistValue::String(v) => KeyedValue::String(v.clone()),
+            PlistValue::Data(v) => KeyedValue::Data(v.clone()),
+            PlistValue::Date(v) => KeyedValue::String(v.to_xml_format()),
+            PlistValue::Array(v) => {
+                KeyedValue::Array(v.iter().map(|v| self.value(v)).collect::<Result<_, _>>()?)
+            }
+            PlistValue::Dictionary(v) => KeyedValue::Object {
+                class_name: None,
+                fields: self.fields(v)?,
+            },
+            _ => KeyedValue::Null,
+        })
+    }
+
+    fn object(&mut self, uid: u64) -> Result<KeyedValue, Error> {
+        if let Some(value) = self.decoded.get(&uid) {
+            return Ok(value.clone());
+        }
+        if !self.nesting.enter(uid)? {
+            return Ok(KeyedValue::Ref(uid));
+        }
+        let result = self.decode_object(uid);
+        self.nesting.leave();
+        let result = result?;
+        self.decoded.insert(uid, result.clone());
+        Ok(result)
+    }
+
+    fn decode_object(&mut self, uid: u64) -> Result<KeyedValue, Error> {
+        let object = self
+            .objects
+            .get(uid as usize)
+            .ok_or_else(|| Error::FormatError(format!("Keyed archive UID {uid} out of bounds")))?;
+        let dict = match object {
+            PlistValue::String(s) if s == "$null" => return Ok(KeyedValue::Null),
+            PlistValue::Dictionary(dict) => dict,
+            other => return self.value(other),
+        };
+
+        let class_name = dict
+            .get("$class")
+            .and_then(self::uid)
+            .and_then(|uid| self.objects.get(uid as usize))
+            .and_then(PlistValue::as_dictionary)
+            .and_then(|class| class.get("$classname"))
+            .and_then(PlistValue::as_string)
+            .map(str::to_string);
+        Ok(match (class_name.as_deref(), dict.get(keys::NS_OBJECTS)) {
+            (Some(name), Some(objects))
+                if !dict.contains_key(keys::NS_KEYS) && is_collection(name) =>
+            {
+                self.value(objects)?
+            }
+            (Some(name), Some(PlistValue::Array(objects))) if is_collection(name) => {
+                let keys = dict
+                    .get(keys::NS_KEYS)
+                    .and_then(PlistValue::as_array)
+                    .map(Vec::as_slice)
+                    .unwrap_or_default();
+                let mut fields = Vec::with_capacity(keys.len());
+                for (key, value) in keys.iter().zip(objects) {
+                    let key = match self.value(key)? {
+                        KeyedValue::String(s) => s,
+                        other => format!("{other:?}"),
+                    };
+                    fields.push((key, self.value(value)?));
+                }
+                KeyedValue::Object {
+                    class_name: Some(name.to_string()),
+                    fields,
+                }
+            }
+            _ => match (dict.get(keys::NS_STRING), dict.get(keys::NS_BYTES)) {
+                (Some(string), _) => self.value(string)?,
+                (None, Some(bytes)) => self.value(bytes)?,
+                _ => KeyedValue::Object {
+                    fields: self.fields(dict)?,
+                    class_name,
+                },
+            },
+        })
+    }
+}
+
+fn is_collection(class_name: &str) -> bool {
+    matches!(
+        class_name,
+        classes::NS_ARRAY
+            | classes::NS_MUTABLE_ARRAY
+            | classes::NS_SET
+            | classes::NS_MUTABLE_SET
+            | classes::NS_ORDERED_SET
+            | classes::NS_MUTABLE_ORDERED_SET
+            | classes::NS_DICTIONARY
+            | classes::NS_MUTABLE_DICTIONARY
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use plist::Uid;
+
+    /// Encodes a binary keyed archive with given objects whose root is object 1.
+    fn keyed_archive(objects: Vec<PlistValue>) -> Vec<u8> {
+        let mut top = Dictionary::new();
+        top.insert("root".into(), PlistValue::Uid(Uid::new(1)));
+        let mut root = Dictionary::new();
+        root.insert("$archiver".into(), "NSKeyedArchiver".into());
+        root.insert("$top".into(), top.into());
+        root.insert("$objects".into(), objects.into());
+        let mut bytes = Vec::new();
+        PlistValue::Dictionary(root)
+            .to_writer_binary(&mut bytes)
+            .unwrap();
+        bytes
+    }
+
+    fn cf_uid(uid: u64) -> PlistValue {
+        let mut dict = Dictionary::new();
+        dict.insert("CF$UID".into(), uid.into());
+        dict.into()
+    }
+
+    #[test]
+    fn xml_references() {
+        let mut array = Dictionary::new();
+        array.insert("$class".into(), cf_uid(3));
+        array.insert("NS.objects".into(), vec![cf_uid(2)].into());
+        let mut class = Dictionary::new();
+        class.insert("$classname".into(), "NSArray".into());
+        class.insert(
+            "$classes".into(),
+            vec![PlistValue::from("NSArray"), "NSObject".into()].into(),
+        );
+        let mut top = Dictionary::new();
+        top.insert("root".into(), cf_uid(1));
+        let mut root = Dictionary::new();
+        root.insert("$archiver".into(), "NSKeyedArchiver".into());
+        root.insert("$top".into(), top.into());
+        root.insert(
+            "$objects".into(),
+            vec!["$null".into(), array.into(), "Title".into(), class.into()].into(),
+        );
+        let mut bytes = Vec::new();
+        PlistValue::Dictionary(root)
+            .to_writer_xml(&mut bytes)
+            .unwrap();
+
+        let KeyedValue::Object { fields, .. } = decode_keyed_archive(&bytes).unwrap() else {
+            panic!("root isn't an object");
+        };
+        assert_eq!(
+            fields,
+            [(
+                "root".into(),
+                KeyedValue::Array(vec![KeyedValue::String("Title".into())])
+            )]
+        );
+    }
+
+    #[test]
+    fn self_referencing_array() {
+        let bytes = keyed_archive(vec![
+            "$null".into(),
+            PlistValue::Array(vec![PlistValue::Uid(Uid::new(1))]),
+        ]);
+        let KeyedValue::Object { fields, .. } = decode_keyed_archive(&bytes).unwrap() else {
+            panic!("root isn't an object");
+        };
+        assert_eq!(
+            fields,
+            [("root".into(), KeyedValue::Array(vec![KeyedValue::Ref(1)]))]
+        );
+    }
+
+    #[test]
+    fn depth_limit() {
+        // Every array references the next one
+        let mut objects = vec![PlistValue::from("$null")];
+        for uid in 2..1000 {
+            objects.push(PlistValue::Array(vec![PlistValue::Uid(Uid::new(uid))]));
+        }
+        objects.push(PlistValue::Array(Vec::new()));
+        let bytes = keyed_archive(objects);
+        assert!(matches!(
+            decode_keyed_archive(&bytes),
+            Err(Error::DepthExceeded { max: 256 })
+        ));
+        assert!(matches!(
+            NIBArchive::from_keyed_archive(&bytes),
+            Err(Error::DepthExceeded { max: 256 })
+        ));
+    }
+
+    #[test]
+    fn convert_self_referencing_array() {
+        let bytes = keyed_archive(vec![
+            "$null".into(),
+            PlistValue::Array(vec![PlistValue::Uid(Uid::new(1))]),
+        ]);
+        assert!(matches!(
+            NIBArchive::from_keyed_archive(&bytes),
+            Err(Error::FormatError(_))
+        ));
+    }
+}
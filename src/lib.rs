@@ -1,16 +1,150 @@
 #![doc = include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/README.md"))]
+#![cfg_attr(not(feature = "std"), no_std)]
 
+extern crate alloc;
+
+/// After reading the current block of data we check that the current stream
+/// position is equal to the start position of a next block.
+macro_rules! check_position {
+    ($decoder:ident, $offset:expr, $section:expr) => {
+        if $decoder.position() != $offset as u64 {
+            return Err($crate::Error::OffsetMismatch {
+                section: $section,
+                expected: $offset as u64,
+                found: $decoder.position(),
+            });
+        }
+    };
+}
+
+/// Emits a [tracing] event if the `tracing` feature is enabled, otherwise does nothing.
+macro_rules! trace_event {
+    ($level:ident, $($arg:tt)+) => {
+        #[cfg(feature = "tracing")]
+        tracing::$level!($($arg)+);
+    };
+}
+
+mod borrowed;
 mod class_name;
+pub mod codec;
+pub mod consts;
+mod decoder;
 mod error;
 mod header;
+mod index;
+pub mod io;
 mod object;
+mod options;
+mod preserve;
+mod quirks;
+mod sniff;
 mod value;
-pub use crate::{class_name::*, error::*, object::*, value::*};
-use header::*;
+pub use crate::{
+    borrowed::*, class_name::*, error::*, header::HeaderInfo, index::*, object::*, options::*,
+    quirks::*, sniff::*, value::*,
+};
+use codec::{encode_var_int, var_int_len};
+use decoder::Decoder;
+use header::Header;
+use preserve::{original_bytes, PreservedEncoding};
+
+#[cfg(feature = "std")]
+mod accessibility;
+#[cfg(feature = "std")]
+pub mod batch;
+#[cfg(feature = "std")]
+mod color;
+#[cfg(feature = "std")]
+mod constraints;
+#[cfg(feature = "std")]
+mod csv;
+#[cfg(feature = "std")]
+mod edit;
+#[cfg(feature = "std")]
+mod find;
+#[cfg(feature = "std")]
+mod fingerprint;
+#[cfg(feature = "std")]
+mod from_nib;
+#[cfg(feature = "std")]
+pub mod gen;
+#[cfg(feature = "std")]
+mod geometry;
+#[cfg(feature = "std")]
+mod hierarchy;
+#[cfg(feature = "std")]
+mod lossy;
+#[cfg(feature = "std")]
+mod markdown;
+#[cfg(feature = "std")]
+mod prune;
+#[cfg(feature = "std")]
+mod pseudolocalize;
+#[cfg(feature = "std")]
+mod query;
+#[cfg(feature = "std")]
+mod redact;
+#[cfg(feature = "std")]
+mod runtime_attributes;
+#[cfg(feature = "std")]
+mod segues;
+#[cfg(feature = "std")]
+mod sequential;
+#[cfg(feature = "std")]
+mod size_report;
+#[cfg(feature = "std")]
+mod strings;
+#[cfg(feature = "std")]
+mod to_nib;
+#[cfg(feature = "std")]
+pub mod xib;
+#[cfg(feature = "std")]
+mod xliff;
+#[cfg(feature = "std")]
+pub use crate::{
+    accessibility::*, color::*, constraints::*, csv::*, from_nib::*, geometry::*, hierarchy::*,
+    lossy::RecoveryNote, markdown::*, prune::*, pseudolocalize::*, query::Expression, redact::*,
+    runtime_attributes::*, segues::*, size_report::*, strings::*, to_nib::*, xliff::*,
+};
 
+#[cfg(feature = "appkit")]
+pub mod appkit;
+#[cfg(feature = "tokio")]
+mod async_io;
+#[cfg(feature = "capi")]
+pub mod capi;
+#[cfg(any(feature = "msgpack", feature = "cbor"))]
+mod compact;
+#[cfg(feature = "derive")]
+pub use nibarchive_derive::{FromNib, ToNib};
+#[cfg(feature = "arbitrary")]
+mod fuzz;
+#[cfg(any(feature = "arbitrary", feature = "testing"))]
+mod generator;
+#[cfg(feature = "keyed-archive")]
+mod keyed_archive;
+#[cfg(feature = "keyed-archive")]
+pub use keyed_archive::*;
+#[cfg(feature = "plist")]
+mod property_list;
+#[cfg(feature = "plist")]
+pub use property_list::*;
+#[cfg(feature = "report")]
+pub mod report;
+#[cfg(feature = "sqlite")]
+pub mod sqlite;
+#[cfg(feature = "testing")]
+pub mod testing;
+#[cfg(feature = "uikit")]
+pub mod uikit;
+
+use alloc::{borrow::Cow, boxed::Box, string::String, vec::Vec};
+use io::{Cursor, Read, Seek, SeekFrom, Write};
+#[cfg(feature = "std")]
 use std::{
     fs::File,
-    io::{BufReader, BufWriter, Cursor, Read, Seek, SeekFrom, Write},
+    io::{BufReader, BufWriter},
 };
 
 const MAGIC_BYTES: &[u8; 10] = b"NIBArchive";
@@ -18,25 +152,48 @@ const DEFAULT_FORMAT_VERSION: u32 = 1;
 const DEFAULT_CODER_VERSION: u32 = 9;
 type VarInt = i32;
 
-/// After reading the current block of data we check that the current stream
-/// position is equal to the start position of a next block.
-macro_rules! check_position {
-    ($reader:ident, $offset:expr, $err:literal) => {
-        if $reader.stream_position()? != $offset as u64 {
-            return Err(Error::FormatError(format!(
-                "Expected {} offset at {} - got {}",
-                $err,
-                $reader.stream_position()?,
-                $offset
-            )));
-        }
-    };
+/// Keys and values of an archive prepared for encoding along with a header.
+///
+/// Values are borrowed from the archive and adjusted one by one while they're encoded.
+struct EncodingPlan<'s> {
+    header: Header,
+    keys: Cow<'s, [String]>,
+    values: &'s [Value],
+    /// Indeces of merged keys values refer to, if duplicate keys are merged.
+    key_map: Option<Vec<VarInt>>,
+    /// Whether values are stored with the smallest types that hold them.
+    minimize: bool,
+    /// Original bytes of objects, keys, values and class names sections
+    /// that are reused as is.
+    raw: [Option<&'s [u8]>; 4],
+}
+
+impl EncodingPlan<'_> {
+    /// Returns the key index and the value a given value is encoded with.
+    fn encoded_value<'v>(&self, val: &'v Value) -> (KeyIndex, Cow<'v, ValueVariant>) {
+        let key_index = self
+            .key_map
+            .as_ref()
+            .and_then(|map| map.get(val.key_id().as_usize()))
+            .map_or(val.key_id(), |index| KeyIndex(*index));
+        let value = match self.minimize.then(|| val.value().minimized()).flatten() {
+            Some(value) => Cow::Owned(value),
+            None => Cow::Borrowed(val.value()),
+        };
+        (key_index, value)
+    }
+
+    fn encoded_value_len(&self, val: &Value) -> usize {
+        let (key_index, value) = self.encoded_value(val);
+        value.encoded_len(key_index)
+    }
 }
 
 /// NIB Archive decoder/encoder.
 ///
 /// Look at the module docs for more details.
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct NIBArchive {
     objects: Vec<Object>,
     keys: Vec<String>,
@@ -44,6 +201,23 @@ pub struct NIBArchive {
     class_names: Vec<ClassName>,
     format_version: u32,
     coder_version: u32,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    preserved: Option<Box<PreservedEncoding>>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    minimize_value_widths: bool,
+}
+
+/// Archives are compared by their contents, the preserved original encoding
+/// and encoding settings are ignored.
+impl PartialEq for NIBArchive {
+    fn eq(&self, other: &Self) -> bool {
+        self.objects == other.objects
+            && self.keys == other.keys
+            && self.values == other.values
+            && self.class_names == other.class_names
+            && self.format_version == other.format_version
+            && self.coder_version == other.coder_version
+    }
 }
 
 impl NIBArchive {
@@ -57,13 +231,13 @@ impl NIBArchive {
         class_names: Vec<ClassName>,
     ) -> Result<Self, Error> {
         for obj in &objects {
-            Self::check_object(obj, values.len() as u32, class_names.len() as u32)?;
+            Self::check_object(obj, values.len() as u32, class_names.len() as u32, None)?;
         }
         for val in &values {
-            Self::check_value(val, keys.len() as u32)?;
+            Self::check_key_index(val.key_id(), keys.len() as u32, None)?;
         }
         for cls in &class_names {
-            Self::check_class_name(cls, class_names.len() as u32)?;
+            Self::check_fallback_classes(cls.fallback_class_ids(), class_names.len() as u32, None)?;
         }
         Ok(Self {
             objects,
@@ -72,6 +246,8 @@ impl NIBArchive {
             class_names,
             format_version: DEFAULT_FORMAT_VERSION,
             coder_version: DEFAULT_CODER_VERSION,
+            preserved: None,
+            minimize_value_widths: false,
         })
     }
 
@@ -93,10 +269,13 @@ impl NIBArchive {
             class_names,
             format_version: DEFAULT_FORMAT_VERSION,
             coder_version: DEFAULT_CODER_VERSION,
+            preserved: None,
+            minimize_value_widths: false,
         }
     }
 
     /// Reads and decodes a NIB Archive from a given file.
+    #[cfg(feature = "std")]
     pub fn from_file<P: AsRef<std::path::Path>>(path: P) -> Result<Self, Error> {
         let file = File::open(path)?;
         let mut reader = BufReader::new(file);
@@ -105,101 +284,187 @@ impl NIBArchive {
 
     /// Reads and decodes a NIB Archive from a given slice of byte.
     pub fn from_bytes<B: AsRef<[u8]>>(bytes: B) -> Result<Self, Error> {
-        let mut cursor = Cursor::new(bytes);
-        Self::from_reader(&mut cursor)
+        let (archive, _) =
+            Self::from_slice_with_options(bytes.as_ref(), &DecodeOptions::default())?;
+        Ok(archive)
     }
 
     /// Reads and decodes a NIB Archive from a given reader.
-    pub fn from_reader<T: Read + Seek>(mut reader: &mut T) -> Result<Self, Error> {
+    pub fn from_reader<T: Read + Seek>(reader: &mut T) -> Result<Self, Error> {
+        let (archive, _) = Self::from_reader_with_options(reader, &DecodeOptions::default())?;
+        Ok(archive)
+    }
+
+    /// Reads and decodes a NIB Archive from a given reader using given options.
+    ///
+    /// The whole archive is read into memory before decoding. Reading stops with an error
+    /// once the stream exceeds [the total size limit](Limits::max_total_size()).
+    ///
+    /// Returns the archive along with [warnings](DecodeWarning) about anomalies found
+    /// during decoding.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(name = "decode", skip_all, err)
+    )]
+    pub fn from_reader_with_options<T: Read + Seek>(
+        reader: &mut T,
+        options: &DecodeOptions,
+    ) -> Result<(Self, Vec<DecodeWarning>), Error> {
+        let bytes = Self::read_all(reader, options.limits().max_total_size())?;
+        Self::from_slice_with_options(&bytes, options)
+    }
+
+    /// Reads a whole stream from its start, but no more than `max` bytes.
+    fn read_all<T: Read + Seek>(reader: &mut T, max: u64) -> Result<Vec<u8>, Error> {
+        reader.seek(SeekFrom::Start(0))?;
+        Self::read_limited(reader, max)
+    }
+
+    /// Reads the rest of a stream, but no more than `max` bytes.
+    fn read_limited<T: Read>(reader: &mut T, max: u64) -> Result<Vec<u8>, Error> {
+        let mut bytes = Vec::new();
+        let mut chunk = [0; 8192];
+        loop {
+            let read = reader.read(&mut chunk)?;
+            if read == 0 {
+                return Ok(bytes);
+            }
+            bytes.extend_from_slice(&chunk[..read]);
+            if bytes.len() as u64 > max {
+                return Err(Error::LimitExceeded {
+                    limit: LimitKind::TotalSize,
+                    value: bytes.len() as u64,
+                    max,
+                    offset: max,
+                });
+            }
+        }
+    }
+
+    /// Decodes an archive with [NIBArchiveRef] and copies its contents.
+    fn from_slice_with_options(
+        bytes: &[u8],
+        options: &DecodeOptions,
+    ) -> Result<(Self, Vec<DecodeWarning>), Error> {
+        let (archive, header, end, warnings) = NIBArchiveRef::decode(bytes, options)?;
+        let mut archive = archive.to_owned();
+        if options.preserve_encoding() {
+            let preserved =
+                PreservedEncoding::capture(&mut Cursor::new(bytes), &header, end, &archive)?;
+            archive.preserved = Some(Box::new(preserved));
+        }
+        Ok((archive, warnings))
+    }
+
+    /// Reads the magic bytes and the header of a NIB Archive from a given reader
+    /// without decoding the rest of it.
+    pub fn peek_header<T: Read + Seek>(reader: &mut T) -> Result<HeaderInfo, Error> {
+        Self::read_header(reader).map(HeaderInfo::from)
+    }
+
+    fn read_header<T: Read + Seek>(reader: &mut T) -> Result<Header, Error> {
         reader.seek(SeekFrom::Start(0))?;
 
         // Check magic bytes
         let mut magic_bytes = [0; 10];
         reader.read_exact(&mut magic_bytes)?;
         if &magic_bytes != MAGIC_BYTES {
-            return Err(Error::FormatError("Magic bytes don't match".into()));
+            // The bytes already read are passed along, so the reader doesn't have to seek back
+            return Err(match sniff::sniff_continued(&magic_bytes, reader)? {
+                kind @ (FileKind::TypedStream | FileKind::KeyedArchive) => {
+                    Error::UnsupportedLegacyFormat(kind)
+                }
+                _ => Error::UnexpectedMagic,
+            });
         }
 
-        // Parse header
-        let header = Header::try_from_reader(&mut reader)?;
-        check_position!(reader, header.offset_objects, "object");
+        Header::try_from_reader(reader)
+    }
 
-        // Parse objects
-        let mut objects = Vec::with_capacity(header.object_count as usize);
-        for _ in 0..header.object_count {
-            let obj = Object::try_from_reader(&mut reader)?;
-            Self::check_object(&obj, header.value_count, header.class_name_count)?;
-            objects.push(obj);
-        }
-        check_position!(reader, header.offset_keys, "keys");
-
-        // Parse keys
-        let mut keys = Vec::with_capacity(header.key_count as usize);
-        for _ in 0..header.key_count {
-            let length = decode_var_int(&mut reader)?;
-            let mut name_bytes = vec![0; length as usize];
-            reader.read_exact(&mut name_bytes)?;
-            let name = String::from_utf8(name_bytes)?;
-            keys.push(name);
-        }
-        check_position!(reader, header.offset_values, "values");
-
-        // Parse values
-        let mut values = Vec::with_capacity(header.value_count as usize);
-        for _ in 0..header.value_count {
-            let val = Value::try_from_reader(&mut reader)?;
-            Self::check_value(&val, header.key_count)?;
-            values.push(val);
-        }
-        check_position!(reader, header.offset_class_names, "class names'");
-
-        // Parse class names
-        let mut class_names = Vec::with_capacity(header.class_name_count as usize);
-        for _ in 0..header.class_name_count {
-            let cls = ClassName::try_from_reader(&mut reader)?;
-            Self::check_class_name(&cls, header.class_name_count)?;
-            class_names.push(cls);
-        }
+    /// Checks versions, counts of elements against limits and sizes of sections.
+    fn check_header<T: Read + Seek>(
+        decoder: &mut Decoder<'_, T>,
+        header: &Header,
+    ) -> Result<(), Error> {
+        decoder.set_quirks(Quirks::for_versions(
+            header.format_version,
+            header.coder_version,
+        ));
+        decoder.check_count(header.object_count, LimitKind::ObjectCount)?;
+        decoder.check_count(header.key_count, LimitKind::KeyCount)?;
+        decoder.check_count(header.value_count, LimitKind::ValueCount)?;
+        decoder.check_count(header.class_name_count, LimitKind::ClassNameCount)?;
+        decoder.check_size(header.offset_class_names as u64)?;
+        header.check_counts()?;
+        check_position!(decoder, header.offset_objects, Section::Objects);
+        Ok(())
+    }
 
-        Ok(Self {
-            objects,
-            keys,
-            values,
-            class_names,
-            format_version: header.format_version,
-            coder_version: header.coder_version,
-        })
+    /// Returns the header this archive would be encoded with.
+    pub fn header_info(&self) -> HeaderInfo {
+        self.encoding_plan().header.into()
     }
 
-    fn check_object(obj: &Object, value_count: u32, class_name_count: u32) -> Result<(), Error> {
-        if (obj.values_index() + obj.value_count()) as u32 > value_count {
-            return Err(Error::FormatError("Value index out of bounds".into()));
+    /// Checks indeces of an object. `offset` is the position the object was decoded at.
+    fn check_object(
+        obj: &Object,
+        value_count: u32,
+        class_name_count: u32,
+        offset: Option<u64>,
+    ) -> Result<(), Error> {
+        // Negative indeces become huge when cast, so they're out of bounds as well
+        let end = obj.first_value_id().0 as u32 as u64 + obj.value_count() as u32 as u64;
+        if end > value_count as u64 {
+            return Err(Error::IndexOutOfBounds {
+                kind: IndexKind::Value,
+                index: end as i64 - 1,
+                max: value_count as u64,
+                offset,
+            });
         }
-        if obj.class_name_index() as u32 > class_name_count {
-            return Err(Error::FormatError("Class name index out of bounds".into()));
+        if obj.class_name_id().0 as u32 >= class_name_count {
+            return Err(Error::IndexOutOfBounds {
+                kind: IndexKind::ClassName,
+                index: obj.class_name_id().0 as i64,
+                max: class_name_count as u64,
+                offset,
+            });
         }
         Ok(())
     }
 
-    fn check_value(val: &Value, key_count: u32) -> Result<(), Error> {
-        if val.key_index() as u32 > key_count {
-            return Err(Error::FormatError("Key index out of bounds".into()));
+    fn check_key_index(index: KeyIndex, key_count: u32, offset: Option<u64>) -> Result<(), Error> {
+        if index.0 as u32 >= key_count {
+            return Err(Error::IndexOutOfBounds {
+                kind: IndexKind::Key,
+                index: index.0 as i64,
+                max: key_count as u64,
+                offset,
+            });
         }
         Ok(())
     }
 
-    fn check_class_name(cls: &ClassName, class_name_count: u32) -> Result<(), Error> {
-        for index in cls.fallback_classes_indeces() {
-            if *index as u32 > class_name_count {
-                return Err(Error::FormatError(
-                    "Class name (fallback class) index out of bounds".into(),
-                ));
+    fn check_fallback_classes(
+        fallback_classes_indeces: &[ClassNameIndex],
+        class_name_count: u32,
+        offset: Option<u64>,
+    ) -> Result<(), Error> {
+        for index in fallback_classes_indeces {
+            if index.0 as u32 >= class_name_count {
+                return Err(Error::IndexOutOfBounds {
+                    kind: IndexKind::ClassName,
+                    index: index.0 as i64,
+                    max: class_name_count as u64,
+                    offset,
+                });
             }
         }
         Ok(())
     }
 
     /// Encodes the given archive and saves it to a file with a given path.
+    #[cfg(feature = "std")]
     pub fn to_file<P: AsRef<std::path::Path>>(&self, path: P) -> Result<(), Error> {
         let file = File::create(path)?;
         let mut reader = BufWriter::new(file);
@@ -207,60 +472,214 @@ impl NIBArchive {
     }
 
     /// Encodes the given archive and returns a vector of bytes.
+    ///
+    /// The vector is allocated once with the [exact size](NIBArchive::encoded_len()).
     pub fn to_bytes(&self) -> Vec<u8> {
-        let mut cursor = Cursor::new(Vec::with_capacity(1024));
-        self.to_writer(&mut cursor).unwrap(); // should be safe since we're writing into a vector
-        cursor.into_inner()
+        let plan = self.encoding_plan();
+        let mut bytes = Vec::with_capacity(self.planned_len(&plan));
+        // should be safe since we're writing into a vector
+        self.write_planned(&plan, &mut bytes, None).unwrap();
+        bytes
+    }
+
+    /// Returns the exact number of bytes the archive takes when encoded,
+    /// without encoding it.
+    pub fn encoded_len(&self) -> usize {
+        self.planned_len(&self.encoding_plan())
+    }
+
+    fn planned_len(&self, plan: &EncodingPlan) -> usize {
+        let classes_len = plan.raw[3].map_or_else(
+            || self.class_names.iter().map(ClassName::encoded_len).sum(),
+            <[u8]>::len,
+        );
+        let trailing_len = self.preserved.as_ref().map_or(0, |p| p.trailing.len());
+        plan.header.offset_class_names as usize + classes_len + trailing_len
     }
 
     /// Encodes the given archive using a writer.
+    ///
+    /// Sizes of sections are computed beforehand, so elements are written to the writer
+    /// directly without buffering whole sections.
+    ///
+    /// Duplicate keys are written only once, unless the archive was decoded with
+    /// [preserved encoding](DecodeOptions::set_preserve_encoding()).
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(name = "encode", skip_all, err)
+    )]
     pub fn to_writer<T: Write>(&self, writer: &mut T) -> Result<(), Error> {
-        // Each objects contains 3 fields with up to 2 bytes VarInt
-        let mut objects_bytes = Vec::with_capacity(self.objects.len() * 3 * 2);
-        for obj in &self.objects {
-            objects_bytes.append(&mut obj.to_bytes());
-        }
+        self.write_planned(&self.encoding_plan(), writer, None)
+    }
 
-        // Let's estimate the average key length as 16 symbols
-        let mut keys_bytes = Vec::with_capacity(self.keys.len() * (16 + 2));
-        for key in &self.keys {
-            keys_bytes.append(&mut encode_var_int(key.len() as i32));
-            keys_bytes.extend(key.as_bytes());
-        }
+    /// Same as [NIBArchive::to_writer()], but calls `callback` with a section, the count
+    /// of its encoded elements and the total count of them, so a frontend can show
+    /// progress of large archives.
+    ///
+    /// The callback is called every 1024 elements and once a section is finished.
+    pub fn to_writer_with_progress<T, F>(&self, writer: &mut T, callback: F) -> Result<(), Error>
+    where
+        T: Write,
+        F: Fn(Section, u64, u64),
+    {
+        self.write_planned(&self.encoding_plan(), writer, Some(&callback))
+    }
+
+    fn write_planned<T: Write>(
+        &self,
+        plan: &EncodingPlan,
+        writer: &mut T,
+        progress: Option<&dyn Fn(Section, u64, u64)>,
+    ) -> Result<(), Error> {
+        let report = |section, done: usize, total: usize| {
+            if let Some(callback) = progress {
+                options::report_progress(callback, section, done as u64, total as u64);
+            }
+        };
+        trace_event!(
+            debug,
+            object_count = plan.header.object_count,
+            key_count = plan.header.key_count,
+            value_count = plan.header.value_count,
+            class_name_count = plan.header.class_name_count,
+            preserved_sections = plan.raw.iter().filter(|raw| raw.is_some()).count(),
+            "Encoding"
+        );
+        writer.write_all(MAGIC_BYTES)?;
+        writer.write_all(&plan.header.to_bytes())?;
+        let [objects_raw, keys_raw, values_raw, classes_raw] = plan.raw;
 
-        let mut values_bytes = Vec::with_capacity(self.values.len() * (8 + 2));
-        for val in &self.values {
-            values_bytes.append(&mut val.to_bytes());
+        match objects_raw {
+            Some(raw) => {
+                writer.write_all(raw)?;
+                report(Section::Objects, self.objects.len(), self.objects.len());
+            }
+            None => {
+                for (i, obj) in self.objects.iter().enumerate() {
+                    writer.write_all(&obj.to_bytes())?;
+                    report(Section::Objects, i + 1, self.objects.len());
+                }
+            }
+        }
+        match keys_raw {
+            Some(raw) => {
+                writer.write_all(raw)?;
+                report(Section::Keys, plan.keys.len(), plan.keys.len());
+            }
+            None => {
+                for (i, key) in plan.keys.iter().enumerate() {
+                    writer.write_all(&encode_var_int(key.len() as VarInt))?;
+                    writer.write_all(key.as_bytes())?;
+                    report(Section::Keys, i + 1, plan.keys.len());
+                }
+            }
+        }
+        match values_raw {
+            Some(raw) => {
+                writer.write_all(raw)?;
+                report(Section::Values, plan.values.len(), plan.values.len());
+            }
+            None => {
+                for (i, val) in plan.values.iter().enumerate() {
+                    let (key_index, value) = plan.encoded_value(val);
+                    writer.write_all(&value.to_bytes(key_index))?;
+                    report(Section::Values, i + 1, plan.values.len());
+                }
+            }
+        }
+        match classes_raw {
+            Some(raw) => {
+                writer.write_all(raw)?;
+                report(
+                    Section::ClassNames,
+                    self.class_names.len(),
+                    self.class_names.len(),
+                );
+            }
+            None => {
+                for (i, cls) in self.class_names.iter().enumerate() {
+                    writer.write_all(&cls.to_bytes())?;
+                    report(Section::ClassNames, i + 1, self.class_names.len());
+                }
+            }
         }
 
-        let mut classes_bytes = Vec::with_capacity(self.class_names.len() * 16);
-        for cls in &self.class_names {
-            classes_bytes.append(&mut cls.to_bytes());
+        if let Some(preserved) = &self.preserved {
+            writer.write_all(&preserved.trailing)?;
         }
+        writer.flush()?;
+
+        Ok(())
+    }
 
-        let header = Header {
+    /// Prepares keys and values for encoding, finds original sections that can be
+    /// reused and computes a header describing the sections.
+    fn encoding_plan(&self) -> EncodingPlan<'_> {
+        let preserved = self.preserved.as_deref();
+
+        // Duplicate keys are merged, so values have to be remapped.
+        // Keys of an archive with preserved encoding are kept as is, though.
+        let unique_keys = match preserved {
+            Some(_) => None,
+            None => preserve::unique_keys(&self.keys),
+        };
+        let (keys, key_map) = match unique_keys {
+            Some((keys, key_map)) => (Cow::Owned(keys), Some(key_map)),
+            None => (Cow::Borrowed(&self.keys[..]), None),
+        };
+        let minimize = self.minimize_value_widths && self.values.iter().any(Value::is_minimizable);
+
+        let raw = [
+            original_bytes(preserved.and_then(|p| p.objects.as_ref()), &self.objects),
+            original_bytes(preserved.and_then(|p| p.keys.as_ref()), &keys),
+            // Minimized values differ from the original ones
+            original_bytes(
+                preserved
+                    .and_then(|p| p.values.as_ref())
+                    .filter(|_| !minimize),
+                &self.values,
+            ),
+            original_bytes(
+                preserved.and_then(|p| p.class_names.as_ref()),
+                &self.class_names,
+            ),
+        ];
+        let objects_len = raw[0].map_or_else(
+            || self.objects.iter().map(Object::encoded_len).sum(),
+            <[u8]>::len,
+        );
+        let keys_len = raw[1].map_or_else(|| preserve::encoded_keys_len(&keys), <[u8]>::len);
+        let mut plan = EncodingPlan {
+            header: Header::default(),
+            keys,
+            values: &self.values,
+            key_map,
+            minimize,
+            raw,
+        };
+        let values_len = raw[2].map_or_else(
+            || {
+                self.values
+                    .iter()
+                    .map(|val| plan.encoded_value_len(val))
+                    .sum()
+            },
+            <[u8]>::len,
+        );
+
+        plan.header = Header {
             format_version: self.format_version,
             coder_version: self.coder_version,
             object_count: self.objects.len() as u32,
             offset_objects: 50,
-            key_count: self.keys.len() as u32,
-            offset_keys: (50 + objects_bytes.len()) as u32,
+            key_count: plan.keys.len() as u32,
+            offset_keys: (50 + objects_len) as u32,
             value_count: self.values.len() as u32,
-            offset_values: (50 + objects_bytes.len() + keys_bytes.len()) as u32,
+            offset_values: (50 + objects_len + keys_len) as u32,
             class_name_count: self.class_names.len() as u32,
-            offset_class_names: (50 + objects_bytes.len() + keys_bytes.len() + values_bytes.len())
-                as u32,
+            offset_class_names: (50 + objects_len + keys_len + values_len) as u32,
         };
-
-        writer.write_all(MAGIC_BYTES)?;
-        writer.write_all(&header.to_bytes())?;
-        writer.write_all(&objects_bytes)?;
-        writer.write_all(&keys_bytes)?;
-        writer.write_all(&values_bytes)?;
-        writer.write_all(&classes_bytes)?;
-        writer.flush()?;
-
-        Ok(())
+        plan
     }
 
     /// Returns the format version of the given archive.
@@ -278,11 +697,30 @@ impl NIBArchive {
         self.coder_version
     }
 
+    /// Returns quirks of the format and coder versions of the archive.
+    pub fn quirks(&self) -> Quirks {
+        Quirks::for_versions(self.format_version, self.coder_version)
+    }
+
     /// Set the coder version of the given archive.
     pub fn set_coder_version(&mut self, value: u32) {
         self.coder_version = value;
     }
 
+    /// Returns whether values are stored with the smallest types when encoding.
+    pub fn minimize_value_widths(&self) -> bool {
+        self.minimize_value_widths
+    }
+
+    /// Enables or disables storing values with the smallest types that hold them
+    /// when encoding, e.g. an `Int64` value of `5` is written as `Int8` and a `Double`
+    /// that is exactly representable as `f32` is written as `Float`.
+    ///
+    /// Values of the archive itself aren't changed. Disabled by default.
+    pub fn set_minimize_value_widths(&mut self, value: bool) {
+        self.minimize_value_widths = value;
+    }
+
     /// Returns a reference to a vector of the archive's [objects](Object).
     pub fn objects(&self) -> &[Object] {
         &self.objects
@@ -294,7 +732,12 @@ impl NIBArchive {
     /// that is out of bounds.
     pub fn set_objects(&mut self, objects: Vec<Object>) -> Result<(), Error> {
         for obj in &objects {
-            Self::check_object(obj, self.values.len() as u32, self.class_names.len() as u32)?;
+            Self::check_object(
+                obj,
+                self.values.len() as u32,
+                self.class_names.len() as u32,
+                None,
+            )?;
         }
         self.objects = objects;
         Ok(())
@@ -305,6 +748,11 @@ impl NIBArchive {
         &self.keys
     }
 
+    /// Returns an iterator over raw bytes of the archive's keys.
+    pub fn keys_raw(&self) -> impl Iterator<Item = &[u8]> {
+        self.keys.iter().map(|key| key.as_bytes())
+    }
+
     /// Sets the archive's keys.
     pub fn set_keys(&mut self, keys: Vec<String>) {
         self.keys = keys;
@@ -320,7 +768,7 @@ impl NIBArchive {
     /// Returns an error if one of values references to a key that is out of bounds.
     pub fn set_values(&mut self, values: Vec<Value>) -> Result<(), Error> {
         for val in &values {
-            Self::check_value(val, self.keys.len() as u32)?;
+            Self::check_key_index(val.key_id(), self.keys.len() as u32, None)?;
         }
         self.values = values;
         Ok(())
@@ -336,64 +784,30 @@ impl NIBArchive {
     /// Returns an error if one of classes references to a fallback class that is out of bounds.
     pub fn set_class_names(&mut self, class_names: Vec<ClassName>) -> Result<(), Error> {
         for cls in &class_names {
-            Self::check_class_name(cls, class_names.len() as u32)?;
+            Self::check_fallback_classes(cls.fallback_class_ids(), class_names.len() as u32, None)?;
         }
         self.class_names = class_names;
         Ok(())
     }
 
-    /// Consumes itself and returns returns a unit of objects, keys, values and class names.
-    pub fn into_inner(self) -> (Vec<Object>, Vec<String>, Vec<Value>, Vec<ClassName>) {
-        (self.objects, self.keys, self.values, self.class_names)
-    }
-}
-
-/// Decodes a variable integer ([more info](https://github.com/matsmattsson/nibsqueeze/blob/master/NibArchive.md#varint-coding))
-/// into a regular i32.
-fn decode_var_int<T: Read + Seek>(reader: &mut T) -> Result<VarInt, Error> {
-    let mut result = 0;
-    let mut shift = 0;
-    loop {
-        let mut current_byte = [0; 1];
-        reader.read_exact(&mut current_byte)?;
-        let current_byte = current_byte[0];
-        result |= (current_byte as VarInt & 0x7F) << shift;
-        shift += 7;
-        if (current_byte & 128) != 0 {
-            break;
-        }
+    /// Returns the number of bytes that the object with a given `index` and its
+    /// [values](Value) contribute to the encoded archive.
+    ///
+    /// Returns `None` if the index or the object's value range is out of bounds.
+    pub fn object_encoded_size(&self, index: usize) -> Option<usize> {
+        let obj = self.objects.get(index)?;
+        let values = self.try_object_values(obj)?;
+        Some(obj.encoded_len() + values.iter().map(Value::encoded_len).sum::<usize>())
     }
-    Ok(result)
-}
 
-/// Encodes an i32 into a variable integer bytes.
-fn encode_var_int(mut value: VarInt) -> Vec<u8> {
-    let mut number_of_bytes = 0;
-    let mut _v = value;
-    loop {
-        number_of_bytes += 1;
-        _v >>= 7;
-        if _v == 0 {
-            break;
-        }
+    /// Returns [values](Value) of an object or `None` if its value range is invalid
+    /// or out of bounds.
+    pub(crate) fn try_object_values(&self, obj: &Object) -> Option<&[Value]> {
+        obj.value_range().and_then(|range| self.values.get(range))
     }
 
-    let mut offset = 0;
-    let mut bytes = vec![0; number_of_bytes];
-
-    while offset < number_of_bytes {
-        let digit: u8 = (0x7f & value) as u8;
-        value >>= 7;
-
-        let is_last_digit = value == 0;
-
-        bytes[offset] = digit | if is_last_digit { 0x80 } else { 0 };
-        offset += 1;
-
-        if is_last_digit {
-            break;
-        }
+    /// Consumes itself and returns returns a unit of objects, keys, values and class names.
+    pub fn into_inner(self) -> (Vec<Object>, Vec<String>, Vec<Value>, Vec<ClassName>) {
+        (self.objects, self.keys, self.values, self.class_names)
     }
-
-    bytes
 }
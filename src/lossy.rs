@@ -0,0 +1,170 @@
+use crate::{
+    decoder::Decoder, ClassName, ClassNameRef, DecodeOptions, Error, NIBArchive, Object, Value,
+    ValueRef,
+};
+use std::io::{Cursor, Read, Seek};
+
+/// A problem [NIBArchive::from_reader_lossy()] recovered from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecoveryNote {
+    /// Name of the section the problem was found in.
+    pub section: &'static str,
+    /// Offset of the element that couldn't be decoded.
+    pub offset: u64,
+    pub message: String,
+}
+
+impl std::fmt::Display for RecoveryNote {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} at offset {}: {}",
+            self.section, self.offset, self.message
+        )
+    }
+}
+
+impl NIBArchive {
+    /// Reads and decodes a possibly damaged NIB Archive from a given reader.
+    ///
+    /// Decoding of a section stops at the first element that can't be decoded, e.g. because
+    /// the section is truncated or a value has an unknown type. Elements decoded before it are
+    /// kept, and decoding continues with the next section. Value ranges of objects are clipped
+    /// to the decoded values; other indeces aren't checked, so they may be out of bounds.
+    ///
+    /// Returns the salvaged archive along with notes about what couldn't be decoded.
+    /// Returns an error only if the archive can't be read or its magic bytes or header are invalid.
+    pub fn from_reader_lossy<T: Read + Seek>(
+        reader: &mut T,
+    ) -> Result<(Self, Vec<RecoveryNote>), Error> {
+        let options = DecodeOptions::default();
+        let bytes = Self::read_all(reader, options.limits().max_total_size())?;
+        let mut cursor = Cursor::new(bytes.as_slice());
+        let header = Self::read_header(&mut cursor)?;
+        let mut decoder = Decoder::new(&mut cursor, &options)?;
+        let mut notes = Vec::new();
+
+        let mut objects = read_section(
+            &mut decoder,
+            "objects",
+            header.offset_objects,
+            header.object_count,
+            Object::try_from_decoder,
+            &mut notes,
+        );
+        let keys = read_section(
+            &mut decoder,
+            "keys",
+            header.offset_keys,
+            header.key_count,
+            |decoder| decoder.key().map(str::to_string),
+            &mut notes,
+        );
+        let values = read_section(
+            &mut decoder,
+            "values",
+            header.offset_values,
+            header.value_count,
+            |decoder| ValueRef::try_from_decoder(decoder).map(|val| Value::from(&val)),
+            &mut notes,
+        );
+        let class_names = read_section(
+            &mut decoder,
+            "class names",
+            header.offset_class_names,
+            header.class_name_count,
+            |decoder| ClassNameRef::try_from_decoder(decoder).map(|cls| ClassName::from(&cls)),
+            &mut notes,
+        );
+
+        for obj in &mut objects {
+            let start = (obj.first_value_id().0.max(0) as usize).min(values.len());
+            let end = (start + obj.value_count().max(0) as usize).min(values.len());
+            obj.set_values_index(start as i32);
+            obj.set_value_count((end - start) as i32);
+        }
+
+        let mut archive = Self::new_unchecked(objects, keys, values, class_names);
+        archive.format_version = header.format_version;
+        archive.coder_version = header.coder_version;
+        Ok((archive, notes))
+    }
+}
+
+/// Decodes up to `count` elements of a section starting at `offset`.
+fn read_section<'a, T, F>(
+    decoder: &mut Decoder<'_, Cursor<&'a [u8]>>,
+    section: &'static str,
+    offset: u32,
+    count: u32,
+    mut decode: F,
+    notes: &mut Vec<RecoveryNote>,
+) -> Vec<T>
+where
+    F: FnMut(&mut Decoder<'_, Cursor<&'a [u8]>>) -> Result<T, Error>,
+{
+    let mut elements = Vec::new();
+    let mut position = offset as u64;
+    let result = decoder.seek(position).and_then(|_| {
+        for _ in 0..count {
+            elements.push(decode(decoder)?);
+            position = decoder.position();
+        }
+        Ok(())
+    });
+    if let Err(e) = result {
+        notes.push(RecoveryNote {
+            section,
+            offset: position,
+            message: format!("{e}; {} of {count} elements decoded", elements.len()),
+        });
+    }
+    elements
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ClassName, NIBArchive, Object, Value, ValueVariant};
+    use std::io::Cursor;
+
+    #[test]
+    fn corrupt_sections() {
+        let values = (1..=4)
+            .map(|i| Value::new(0, ValueVariant::Int8(i)))
+            .collect();
+        let archive = NIBArchive::new(
+            vec![Object::new(0, 0, 2), Object::new(0, 2, 2)],
+            vec!["UITag".into()],
+            values,
+            vec![ClassName::with_fallbacks("UIView".into(), Vec::new())],
+        )
+        .unwrap();
+        let mut bytes = archive.to_bytes();
+        let header = NIBArchive::read_header(&mut Cursor::new(bytes.as_slice())).unwrap();
+
+        // Every value takes three bytes: a key, a type and an Int8.
+        let third_value = header.offset_values as u64 + 6;
+        bytes[third_value as usize + 1] = 0xEE;
+        // Only the length of the class name is left.
+        let class_names = header.offset_class_names as u64;
+        bytes.truncate(class_names as usize + 1);
+
+        let (salvaged, notes) = NIBArchive::from_reader_lossy(&mut Cursor::new(bytes)).unwrap();
+        assert_eq!(salvaged.objects().len(), 2);
+        assert_eq!(salvaged.keys(), ["UITag"]);
+        assert_eq!(salvaged.values(), &archive.values()[..2]);
+        assert!(salvaged.class_names().is_empty());
+        assert_eq!(
+            salvaged.objects(),
+            [Object::new(0, 0, 2), Object::new(0, 2, 0)]
+        );
+
+        assert_eq!(notes.len(), 2);
+        assert_eq!(notes[0].section, "values");
+        assert_eq!(notes[0].offset, third_value);
+        assert!(notes[0].message.ends_with("2 of 4 elements decoded"));
+        assert_eq!(notes[1].section, "class names");
+        assert_eq!(notes[1].offset, class_names);
+        assert!(notes[1].message.ends_with("0 of 1 elements decoded"));
+    }
+}
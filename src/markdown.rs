@@ -0,0 +1,108 @@
+use crate::{NIBArchive, ViewNode};
+use std::{collections::BTreeMap, fmt::Write};
+
+/// Formats a readable Markdown summary of a given archive: the view hierarchy with
+/// frames, tags and identifiers, followed by tables of classes and strings.
+///
+/// The summary is meant to be pasted into pull requests and issues.
+pub fn nib_to_markdown(archive: &NIBArchive) -> String {
+    let mut md = String::new();
+    let _ = writeln!(
+        md,
+        "{} objects, {} keys, {} values, {} class names.\n",
+        archive.objects().len(),
+        archive.keys().len(),
+        archive.values().len(),
+        archive.class_names().len()
+    );
+
+    md.push_str("## View hierarchy\n\n");
+    let views = archive.view_hierarchy();
+    if views.is_empty() {
+        md.push_str("No views.\n");
+    }
+    for view in &views {
+        write_view(view, 0, &mut md);
+    }
+
+    md.push_str("\n## Classes\n\n| Class | Objects |\n| --- | ---: |\n");
+    let mut counts: BTreeMap<&str, usize> = BTreeMap::new();
+    for obj in archive.objects() {
+        if let Some(class_name) = archive.class_name(obj.class_name_id()) {
+            *counts.entry(class_name.name()).or_default() += 1;
+        }
+    }
+    for (name, count) in counts {
+        let _ = writeln!(md, "| `{}` | {count} |", escape_cell(name));
+    }
+
+    md.push_str("\n## Strings\n\n");
+    let strings = archive.localizable_strings();
+    if strings.is_empty() {
+        md.push_str("No strings.\n");
+        return md;
+    }
+    md.push_str("| Key path | String |\n| --- | --- |\n");
+    for s in &strings {
+        let _ = writeln!(
+            md,
+            "| `{}` | {} |",
+            escape_cell(s.key_path()),
+            escape_cell(s.value())
+        );
+    }
+    md
+}
+
+fn write_view(view: &ViewNode, depth: usize, md: &mut String) {
+    let _ = write!(
+        md,
+        "{}- `{}` #{}",
+        "  ".repeat(depth),
+        view.class_name,
+        view.index
+    );
+    if let Some(frame) = &view.frame {
+        let _ = write!(md, " {frame}");
+    }
+    if let Some(tag) = view.tag {
+        let _ = write!(md, ", tag {tag}");
+    }
+    if let Some(identifier) = &view.accessibility_identifier {
+        let _ = write!(md, ", *{}*", escape_cell(identifier));
+    }
+    md.push('\n');
+    for subview in &view.subviews {
+        write_view(subview, depth + 1, md);
+    }
+}
+
+/// Escapes a string to be placed into a table cell, which has to fit into a single line
+/// and shouldn't be interpreted as HTML.
+fn escape_cell(s: &str) -> String {
+    s.replace('\\', "\\\\")
+        .replace('|', "\\|")
+        .replace('<', "&lt;")
+        .replace("\r\n", "<br>")
+        .replace(['\n', '\r'], "<br>")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ClassName, Object, Value, ValueVariant};
+
+    #[test]
+    fn table_escaping() {
+        let archive = NIBArchive::new(
+            vec![Object::new(0, 0, 1)],
+            vec!["UIText".into()],
+            vec![Value::new(0, ValueVariant::Data(b"A|B\\\nC<D>".to_vec()))],
+            vec![ClassName::with_fallbacks("My|Label".into(), Vec::new())],
+        )
+        .unwrap();
+        let md = nib_to_markdown(&archive);
+        assert!(md.contains("| `My\\|Label` | 1 |\n"), "{md}");
+        assert!(md.contains(" | A\\|B\\\\<br>C&lt;D> |\n"), "{md}");
+    }
+}
@@ -1,60 +1,94 @@
-use crate::{decode_var_int, encode_var_int, ClassName, Error, Value, VarInt};
-use std::io::{Read, Seek};
+use crate::{
+    decoder::Decoder,
+    encode_var_int,
+    io::{Read, Seek},
+    var_int_len, ClassName, ClassNameIndex, Error, Value, ValueIndex, VarInt,
+};
+use alloc::vec::Vec;
+use core::ops::Range;
 
 /// Represents a single object of a NIB Archive.
 ///
 /// An object contains the index of a representing class name, the first index of
 /// a value and the count of all values.
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub struct Object {
-    class_name_index: VarInt,
-    values_index: VarInt,
+    class_name_index: ClassNameIndex,
+    values_index: ValueIndex,
     value_count: VarInt,
 }
 
 impl Object {
-    pub(crate) fn try_from_reader<T: Read + Seek>(mut reader: &mut T) -> Result<Self, Error> {
+    pub(crate) fn try_from_decoder<T: Read + Seek>(
+        decoder: &mut Decoder<'_, T>,
+    ) -> Result<Self, Error> {
         Ok(Self {
-            class_name_index: decode_var_int(&mut reader)?,
-            values_index: decode_var_int(&mut reader)?,
-            value_count: decode_var_int(&mut reader)?,
+            class_name_index: decoder.index("class name index")?.into(),
+            values_index: decoder.index("value index")?.into(),
+            value_count: decoder.index("value count")?,
         })
     }
 
     pub(crate) fn to_bytes(&self) -> Vec<u8> {
-        let mut bytes = encode_var_int(self.class_name_index);
-        bytes.append(&mut encode_var_int(self.values_index));
+        let mut bytes = encode_var_int(self.class_name_index.0);
+        bytes.append(&mut encode_var_int(self.values_index.0));
         bytes.append(&mut encode_var_int(self.value_count));
         bytes
     }
 
+    pub(crate) fn encoded_len(&self) -> usize {
+        var_int_len(self.class_name_index.0)
+            + var_int_len(self.values_index.0)
+            + var_int_len(self.value_count)
+    }
+
     /// Creates a new NIB Archive object.
-    pub fn new(class_name_index: VarInt, values_index: VarInt, value_count: VarInt) -> Self {
+    ///
+    /// Indeces may be given either typed or as raw `i32` values.
+    pub fn new(
+        class_name_index: impl Into<ClassNameIndex>,
+        values_index: impl Into<ValueIndex>,
+        value_count: VarInt,
+    ) -> Self {
         Self {
-            class_name_index,
-            values_index,
+            class_name_index: class_name_index.into(),
+            values_index: values_index.into(),
             value_count,
         }
     }
 
     /// Returns the index of a [ClassName] that describes the current object.
-    pub fn class_name_index(&self) -> VarInt {
+    pub fn class_name_id(&self) -> ClassNameIndex {
         self.class_name_index
     }
 
+    /// Returns the raw index of a [ClassName] that describes the current object.
+    #[deprecated(note = "use `Object::class_name_id()`, which returns a typed index")]
+    pub fn class_name_index(&self) -> VarInt {
+        self.class_name_index.0
+    }
+
     /// Sets object's class name index.
-    pub fn set_class_name_index(&mut self, index: VarInt) {
-        self.class_name_index = index
+    pub fn set_class_name_index(&mut self, index: impl Into<ClassNameIndex>) {
+        self.class_name_index = index.into()
     }
 
     /// Returns the first index of a [Value] that the object contains.
-    pub fn values_index(&self) -> VarInt {
+    pub fn first_value_id(&self) -> ValueIndex {
         self.values_index
     }
 
+    /// Returns the raw first index of a [Value] that the object contains.
+    #[deprecated(note = "use `Object::first_value_id()`, which returns a typed index")]
+    pub fn values_index(&self) -> VarInt {
+        self.values_index.0
+    }
+
     /// Sets value's first index of an object.
-    pub fn set_values_index(&mut self, index: VarInt) {
-        self.values_index = index
+    pub fn set_values_index(&mut self, index: impl Into<ValueIndex>) {
+        self.values_index = index.into()
     }
 
     /// Returns the count of all [Values](Value) that the object contains.
@@ -70,21 +104,60 @@ impl Object {
     /// Returns a slice of [Values](Value) associated with the current object.
     ///
     /// Pass the return value of [crate::NIBArchive::values()] method for a proper result.
+    ///
+    /// Panics if the object's value range is invalid or out of bounds.
     pub fn values<'a>(&self, values: &'a [Value]) -> &'a [Value] {
-        let start = self.values_index() as usize;
-        let end = start + self.value_count() as usize;
-        &values[start..end]
+        let range = self.value_range().expect("object's value range is invalid");
+        &values[range]
+    }
+
+    /// Returns the range of indeces of the object's values.
+    ///
+    /// Returns `None` if the first index or the count is negative, or the range overflows.
+    pub fn value_range(&self) -> Option<Range<usize>> {
+        let start = usize::try_from(self.values_index.0).ok()?;
+        let count = usize::try_from(self.value_count).ok()?;
+        Some(start..start.checked_add(count)?)
     }
 
     /// Returns a reference to a [ClassName] associated with the current object.
     ///
     /// Pass the return value of [crate::NIBArchive::class_names()] method for a proper result.
     pub fn class_name<'a>(&self, class_names: &'a [ClassName]) -> &'a ClassName {
-        &class_names[self.class_name_index() as usize]
+        &class_names[self.class_name_id().as_usize()]
     }
 
-    /// Consumes itself and returns a unit of `class_name_index`, `values_index` and `value_count`.
+    /// Consumes itself and returns a unit of raw `class_name_index`, `values_index`
+    /// and `value_count`.
     pub fn into_inner(self) -> (VarInt, VarInt, VarInt) {
-        (self.class_name_index, self.values_index, self.value_count)
+        (
+            self.class_name_index.0,
+            self.values_index.0,
+            self.value_count,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn value_range() {
+        assert_eq!(Object::new(0, 2, 3).value_range(), Some(2..5));
+        assert_eq!(Object::new(0, -1, 2).value_range(), None);
+        assert_eq!(Object::new(0, 2, -1).value_range(), None);
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn negative_values_index() {
+        let archive = crate::NIBArchive::new_unchecked(
+            vec![Object::new(0, -1, 2)],
+            vec!["UIText".into()],
+            Vec::new(),
+            vec![ClassName::with_fallbacks("UILabel".into(), Vec::new())],
+        );
+        assert!(archive.find_by_key("UIText").is_empty());
     }
 }
@@ -0,0 +1,286 @@
+use crate::Section;
+use alloc::sync::Arc;
+
+/// Options that control decoding of a NIB Archive.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DecodeOptions {
+    strict: bool,
+    limits: Limits,
+    preserve_encoding: bool,
+    lenient_value_types: bool,
+    progress: Option<ProgressHook>,
+}
+
+/// A callback receiving a section, the count of its decoded or encoded elements
+/// and the total count of them.
+pub(crate) type ProgressFn = dyn Fn(Section, u64, u64) + Send + Sync;
+
+/// Options are compared by whether they share the same hook.
+#[derive(Clone)]
+pub(crate) struct ProgressHook(Arc<ProgressFn>);
+
+impl ProgressHook {
+    pub fn report(&self, section: Section, done: u64, total: u64) {
+        report_progress(&*self.0, section, done, total);
+    }
+}
+
+/// Count of elements between two reports, so a callback isn't called for every
+/// single element of a large archive.
+const PROGRESS_INTERVAL: u64 = 1024;
+
+/// Calls `callback` every [PROGRESS_INTERVAL] elements and at the end of a section.
+pub(crate) fn report_progress(
+    callback: &dyn Fn(Section, u64, u64),
+    section: Section,
+    done: u64,
+    total: u64,
+) {
+    if done == total || done.is_multiple_of(PROGRESS_INTERVAL) {
+        callback(section, done, total);
+    }
+}
+
+impl core::fmt::Debug for ProgressHook {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str("ProgressHook")
+    }
+}
+
+impl PartialEq for ProgressHook {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl Eq for ProgressHook {}
+
+impl DecodeOptions {
+    /// Creates default decoding options.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns whether the strict mode is enabled.
+    pub fn strict(&self) -> bool {
+        self.strict
+    }
+
+    /// Enables or disables the strict mode.
+    ///
+    /// In the strict mode anomalies that are normally reported as
+    /// [warnings](DecodeWarning) become errors, except for
+    /// [unknown versions](DecodeWarning::UnknownVersion).
+    pub fn set_strict(&mut self, value: bool) {
+        self.strict = value;
+    }
+
+    /// Returns parser limits.
+    pub fn limits(&self) -> &Limits {
+        &self.limits
+    }
+
+    /// Sets parser limits.
+    pub fn set_limits(&mut self, limits: Limits) {
+        self.limits = limits;
+    }
+
+    /// Returns whether the original encoding is preserved.
+    pub fn preserve_encoding(&self) -> bool {
+        self.preserve_encoding
+    }
+
+    /// Enables or disables preservation of the original encoding.
+    ///
+    /// When enabled, a decoded archive keeps the original bytes of sections that
+    /// the encoder wouldn't reproduce exactly (e.g. non-minimal variable integers), as well
+    /// as any bytes following the last section. Sections that stay untouched are written
+    /// back byte for byte, so an unmodified archive is encoded into the original bytes.
+    pub fn set_preserve_encoding(&mut self, value: bool) {
+        self.preserve_encoding = value;
+    }
+
+    /// Returns whether values of unknown types are accepted.
+    pub fn lenient_value_types(&self) -> bool {
+        self.lenient_value_types
+    }
+
+    /// Enables or disables decoding of values of unknown types into
+    /// [ValueVariant::Unknown](crate::ValueVariant::Unknown).
+    ///
+    /// The length of an unknown payload isn't encoded anywhere, so it's found by trying
+    /// lengths until the rest of the values section decodes into exactly the remaining
+    /// count of values with valid keys. If no length fits, or there is another unknown
+    /// value after the current one, decoding fails.
+    pub fn set_lenient_value_types(&mut self, value: bool) {
+        self.lenient_value_types = value;
+    }
+
+    /// Sets a callback that is called with a section, the count of its decoded elements
+    /// and the total count of them while decoding, so a frontend can show progress
+    /// of large archives.
+    ///
+    /// The callback is called every 1024 elements and once a section is finished.
+    /// It's shared by all archives decoded with the options, e.g. by
+    /// [batch decoding](crate::batch::decode_dir()).
+    pub fn on_progress<F>(&mut self, callback: F)
+    where
+        F: Fn(Section, u64, u64) + Send + Sync + 'static,
+    {
+        self.progress = Some(ProgressHook(Arc::new(callback)));
+    }
+
+    /// Removes a callback set with [DecodeOptions::on_progress()].
+    pub fn clear_progress(&mut self) {
+        self.progress = None;
+    }
+
+    pub(crate) fn progress(&self) -> Option<&ProgressHook> {
+        self.progress.as_ref()
+    }
+}
+
+/// Limits that a decoder enforces to protect against malformed or hostile input.
+///
+/// Default limits are well above anything found in real archives.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Limits {
+    max_count: u32,
+    max_data_length: u32,
+    max_var_int_bytes: usize,
+    max_total_size: u64,
+    max_depth: u32,
+    max_decoded_objects: u32,
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Self {
+            max_count: 1 << 20,
+            max_data_length: 64 << 20,
+            max_var_int_bytes: 5,
+            max_total_size: 1 << 30,
+            max_depth: 256,
+            max_decoded_objects: 1 << 20,
+        }
+    }
+}
+
+impl Limits {
+    /// Creates default limits.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates limits suitable for untrusted input. They still fit nibs of
+    /// any real interface, but keep memory usage of a decoder within a few megabytes.
+    pub fn strict() -> Self {
+        Self {
+            max_count: 1 << 16,
+            max_data_length: 1 << 20,
+            max_var_int_bytes: 5,
+            max_total_size: 16 << 20,
+            max_depth: 64,
+            max_decoded_objects: 1 << 16,
+        }
+    }
+
+    /// Returns the maximum count of objects, keys, values, class names and fallback
+    /// classes of a single class name.
+    pub fn max_count(&self) -> u32 {
+        self.max_count
+    }
+
+    /// Sets the maximum count of elements of every kind.
+    pub fn set_max_count(&mut self, value: u32) {
+        self.max_count = value;
+    }
+
+    /// Returns the maximum length of a key, a class name or a data value in bytes.
+    pub fn max_data_length(&self) -> u32 {
+        self.max_data_length
+    }
+
+    /// Sets the maximum length of a key, a class name or a data value in bytes.
+    pub fn set_max_data_length(&mut self, value: u32) {
+        self.max_data_length = value;
+    }
+
+    /// Returns the maximum count of bytes of a single variable integer.
+    pub fn max_var_int_bytes(&self) -> usize {
+        self.max_var_int_bytes
+    }
+
+    /// Sets the maximum count of bytes of a single variable integer.
+    pub fn set_max_var_int_bytes(&mut self, value: usize) {
+        self.max_var_int_bytes = value;
+    }
+
+    /// Returns the maximum size of an archive in bytes.
+    pub fn max_total_size(&self) -> u64 {
+        self.max_total_size
+    }
+
+    /// Sets the maximum size of an archive in bytes. Both section offsets and the total
+    /// length of keys, class names and data values are checked against it.
+    pub fn set_max_total_size(&mut self, value: u64) {
+        self.max_total_size = value;
+    }
+
+    /// Returns the maximum nesting depth of recursive structures, e.g. objects
+    /// of keyed archives, objects decoded with [FromNib](crate::FromNib) and views
+    /// of a hierarchy.
+    pub fn max_depth(&self) -> u32 {
+        self.max_depth
+    }
+
+    /// Sets the maximum nesting depth of recursive structures.
+    pub fn set_max_depth(&mut self, value: u32) {
+        self.max_depth = value;
+    }
+
+    /// Returns the maximum count of objects decoded with [FromNib](crate::FromNib) at once.
+    /// Objects referenced several times are counted every time they're decoded, so shared
+    /// objects can't make decoding take exponential time.
+    pub fn max_decoded_objects(&self) -> u32 {
+        self.max_decoded_objects
+    }
+
+    /// Sets the maximum count of objects decoded with [FromNib](crate::FromNib) at once.
+    pub fn set_max_decoded_objects(&mut self, value: u32) {
+        self.max_decoded_objects = value;
+    }
+}
+
+/// An anomaly found during decoding that doesn't prevent reading an archive.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum DecodeWarning {
+    /// A variable integer at a given offset is encoded with redundant leading groups.
+    ///
+    /// Encoders used by Apple always produce minimal variable integers, so it usually
+    /// indicates that an archive has been created or patched by a foreign encoder.
+    NonMinimalVarInt { offset: u64 },
+    /// The combination of format and coder versions isn't known, so the archive is decoded
+    /// without the assumptions [quirks](crate::Quirks) of known versions make. It's reported
+    /// even in the strict mode, since the archive itself may be fine.
+    UnknownVersion {
+        format_version: u32,
+        coder_version: u32,
+    },
+}
+
+impl core::fmt::Display for DecodeWarning {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            DecodeWarning::NonMinimalVarInt { offset } => f.write_fmt(format_args!(
+                "Non-minimal variable integer at offset {offset}"
+            )),
+            DecodeWarning::UnknownVersion {
+                format_version,
+                coder_version,
+            } => f.write_fmt(format_args!(
+                "Unknown format version {format_version} and coder version {coder_version}"
+            )),
+        }
+    }
+}
@@ -0,0 +1,235 @@
+use crate::{
+    header::Header,
+    io::{Read, Seek, SeekFrom},
+    ClassName, Error, NIBArchive, Object, Value, VarInt,
+};
+use alloc::{collections::BTreeMap, string::String, vec, vec::Vec};
+
+/// Original encoding of an archive kept when [DecodeOptions::set_preserve_encoding()]
+/// is enabled.
+///
+/// Sections are kept only if their original bytes differ from the ones the encoder
+/// would produce, e.g. because of non-minimal variable integers. Along with the bytes
+/// the decoded elements are kept, so the bytes are reused only while the section
+/// stays untouched.
+///
+/// [DecodeOptions::set_preserve_encoding()]: crate::DecodeOptions::set_preserve_encoding()
+#[derive(Debug, Clone, Default)]
+pub(crate) struct PreservedEncoding {
+    pub objects: Option<(Vec<Object>, Vec<u8>)>,
+    pub keys: Option<(Vec<String>, Vec<u8>)>,
+    pub values: Option<(Vec<Value>, Vec<u8>)>,
+    pub class_names: Option<(Vec<ClassName>, Vec<u8>)>,
+    /// Bytes following the class names section.
+    pub trailing: Vec<u8>,
+}
+
+impl PreservedEncoding {
+    /// Reads original sections of a decoded `archive`. `end` is the position right
+    /// after the class names section.
+    pub(crate) fn capture<T: Read + Seek>(
+        reader: &mut T,
+        header: &Header,
+        end: u64,
+        archive: &NIBArchive,
+    ) -> Result<Self, Error> {
+        let mut read_section = |start: u32, end: u64| -> Result<Vec<u8>, Error> {
+            reader.seek(SeekFrom::Start(start as u64))?;
+            let mut buf = vec![0; (end - start as u64) as usize];
+            reader.read_exact(&mut buf)?;
+            Ok(buf)
+        };
+        let objects = read_section(header.offset_objects, header.offset_keys as u64)?;
+        let keys = read_section(header.offset_keys, header.offset_values as u64)?;
+        let values = read_section(header.offset_values, header.offset_class_names as u64)?;
+        let class_names = read_section(header.offset_class_names, end)?;
+        let length = reader.seek(SeekFrom::End(0))?;
+        reader.seek(SeekFrom::Start(end))?;
+        let mut trailing = vec![0; length.saturating_sub(end) as usize];
+        reader.read_exact(&mut trailing)?;
+
+        Ok(Self {
+            objects: keep(
+                &archive.objects,
+                objects,
+                archive.objects.iter().flat_map(|o| o.to_bytes()).collect(),
+            ),
+            keys: keep(&archive.keys, keys, encode_keys(&archive.keys)),
+            values: keep(
+                &archive.values,
+                values,
+                archive.values.iter().flat_map(|v| v.to_bytes()).collect(),
+            ),
+            class_names: keep(
+                &archive.class_names,
+                class_names,
+                archive
+                    .class_names
+                    .iter()
+                    .flat_map(|c| c.to_bytes())
+                    .collect(),
+            ),
+            trailing,
+        })
+    }
+}
+
+/// Returns elements along with their original bytes if the bytes differ from
+/// the encoded ones.
+fn keep<T: Clone>(elements: &[T], raw: Vec<u8>, encoded: Vec<u8>) -> Option<(Vec<T>, Vec<u8>)> {
+    (raw != encoded).then(|| (elements.to_vec(), raw))
+}
+
+/// Elements of a section that are compared with their originals.
+pub(crate) trait Unchanged {
+    /// Returns `true` if the element would be encoded into the same bytes as `original`.
+    fn is_unchanged(&self, original: &Self) -> bool;
+}
+
+macro_rules! impl_unchanged {
+    ($($t:ty),*) => {
+        $(
+            impl Unchanged for $t {
+                fn is_unchanged(&self, original: &Self) -> bool {
+                    self == original
+                }
+            }
+        )*
+    };
+}
+
+impl_unchanged!(Object, String, ClassName);
+
+impl Unchanged for Value {
+    fn is_unchanged(&self, original: &Self) -> bool {
+        // PartialEq treats -0.0 and 0.0 as equal and NaNs as unequal
+        self.key_id() == original.key_id() && self.value().is_identical(original.value())
+    }
+}
+
+/// Returns original bytes of a section if its elements haven't changed.
+pub(crate) fn original_bytes<'s, T: Unchanged>(
+    section: Option<&'s (Vec<T>, Vec<u8>)>,
+    elements: &[T],
+) -> Option<&'s [u8]> {
+    section
+        .filter(|(original, _)| {
+            original.len() == elements.len()
+                && elements
+                    .iter()
+                    .zip(original)
+                    .all(|(a, b)| a.is_unchanged(b))
+        })
+        .map(|(_, raw)| &raw[..])
+}
+
+pub(crate) fn encoded_keys_len(keys: &[String]) -> usize {
+    keys.iter()
+        .map(|key| crate::var_int_len(key.len() as i32) + key.len())
+        .sum()
+}
+
+pub(crate) fn encode_keys(keys: &[String]) -> Vec<u8> {
+    // Let's estimate the average key length as 16 symbols
+    let mut bytes = Vec::with_capacity(keys.len() * (16 + 2));
+    for key in keys {
+        bytes.append(&mut crate::encode_var_int(key.len() as i32));
+        bytes.extend(key.as_bytes());
+    }
+    bytes
+}
+
+/// Returns deduplicated keys along with a map of old key indeces to new ones,
+/// or `None` if all keys are unique.
+pub(crate) fn unique_keys(keys: &[String]) -> Option<(Vec<String>, Vec<VarInt>)> {
+    let mut first_index: BTreeMap<&str, VarInt> = BTreeMap::new();
+    let mut key_map = Vec::with_capacity(keys.len());
+    let mut unique = Vec::new();
+    for key in keys {
+        let next_index = unique.len() as VarInt;
+        let index = *first_index.entry(key).or_insert_with(|| {
+            unique.push(key.clone());
+            next_index
+        });
+        key_map.push(index);
+    }
+    (unique.len() != keys.len()).then_some((unique, key_map))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{io::Cursor, ClassName, DecodeOptions, NIBArchive, Object, Value, ValueVariant};
+    use alloc::{vec, vec::Vec};
+
+    /// Encodes an archive with a single double value whose key index is encoded
+    /// non-minimally, so the original values section is preserved on decoding.
+    fn decode_preserved(value: f64) -> (NIBArchive, Vec<u8>) {
+        let archive = NIBArchive::new(
+            vec![Object::new(0, 0, 1)],
+            vec!["UIAlpha".into()],
+            vec![Value::new(0, ValueVariant::Double(value))],
+            vec![ClassName::with_fallbacks("UIView".into(), vec![])],
+        )
+        .unwrap();
+        let mut bytes = archive.to_bytes();
+        let offset = |i: usize| u32::from_le_bytes(bytes[i..i + 4].try_into().unwrap());
+        let (values_offset, class_names_offset) = (offset(38), offset(46));
+        bytes.insert(values_offset as usize, 0x00);
+        bytes[46..50].copy_from_slice(&(class_names_offset + 1).to_le_bytes());
+
+        let mut options = DecodeOptions::new();
+        options.set_preserve_encoding(true);
+        let (archive, _) =
+            NIBArchive::from_reader_with_options(&mut Cursor::new(&bytes), &options).unwrap();
+        (archive, bytes)
+    }
+
+    #[test]
+    fn signed_zero() {
+        let (mut archive, bytes) = decode_preserved(0.0);
+        assert_eq!(archive.to_bytes(), bytes);
+
+        archive
+            .set_values(vec![Value::new(0, ValueVariant::Double(-0.0))])
+            .unwrap();
+        let decoded = NIBArchive::from_bytes(archive.to_bytes()).unwrap();
+        assert!(matches!(
+            decoded.values()[0].value(),
+            ValueVariant::Double(v) if v.is_sign_negative()
+        ));
+    }
+
+    #[test]
+    fn nan() {
+        let (archive, bytes) = decode_preserved(f64::NAN);
+        assert_eq!(archive.to_bytes(), bytes);
+    }
+
+    #[test]
+    fn merged_keys_and_minimized_values() {
+        let mut archive = NIBArchive::new(
+            vec![Object::new(0, 0, 2)],
+            vec!["UITag".into(), "UITag".into()],
+            vec![
+                Value::new(1, ValueVariant::Int64(5)),
+                Value::new(0, ValueVariant::Data(vec![1, 2])),
+            ],
+            vec![ClassName::with_fallbacks("UIView".into(), vec![])],
+        )
+        .unwrap();
+        archive.set_minimize_value_widths(true);
+        let bytes = archive.to_bytes();
+        assert_eq!(bytes.len(), archive.encoded_len());
+
+        let decoded = NIBArchive::from_bytes(&bytes).unwrap();
+        assert_eq!(decoded.keys().len(), 1);
+        assert_eq!(
+            decoded.values(),
+            [
+                Value::new(0, ValueVariant::Int8(5)),
+                Value::new(0, ValueVariant::Data(vec![1, 2])),
+            ]
+        );
+        assert_eq!(archive.values()[0].value(), &ValueVariant::Int64(5));
+    }
+}
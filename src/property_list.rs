@@ -0,0 +1,100 @@
+use crate::{Error, NIBArchive, ValueVariant};
+use plist::{Dictionary, Value as PlistValue};
+use std::io::Write;
+
+/// Output format of a property list.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum PlistFormat {
+    /// A human-readable XML property list.
+    #[default]
+    Xml,
+    /// A binary property list (`bplist00`).
+    Binary,
+}
+
+/// Converts an archive into a property list value.
+///
+/// The root dictionary contains `formatVersion`, `coderVersion` and an array of `objects`.
+/// Every object is a dictionary with its `class` name and an array of `values`, each of them
+/// being a dictionary with a `key` and a `value`. Since property lists don't have a null
+/// value, [ValueVariant::Nil] is represented by a `$null` string and object references
+/// are represented by `{"CF$UID": index}` dictionaries like in XML keyed archives.
+pub fn nib_to_plist_value(archive: &NIBArchive) -> PlistValue {
+    let mut objects = Vec::with_capacity(archive.objects().len());
+    for obj in archive.objects() {
+        let class_name = archive
+            .class_names()
+            .get(obj.class_name_id().as_usize())
+            .map(|c| c.name().to_string())
+            .unwrap_or_default();
+        let mut values = Vec::with_capacity(obj.value_count() as usize);
+        for val in archive.object_values(obj) {
+            let key = archive
+                .keys()
+                .get(val.key_id().as_usize())
+                .cloned()
+                .unwrap_or_default();
+            let value = match val.value() {
+                ValueVariant::Int8(v) => PlistValue::Integer((*v).into()),
+                ValueVariant::Int16(v) => PlistValue::Integer((*v).into()),
+                ValueVariant::Int32(v) => PlistValue::Integer((*v).into()),
+                ValueVariant::Int64(v) => PlistValue::Integer((*v).into()),
+                ValueVariant::Bool(v) => PlistValue::Boolean(*v),
+                ValueVariant::Float(v) => PlistValue::Real((*v).into()),
+                ValueVariant::Double(v) => PlistValue::Real(*v),
+                ValueVariant::Data(v) => PlistValue::Data(v.clone()),
+                ValueVariant::Nil => PlistValue::String("$null".into()),
+                ValueVariant::ObjectRef(v) => {
+                    let mut uid = Dictionary::new();
+                    uid.insert("CF$UID".into(), PlistValue::Integer((*v).into()));
+                    PlistValue::Dictionary(uid)
+                }
+                ValueVariant::Unknown { type_byte, raw } => {
+                    let mut unknown = Dictionary::new();
+                    unknown.insert("type".into(), PlistValue::Integer((*type_byte).into()));
+                    unknown.insert("raw".into(), PlistValue::Data(raw.clone()));
+                    PlistValue::Dictionary(unknown)
+                }
+            };
+            let mut dict = Dictionary::new();
+            dict.insert("key".into(), PlistValue::String(key));
+            dict.insert("value".into(), value);
+            values.push(PlistValue::Dictionary(dict));
+        }
+        let mut dict = Dictionary::new();
+        dict.insert("class".into(), PlistValue::String(class_name));
+        dict.insert("values".into(), PlistValue::Array(values));
+        objects.push(PlistValue::Dictionary(dict));
+    }
+
+    let mut root = Dictionary::new();
+    root.insert(
+        "formatVersion".into(),
+        PlistValue::Integer(archive.format_version().into()),
+    );
+    root.insert(
+        "coderVersion".into(),
+        PlistValue::Integer(archive.coder_version().into()),
+    );
+    root.insert("objects".into(), PlistValue::Array(objects));
+    PlistValue::Dictionary(root)
+}
+
+/// Converts an archive into a property list and writes it in a given format.
+///
+/// Look at [nib_to_plist_value()] for the structure of the property list.
+pub fn nib_to_plist<W: Write>(
+    archive: &NIBArchive,
+    writer: W,
+    format: PlistFormat,
+) -> Result<(), Error> {
+    let value = nib_to_plist_value(archive);
+    let result = match format {
+        PlistFormat::Xml => value.to_writer_xml(writer),
+        PlistFormat::Binary => value.to_writer_binary(writer),
+    };
+    result.map_err(|e| match e.into_io() {
+        Ok(e) => Error::IOError(e),
+        Err(e) => Error::IOError(std::io::Error::other(e)),
+    })
+}
@@ -0,0 +1,343 @@
+use crate::{ClassName, NIBArchive, Object, Value, ValueVariant, VarInt};
+
+/// Elements removed by [NIBArchive::prune()].
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct PruneReport {
+    pub keys: Vec<String>,
+    pub values: Vec<Value>,
+    pub class_names: Vec<ClassName>,
+}
+
+impl PruneReport {
+    /// Returns `true` if nothing was removed.
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty() && self.values.is_empty() && self.class_names.is_empty()
+    }
+}
+
+impl NIBArchive {
+    /// Removes elements that aren't used by anything and returns them:
+    /// - values that aren't covered by the value range of any object;
+    /// - keys that aren't referenced by any of the remaining values;
+    /// - class names that aren't referenced by any object, directly or as a fallback class.
+    ///
+    /// All indeces are remapped accordingly.
+    pub fn prune(&mut self) -> PruneReport {
+        let mut report = PruneReport::default();
+
+        // Values
+        let mut used = vec![false; self.values.len()];
+        for obj in &self.objects {
+            let range = obj.value_range().unwrap_or_default();
+            let (start, end) = (range.start, range.end.min(used.len()));
+            for flag in used.iter_mut().take(end).skip(start) {
+                *flag = true;
+            }
+        }
+        let value_map = compact_map(&used);
+        for obj in &mut self.objects {
+            if let Some(index) = value_map.get(obj.first_value_id().as_usize()) {
+                obj.set_values_index(*index);
+            }
+        }
+        (self.values, report.values) = split_used(std::mem::take(&mut self.values), &used);
+
+        // Keys
+        let mut used = vec![false; self.keys.len()];
+        for val in &self.values {
+            if let Some(flag) = used.get_mut(val.key_id().as_usize()) {
+                *flag = true;
+            }
+        }
+        let key_map = compact_map(&used);
+        for val in &mut self.values {
+            if let Some(index) = key_map.get(val.key_id().as_usize()) {
+                val.set_key_index(*index);
+            }
+        }
+        (self.keys, report.keys) = split_used(std::mem::take(&mut self.keys), &used);
+
+        // Class names
+        let mut used = vec![false; self.class_names.len()];
+        let mut stack: Vec<usize> = self
+            .objects
+            .iter()
+            .map(|obj| obj.class_name_id().as_usize())
+            .collect();
+        while let Some(index) = stack.pop() {
+            match used.get_mut(index) {
+                Some(flag) if !*flag => *flag = true,
+                _ => continue,
+            }
+            stack.extend(
+                self.class_names[index]
+                    .fallback_class_ids()
+                    .iter()
+                    .map(|i| i.as_usize()),
+            );
+        }
+        let class_map = compact_map(&used);
+        for obj in &mut self.objects {
+            if let Some(index) = class_map.get(obj.class_name_id().as_usize()) {
+                obj.set_class_name_index(*index);
+            }
+        }
+        (self.class_names, report.class_names) =
+            split_used(std::mem::take(&mut self.class_names), &used);
+        for cls in &mut self.class_names {
+            let fallbacks = cls
+                .fallback_class_ids()
+                .iter()
+                .map(|i| class_map.get(i.as_usize()).map_or(*i, |i| (*i).into()))
+                .collect();
+            cls.set_fallback_class_ids(fallbacks);
+        }
+
+        report
+    }
+
+    /// Returns indeces of objects that can't be reached by following object references
+    /// from the root object, i.e. the first one.
+    pub fn unreachable_objects(&self) -> Vec<usize> {
+        let reachable = self.reachable_objects();
+        (0..self.objects.len()).filter(|i| !reachable[*i]).collect()
+    }
+
+    /// Removes [unreachable objects](NIBArchive::unreachable_objects()) along with their
+    /// values and returns them.
+    ///
+    /// Values' indeces and object references are remapped accordingly.
+    pub fn strip_unreachable(&mut self) -> Vec<(Object, Vec<Value>)> {
+        let reachable = self.reachable_objects();
+        if reachable.iter().all(|r| *r) {
+            return Vec::new();
+        }
+
+        // Values are kept if they belong to a reachable object or don't belong to any
+        let mut used_values = vec![true; self.values.len()];
+        for keep in [false, true] {
+            for (obj, _) in self
+                .objects
+                .iter()
+                .zip(&reachable)
+                .filter(|(_, r)| **r == keep)
+            {
+                let range = obj.value_range().unwrap_or_default();
+                let (start, end) = (range.start, range.end.min(used_values.len()));
+                for flag in used_values.iter_mut().take(end).skip(start) {
+                    *flag = keep;
+                }
+            }
+        }
+
+        let removed_objects: Vec<(Object, Vec<Value>)> = self
+            .objects
+            .iter()
+            .zip(&reachable)
+            .filter(|(_, r)| !**r)
+            .map(|(obj, _)| (obj.clone(), self.object_values(obj).to_vec()))
+            .collect();
+
+        let object_map = compact_map(&reachable);
+        for val in &mut self.values {
+            if let ValueVariant::ObjectRef(r) = val.value() {
+                match reachable.get(*r as usize) {
+                    Some(true) => {
+                        val.set_value(ValueVariant::ObjectRef(object_map[*r as usize] as u32))
+                    }
+                    Some(false) => val.set_value(ValueVariant::Nil),
+                    None => {}
+                }
+            }
+        }
+        let value_map = compact_map(&used_values);
+        for obj in &mut self.objects {
+            if let Some(index) = value_map.get(obj.first_value_id().as_usize()) {
+                obj.set_values_index(*index);
+            }
+        }
+        self.values = split_used(std::mem::take(&mut self.values), &used_values).0;
+        self.objects = split_used(std::mem::take(&mut self.objects), &reachable).0;
+        removed_objects
+    }
+
+    fn reachable_objects(&self) -> Vec<bool> {
+        let mut reachable = vec![false; self.objects.len()];
+        let mut stack = vec![0];
+        while let Some(index) = stack.pop() {
+            match reachable.get_mut(index) {
+                Some(flag) if !*flag => *flag = true,
+                _ => continue,
+            }
+            for val in self.object_values(&self.objects[index]) {
+                if let ValueVariant::ObjectRef(r) = val.value() {
+                    stack.push(*r as usize);
+                }
+            }
+        }
+        reachable
+    }
+}
+
+/// Maps every old index to the count of used elements before it. The map has one
+/// extra entry, so an index pointing right past the end is remapped as well.
+fn compact_map(used: &[bool]) -> Vec<VarInt> {
+    let mut map = Vec::with_capacity(used.len() + 1);
+    let mut count = 0;
+    for flag in used {
+        map.push(count);
+        if *flag {
+            count += 1;
+        }
+    }
+    map.push(count);
+    map
+}
+
+/// Splits elements into used and unused ones.
+fn split_used<T>(elements: Vec<T>, used: &[bool]) -> (Vec<T>, Vec<T>) {
+    let mut kept = Vec::with_capacity(elements.len());
+    let mut removed = Vec::new();
+    for (element, flag) in elements.into_iter().zip(used) {
+        if *flag {
+            kept.push(element);
+        } else {
+            removed.push(element);
+        }
+    }
+    (kept, removed)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ClassName, ClassNameIndex, NIBArchive, Object, Value, ValueVariant};
+
+    fn roundtrip(archive: &NIBArchive) {
+        assert_eq!(
+            &NIBArchive::from_bytes(archive.to_bytes()).unwrap(),
+            archive
+        );
+    }
+
+    #[test]
+    fn prune() {
+        let class = |name: &str, fallbacks: Vec<i32>| {
+            ClassName::with_fallbacks(
+                name.into(),
+                fallbacks.into_iter().map(ClassNameIndex).collect(),
+            )
+        };
+        let mut archive = NIBArchive::new(
+            vec![Object::new(2, 1, 2), Object::new(4, 4, 1)],
+            vec!["Unused".into(), "A".into(), "Gone".into(), "B".into()],
+            vec![
+                Value::new(0, ValueVariant::Nil),
+                Value::new(1, ValueVariant::Int8(1)),
+                Value::new(3, ValueVariant::ObjectRef(1)),
+                Value::new(2, ValueVariant::Nil),
+                Value::new(3, ValueVariant::ObjectRef(0)),
+            ],
+            vec![
+                class("Unused", vec![]),
+                class("Other", vec![4]),
+                class("MyView", vec![3]),
+                class("UIView", vec![]),
+                class("NSObject", vec![]),
+            ],
+        )
+        .unwrap();
+        let report = archive.prune();
+        assert_eq!(report.keys, ["Unused", "Gone"]);
+        assert_eq!(report.values.len(), 2);
+        assert_eq!(
+            report
+                .class_names
+                .iter()
+                .map(|c| c.name())
+                .collect::<Vec<_>>(),
+            ["Unused", "Other"]
+        );
+
+        assert_eq!(archive.keys(), ["A", "B"]);
+        assert_eq!(
+            archive.values(),
+            [
+                Value::new(0, ValueVariant::Int8(1)),
+                Value::new(1, ValueVariant::ObjectRef(1)),
+                Value::new(1, ValueVariant::ObjectRef(0)),
+            ]
+        );
+        assert_eq!(
+            archive.objects(),
+            [Object::new(0, 0, 2), Object::new(2, 2, 1)]
+        );
+        assert_eq!(
+            archive.class_names(),
+            [
+                class("MyView", vec![1]),
+                class("UIView", vec![]),
+                class("NSObject", vec![])
+            ]
+        );
+        assert!(archive.prune().is_empty());
+        roundtrip(&archive);
+    }
+
+    #[test]
+    fn strip_unreachable() {
+        let mut archive = NIBArchive::new(
+            vec![
+                Object::new(0, 0, 2),
+                Object::new(0, 2, 1),
+                Object::new(1, 4, 1),
+                Object::new(1, 5, 1),
+            ],
+            vec!["UIChild".into(), "UITag".into()],
+            vec![
+                Value::new(0, ValueVariant::ObjectRef(2)),
+                Value::new(1, ValueVariant::Int8(1)),
+                Value::new(0, ValueVariant::ObjectRef(2)),
+                // Doesn't belong to any object, so it's kept
+                Value::new(0, ValueVariant::ObjectRef(1)),
+                Value::new(0, ValueVariant::ObjectRef(0)),
+                Value::new(1, ValueVariant::Int8(3)),
+            ],
+            vec![
+                ClassName::with_fallbacks("UIView".into(), vec![]),
+                ClassName::with_fallbacks("UILabel".into(), vec![]),
+            ],
+        )
+        .unwrap();
+        assert_eq!(archive.unreachable_objects(), [1, 3]);
+        let removed = archive.strip_unreachable();
+        assert_eq!(
+            removed,
+            [
+                (
+                    Object::new(0, 2, 1),
+                    vec![Value::new(0, ValueVariant::ObjectRef(2))]
+                ),
+                (
+                    Object::new(1, 5, 1),
+                    vec![Value::new(1, ValueVariant::Int8(3))]
+                ),
+            ]
+        );
+        assert_eq!(
+            archive.objects(),
+            [Object::new(0, 0, 2), Object::new(1, 3, 1)]
+        );
+        assert_eq!(
+            archive.values(),
+            [
+                Value::new(0, ValueVariant::ObjectRef(1)),
+                Value::new(1, ValueVariant::Int8(1)),
+                Value::new(0, ValueVariant::Nil),
+                Value::new(0, ValueVariant::ObjectRef(0)),
+            ]
+        );
+        assert!(archive.unreachable_objects().is_empty());
+        assert!(archive.strip_unreachable().is_empty());
+        roundtrip(&archive);
+    }
+}
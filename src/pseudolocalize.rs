@@ -0,0 +1,251 @@
+use crate::NIBArchive;
+use std::collections::HashMap;
+
+/// Accented replacements of ASCII letters.
+const ACCENTED: &[(char, char)] = &[
+    ('a', 'á'),
+    ('c', 'ç'),
+    ('e', 'é'),
+    ('i', 'í'),
+    ('n', 'ñ'),
+    ('o', 'ó'),
+    ('s', 'š'),
+    ('u', 'ú'),
+    ('y', 'ý'),
+    ('z', 'ž'),
+    ('A', 'Å'),
+    ('C', 'Ç'),
+    ('E', 'É'),
+    ('I', 'Í'),
+    ('N', 'Ñ'),
+    ('O', 'Ö'),
+    ('S', 'Š'),
+    ('U', 'Ü'),
+    ('Y', 'Ý'),
+    ('Z', 'Ž'),
+];
+
+/// Options that control [NIBArchive::pseudolocalize()].
+#[derive(Debug, Clone, PartialEq)]
+pub struct PseudolocalizeOptions {
+    brackets: bool,
+    accents: bool,
+    expansion: f32,
+}
+
+impl Default for PseudolocalizeOptions {
+    fn default() -> Self {
+        Self {
+            brackets: true,
+            accents: true,
+            expansion: 0.3,
+        }
+    }
+}
+
+impl PseudolocalizeOptions {
+    /// Creates default options: strings are bracketed, accented and expanded by 30%.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns whether strings are wrapped in square brackets.
+    pub fn brackets(&self) -> bool {
+        self.brackets
+    }
+
+    /// Enables or disables wrapping strings in square brackets, which makes
+    /// truncated strings easy to spot.
+    pub fn set_brackets(&mut self, value: bool) {
+        self.brackets = value;
+    }
+
+    /// Returns whether letters are replaced with accented ones.
+    pub fn accents(&self) -> bool {
+        self.accents
+    }
+
+    /// Enables or disables replacing letters with accented ones.
+    pub fn set_accents(&mut self, value: bool) {
+        self.accents = value;
+    }
+
+    /// Returns the length expansion factor.
+    pub fn expansion(&self) -> f32 {
+        self.expansion
+    }
+
+    /// Sets the length expansion factor, e.g. `0.3` makes strings 30% longer
+    /// by appending `~` characters.
+    pub fn set_expansion(&mut self, value: f32) {
+        self.expansion = value;
+    }
+}
+
+impl NIBArchive {
+    /// Pseudolocalizes all [localizable strings](NIBArchive::localizable_strings())
+    /// and returns the count of changed strings.
+    ///
+    /// Only user-visible text is changed. Outlet and action names, class names and
+    /// identifiers are left intact, so the nib still loads.
+    ///
+    /// Format specifiers like `%@` or `%1$d` are kept intact. Strings keep their encoding.
+    pub fn pseudolocalize(&mut self, options: &PseudolocalizeOptions) -> usize {
+        let strings: HashMap<String, String> = self
+            .localizable_strings()
+            .into_iter()
+            .map(|s| {
+                (
+                    s.key_path().to_string(),
+                    pseudolocalize_string(s.value(), options),
+                )
+            })
+            .collect();
+        self.apply_strings(&strings)
+    }
+}
+
+/// Pseudolocalizes a single string.
+///
+/// Format specifiers follow the `printf` grammar: `%`, an optional argument position like
+/// `1$`, flags, a width, a precision, a length modifier and a conversion character,
+/// e.g. `%@`, `%-5d` or `%.2f`. A `%` that doesn't start a specifier is treated as text.
+/// The space flag isn't recognized, since text like `50% off` is far more common.
+pub fn pseudolocalize_string(s: &str, options: &PseudolocalizeOptions) -> String {
+    let mut result = String::with_capacity(s.len() * 2);
+    if options.brackets {
+        result.push('[');
+    }
+    let mut rest = s;
+    while let Some(c) = rest.chars().next() {
+        if let Some(len) = format_specifier_len(rest) {
+            result.push_str(&rest[..len]);
+            rest = &rest[len..];
+            continue;
+        }
+        let replacement = ACCENTED
+            .iter()
+            .find(|(from, _)| *from == c)
+            .filter(|_| options.accents)
+            .map_or(c, |(_, to)| *to);
+        result.push(replacement);
+        rest = &rest[c.len_utf8()..];
+    }
+    let padding = (s.chars().count() as f32 * options.expansion.max(0.0)).ceil() as usize;
+    result.extend(std::iter::repeat_n('~', padding));
+    if options.brackets {
+        result.push(']');
+    }
+    result
+}
+
+/// Returns the length of a format specifier `s` starts with, if any.
+fn format_specifier_len(s: &str) -> Option<usize> {
+    let bytes = s.as_bytes();
+    if bytes.first() != Some(&b'%') {
+        return None;
+    }
+    let digits = |i: usize| i + bytes[i..].iter().take_while(|b| b.is_ascii_digit()).count();
+    let mut i = 1;
+
+    // Argument position
+    let end = digits(i);
+    if end > i && bytes.get(end) == Some(&b'$') {
+        i = end + 1;
+    }
+    while bytes.get(i).is_some_and(|b| b"-+#0'".contains(b)) {
+        i += 1;
+    }
+    // Width and precision, either numbers or `*`
+    i = if bytes.get(i) == Some(&b'*') {
+        i + 1
+    } else {
+        digits(i)
+    };
+    if bytes.get(i) == Some(&b'.') {
+        i += 1;
+        i = if bytes.get(i) == Some(&b'*') {
+            i + 1
+        } else {
+            digits(i)
+        };
+    }
+    for modifier in ["hh", "ll", "h", "l", "q", "L", "z", "t", "j"] {
+        if s[i..].starts_with(modifier) {
+            i += modifier.len();
+            break;
+        }
+    }
+    bytes
+        .get(i)
+        .filter(|b| b"@%diouxXfFeEgGaAcCsSpDUO".contains(b))
+        .map(|_| i + 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{pseudolocalize_string, PseudolocalizeOptions};
+    use crate::{ClassName, NIBArchive, Object, Value, ValueVariant};
+
+    fn pseudolocalize(s: &str) -> String {
+        let mut options = PseudolocalizeOptions::new();
+        options.set_brackets(false);
+        options.set_expansion(0.0);
+        pseudolocalize_string(s, &options)
+    }
+
+    #[test]
+    fn format_specifiers() {
+        assert_eq!(pseudolocalize("%@ and %1$@"), "%@ áñd %1$@");
+        assert_eq!(
+            pseudolocalize("%-5d, %.2f, %lld, %*s"),
+            "%-5d, %.2f, %lld, %*s"
+        );
+        assert_eq!(pseudolocalize("100%% sure"), "100%% šúré");
+    }
+
+    #[test]
+    fn percent_in_text() {
+        assert_eq!(pseudolocalize("50% off"), "50% óff");
+        assert_eq!(pseudolocalize("100%! Yes"), "100%! Ýéš");
+        assert_eq!(pseudolocalize("%"), "%");
+    }
+
+    #[test]
+    fn only_text() {
+        let class = |name: &str| ClassName::with_fallbacks(name.into(), Vec::new());
+        let mut archive = NIBArchive::new(
+            vec![
+                Object::new(0, 0, 3),
+                Object::new(1, 3, 1),
+                Object::new(2, 4, 1),
+                Object::new(2, 5, 1),
+            ],
+            vec![
+                "UIText".into(),
+                "UIClassName".into(),
+                "UIConnection".into(),
+                "UILabel".into(),
+                "NS.bytes".into(),
+            ],
+            vec![
+                Value::new(0, ValueVariant::ObjectRef(3)),
+                Value::new(1, ValueVariant::Data(b"MyLabel".to_vec())),
+                Value::new(2, ValueVariant::ObjectRef(1)),
+                Value::new(3, ValueVariant::ObjectRef(2)),
+                Value::new(4, ValueVariant::Data(b"nameLabel".to_vec())),
+                Value::new(4, ValueVariant::Data(b"Name".to_vec())),
+            ],
+            vec![
+                class("UILabel"),
+                class("UIRuntimeOutletConnection"),
+                class("NSString"),
+            ],
+        )
+        .unwrap();
+        let original = archive.values().to_vec();
+        assert_eq!(archive.pseudolocalize(&PseudolocalizeOptions::new()), 1);
+        assert_eq!(archive.values()[..5], original[..5]);
+        assert_ne!(archive.values()[5], original[5]);
+    }
+}
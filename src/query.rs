@@ -0,0 +1,610 @@
+use crate::{Error, NIBArchive, Value, ValueIndex, ValueVariant, VarInt};
+
+/// A parsed key path like `UIViewController.UIView.UINibEncoderEmptyKey[2].UIFrame`.
+///
+/// The first component is a class name that selects the starting objects. Every next
+/// component is a key, optionally followed by an index in square brackets that selects
+/// a single value among the object's values with the same key. Intermediate values
+/// have to be object references, which are followed to get to the next object.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct KeyPath {
+    pub class_name: String,
+    pub components: Vec<KeyPathComponent>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct KeyPathComponent {
+    pub key: String,
+    pub index: Option<usize>,
+}
+
+impl std::str::FromStr for KeyPath {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = s.split('.');
+        let class_name = parts.next().unwrap_or_default();
+        if class_name.is_empty() {
+            return Err(Error::QueryError(format!(
+                "Key path `{s}` doesn't start with a class name"
+            )));
+        }
+        let mut components = Vec::new();
+        for part in parts {
+            let (key, index) = match part.find('[') {
+                Some(start) => {
+                    let Some(index) = part[start + 1..].strip_suffix(']') else {
+                        return Err(Error::QueryError(format!(
+                            "Unclosed bracket in key path component `{part}`"
+                        )));
+                    };
+                    let index = index.parse::<usize>().map_err(|_| {
+                        Error::QueryError(format!("Invalid index in key path component `{part}`"))
+                    })?;
+                    (&part[..start], Some(index))
+                }
+                None => (part, None),
+            };
+            if key.is_empty() {
+                return Err(Error::QueryError(format!("Empty key in key path `{s}`")));
+            }
+            components.push(KeyPathComponent {
+                key: key.into(),
+                index,
+            });
+        }
+        if components.is_empty() {
+            return Err(Error::QueryError(format!(
+                "Key path `{s}` doesn't contain any keys"
+            )));
+        }
+        Ok(Self {
+            class_name: class_name.into(),
+            components,
+        })
+    }
+}
+
+impl NIBArchive {
+    /// Returns all values matching a given key path.
+    ///
+    /// A key path starts with a class name followed by keys separated with dots, e.g.
+    /// `UIViewController.UIView.UINibEncoderEmptyKey[2].UIFrame`. Object references
+    /// are followed when a key isn't the last one. An optional index selects a single
+    /// value among the values with the same key, which is handy for arrays
+    /// encoded with `UINibEncoderEmptyKey`.
+    ///
+    /// Returns an error if the key path can't be parsed.
+    pub fn query(&self, path: &str) -> Result<Vec<&ValueVariant>, Error> {
+        let path: KeyPath = path.parse()?;
+        Ok(self
+            .resolve_key_path(&path)
+            .into_iter()
+            .map(|i| self.values[i.as_usize()].value())
+            .collect())
+    }
+
+    /// Replaces all values matching a given key path with `value` and returns
+    /// the count of replaced values.
+    ///
+    /// Key paths are described in [NIBArchive::query()]. Keys of the values stay the same.
+    ///
+    /// Returns an error if the key path can't be parsed.
+    pub fn set_key_path(&mut self, path: &str, value: ValueVariant) -> Result<usize, Error> {
+        let path: KeyPath = path.parse()?;
+        let mut indeces = self.resolve_key_path(&path);
+        // A value may be reachable through several objects
+        indeces.sort_unstable();
+        indeces.dedup();
+        for i in &indeces {
+            self.values[i.as_usize()].set_value(value.clone());
+        }
+        Ok(indeces.len())
+    }
+
+    /// Removes all values matching a given key path and returns them.
+    ///
+    /// Key paths are described in [NIBArchive::query()]. Objects owning the values
+    /// are updated the same way [NIBArchive::remove_value()] does.
+    ///
+    /// Returns an error if the key path can't be parsed.
+    pub fn remove_key_path(&mut self, path: &str) -> Result<Vec<Value>, Error> {
+        let path: KeyPath = path.parse()?;
+        let mut indeces = self.resolve_key_path(&path);
+        indeces.sort_unstable();
+        indeces.dedup();
+        let mut removed = Vec::with_capacity(indeces.len());
+        // Removing from the end keeps the remaining indeces valid
+        for i in indeces.into_iter().rev() {
+            removed.push(self.remove_value(i)?);
+        }
+        removed.reverse();
+        Ok(removed)
+    }
+
+    /// Returns indeces of all values matching a key path, in the order they're found.
+    pub(crate) fn resolve_key_path(&self, path: &KeyPath) -> Vec<ValueIndex> {
+        let mut objects: Vec<usize> = self
+            .find_by_class(&path.class_name)
+            .into_iter()
+            .map(|(i, _)| i)
+            .collect();
+        let mut result = Vec::new();
+        let last = path.components.len() - 1;
+        for (depth, component) in path.components.iter().enumerate() {
+            let mut next_objects = Vec::new();
+            for index in objects {
+                let obj = &self.objects[index];
+                let start = obj.first_value_id();
+                let matching = self
+                    .object_values(obj)
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, val)| {
+                        self.keys
+                            .get(val.key_id().as_usize())
+                            .is_some_and(|k| k == &component.key)
+                    });
+                let selected: Vec<_> = match component.index {
+                    Some(n) => matching.skip(n).take(1).collect(),
+                    None => matching.collect(),
+                };
+                for (offset, val) in selected {
+                    if depth == last {
+                        result.push(ValueIndex(start.0 + offset as VarInt));
+                    } else if let ValueVariant::ObjectRef(r) = val.value() {
+                        if (*r as usize) < self.objects.len() {
+                            next_objects.push(*r as usize);
+                        }
+                    }
+                }
+            }
+            objects = next_objects;
+        }
+        result
+    }
+}
+
+/// A parsed object selection expression like
+/// `objects[class="UIButton" && values["UIEnabled"]=false]`.
+///
+/// The `objects[...]` wrapper is optional. Supported operands are:
+/// - `class` — the class name of an object;
+/// - `index` — the index of an object;
+/// - `values["Key"]` — values of an object with a given key. Used without a comparison it
+///   checks that such a value exists, otherwise at least one of the values has to match.
+///
+/// Operands can be compared with string, number, `true`, `false` and `nil` literals using
+/// `=`, `!=`, `<`, `<=`, `>` and `>=`. Strings are compared with class names and
+/// [data values](ValueVariant::Data) that [contain strings](ValueVariant::data_as_string).
+/// Comparisons can be combined with `&&`, `||`, `!` and parentheses, which may be nested
+/// up to 64 levels deep.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Expression {
+    node: Node,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Node {
+    Or(Vec<Node>),
+    And(Vec<Node>),
+    Not(Box<Node>),
+    Exists(Operand),
+    Compare(Operand, Operator, Literal),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Operand {
+    Class,
+    Index,
+    Values(String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Operator {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Literal {
+    String(String),
+    Number(f64),
+    Bool(bool),
+    Nil,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Ident(String),
+    String(String),
+    Number(f64),
+    Punct(&'static str),
+}
+
+const PUNCTUATION: &[&str] = &[
+    "&&", "||", "!=", "<=", ">=", "=", "<", ">", "!", "(", ")", "[", "]",
+];
+
+fn tokenize(s: &str) -> Result<Vec<Token>, Error> {
+    let unterminated = || Error::QueryError("Unterminated string literal".into());
+    let mut tokens = Vec::new();
+    let mut chars = s.char_indices().peekable();
+    while let Some(&(i, c)) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+        } else if c == '"' {
+            chars.next();
+            let mut string = String::new();
+            loop {
+                match chars.next() {
+                    Some((_, '"')) => break,
+                    Some((_, '\\')) => string.push(chars.next().ok_or_else(unterminated)?.1),
+                    Some((_, c)) => string.push(c),
+                    None => return Err(unterminated()),
+                }
+            }
+            tokens.push(Token::String(string));
+        } else if c.is_ascii_digit() || c == '-' {
+            let mut end = i + c.len_utf8();
+            chars.next();
+            let mut previous = c;
+            while let Some(&(j, c)) = chars.peek() {
+                let is_exponent_sign = matches!(c, '+' | '-') && matches!(previous, 'e' | 'E');
+                if !(c.is_ascii_digit() || c == '.' || c == 'e' || c == 'E' || is_exponent_sign) {
+                    break;
+                }
+                end = j + c.len_utf8();
+                previous = c;
+                chars.next();
+            }
+            let number = s[i..end]
+                .parse()
+                .map_err(|_| Error::QueryError(format!("Invalid number `{}`", &s[i..end])))?;
+            tokens.push(Token::Number(number));
+        } else if c.is_alphabetic() || c == '_' {
+            let mut end = i;
+            while let Some(&(j, c)) = chars.peek() {
+                if !(c.is_alphanumeric() || c == '_') {
+                    break;
+                }
+                end = j + c.len_utf8();
+                chars.next();
+            }
+            tokens.push(Token::Ident(s[i..end].into()));
+        } else {
+            let Some(punct) = PUNCTUATION.iter().find(|p| s[i..].starts_with(**p)) else {
+                return Err(Error::QueryError(format!("Unexpected character `{c}`")));
+            };
+            for _ in 0..punct.len() {
+                chars.next();
+            }
+            tokens.push(Token::Punct(punct));
+        }
+    }
+    Ok(tokens)
+}
+
+/// Maximum nesting of parentheses and negations, so hostile expressions can't
+/// overflow the stack.
+const MAX_NESTING: usize = 64;
+
+struct Parser {
+    tokens: Vec<Token>,
+    position: usize,
+    nesting: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.position).cloned();
+        self.position += 1;
+        token
+    }
+
+    fn eat(&mut self, punct: &str) -> bool {
+        if matches!(self.peek(), Some(Token::Punct(p)) if *p == punct) {
+            self.position += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn enter(&mut self) -> Result<(), Error> {
+        self.nesting += 1;
+        if self.nesting > MAX_NESTING {
+            return Err(Error::QueryError(format!(
+                "Expression is nested deeper than {MAX_NESTING} levels"
+            )));
+        }
+        Ok(())
+    }
+
+    fn expect(&mut self, punct: &str) -> Result<(), Error> {
+        if self.eat(punct) {
+            Ok(())
+        } else {
+            Err(Error::QueryError(format!("Expected `{punct}`")))
+        }
+    }
+
+    fn or(&mut self) -> Result<Node, Error> {
+        let mut nodes = vec![self.and()?];
+        while self.eat("||") {
+            nodes.push(self.and()?);
+        }
+        Ok(match nodes.len() {
+            1 => nodes.remove(0),
+            _ => Node::Or(nodes),
+        })
+    }
+
+    fn and(&mut self) -> Result<Node, Error> {
+        let mut nodes = vec![self.unary()?];
+        while self.eat("&&") {
+            nodes.push(self.unary()?);
+        }
+        Ok(match nodes.len() {
+            1 => nodes.remove(0),
+            _ => Node::And(nodes),
+        })
+    }
+
+    fn unary(&mut self) -> Result<Node, Error> {
+        if self.eat("!") {
+            self.enter()?;
+            let node = Node::Not(Box::new(self.unary()?));
+            self.nesting -= 1;
+            return Ok(node);
+        }
+        if self.eat("(") {
+            self.enter()?;
+            let node = self.or()?;
+            self.expect(")")?;
+            self.nesting -= 1;
+            return Ok(node);
+        }
+        let operand = match self.next() {
+            Some(Token::Ident(ident)) if ident == "class" => Operand::Class,
+            Some(Token::Ident(ident)) if ident == "index" => Operand::Index,
+            Some(Token::Ident(ident)) if ident == "values" => {
+                self.expect("[")?;
+                let Some(Token::String(key)) = self.next() else {
+                    return Err(Error::QueryError("Expected a key string".into()));
+                };
+                self.expect("]")?;
+                Operand::Values(key)
+            }
+            token => {
+                return Err(Error::QueryError(format!(
+                    "Expected `class`, `index` or `values`, got {token:?}"
+                )))
+            }
+        };
+        let operator = match self.peek() {
+            Some(Token::Punct("=")) => Operator::Eq,
+            Some(Token::Punct("!=")) => Operator::Ne,
+            Some(Token::Punct("<")) => Operator::Lt,
+            Some(Token::Punct("<=")) => Operator::Le,
+            Some(Token::Punct(">")) => Operator::Gt,
+            Some(Token::Punct(">=")) => Operator::Ge,
+            _ => return Ok(Node::Exists(operand)),
+        };
+        self.position += 1;
+        let literal = match self.next() {
+            Some(Token::String(s)) => Literal::String(s),
+            Some(Token::Number(n)) => Literal::Number(n),
+            Some(Token::Ident(ident)) if ident == "true" => Literal::Bool(true),
+            Some(Token::Ident(ident)) if ident == "false" => Literal::Bool(false),
+            Some(Token::Ident(ident)) if ident == "nil" => Literal::Nil,
+            token => {
+                return Err(Error::QueryError(format!(
+                    "Expected a literal, got {token:?}"
+                )))
+            }
+        };
+        Ok(Node::Compare(operand, operator, literal))
+    }
+}
+
+impl std::str::FromStr for Expression {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut tokens = tokenize(s)?;
+        // Strip the optional `objects[...]` wrapper
+        if matches!(tokens.first(), Some(Token::Ident(ident)) if ident == "objects") {
+            if tokens.get(1) != Some(&Token::Punct("["))
+                || tokens.last() != Some(&Token::Punct("]"))
+            {
+                return Err(Error::QueryError(
+                    "Expected an expression in square brackets after `objects`".into(),
+                ));
+            }
+            tokens.pop();
+            tokens.drain(..2);
+        }
+        let mut parser = Parser {
+            tokens,
+            position: 0,
+            nesting: 0,
+        };
+        let node = parser.or()?;
+        if let Some(token) = parser.peek() {
+            return Err(Error::QueryError(format!("Unexpected token {token:?}")));
+        }
+        Ok(Self { node })
+    }
+}
+
+impl Expression {
+    /// Returns whether an object at `index` matches the expression.
+    pub fn matches(&self, archive: &NIBArchive, index: usize) -> bool {
+        index < archive.objects().len() && Self::eval(&self.node, archive, index)
+    }
+
+    fn eval(node: &Node, archive: &NIBArchive, index: usize) -> bool {
+        match node {
+            Node::Or(nodes) => nodes.iter().any(|n| Self::eval(n, archive, index)),
+            Node::And(nodes) => nodes.iter().all(|n| Self::eval(n, archive, index)),
+            Node::Not(a) => !Self::eval(a, archive, index),
+            Node::Exists(Operand::Values(key)) => archive.object_value(index, key).is_some(),
+            Node::Exists(_) => true,
+            Node::Compare(operand, operator, literal) => {
+                let obj = &archive.objects()[index];
+                match operand {
+                    Operand::Class => {
+                        let name = archive
+                            .class_names()
+                            .get(obj.class_name_id().as_usize())
+                            .map(|c| c.name());
+                        match (name, literal) {
+                            (Some(name), Literal::String(s)) => {
+                                compare(name.cmp(s.as_str()), *operator)
+                            }
+                            _ => false,
+                        }
+                    }
+                    Operand::Index => match literal {
+                        Literal::Number(n) => (index as f64)
+                            .partial_cmp(n)
+                            .is_some_and(|o| compare(o, *operator)),
+                        _ => false,
+                    },
+                    Operand::Values(key) => archive
+                        .object_values(obj)
+                        .iter()
+                        .filter(|v| {
+                            archive
+                                .keys()
+                                .get(v.key_id().as_usize())
+                                .is_some_and(|k| k == key)
+                        })
+                        .any(|v| compare_value(v.value(), *operator, literal)),
+                }
+            }
+        }
+    }
+}
+
+fn compare(ordering: std::cmp::Ordering, operator: Operator) -> bool {
+    use std::cmp::Ordering::*;
+    match operator {
+        Operator::Eq => ordering == Equal,
+        Operator::Ne => ordering != Equal,
+        Operator::Lt => ordering == Less,
+        Operator::Le => ordering != Greater,
+        Operator::Gt => ordering == Greater,
+        Operator::Ge => ordering != Less,
+    }
+}
+
+fn compare_value(value: &ValueVariant, operator: Operator, literal: &Literal) -> bool {
+    let ordering = match (value, literal) {
+        (ValueVariant::Bool(v), Literal::Bool(l)) => Some(v.cmp(l)),
+        (ValueVariant::Nil, Literal::Nil) => Some(std::cmp::Ordering::Equal),
+        (ValueVariant::Data(_), Literal::String(l)) => {
+            value.data_as_string().map(|s| s.as_str().cmp(l.as_str()))
+        }
+        (_, Literal::Number(l)) => {
+            let number = match value {
+                ValueVariant::Int8(v) => f64::from(*v),
+                ValueVariant::Int16(v) => f64::from(*v),
+                ValueVariant::Int32(v) => f64::from(*v),
+                ValueVariant::Int64(v) => *v as f64,
+                ValueVariant::Float(v) => f64::from(*v),
+                ValueVariant::Double(v) => *v,
+                ValueVariant::ObjectRef(v) => f64::from(*v),
+                _ => return false,
+            };
+            number.partial_cmp(l)
+        }
+        _ => None,
+    };
+    ordering.is_some_and(|o| compare(o, operator))
+}
+
+impl NIBArchive {
+    /// Returns indeces and [values](Value) of all objects matching a given
+    /// [expression](Expression), e.g. `objects[class="UIButton" && values["UIEnabled"]=false]`.
+    ///
+    /// Returns an error if the expression can't be parsed.
+    pub fn select(&self, expression: &str) -> Result<Vec<(usize, &[Value])>, Error> {
+        let expression: Expression = expression.parse()?;
+        let mut result = Vec::new();
+        for (i, obj) in self.objects.iter().enumerate() {
+            if expression.matches(self, i) {
+                result.push((i, self.object_values(obj)));
+            }
+        }
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn nesting_limit() {
+        let nested = format!("{}class=\"UIView\"", "!".repeat(64));
+        assert!(nested.parse::<Expression>().is_ok());
+        let hostile = format!("{}class=\"UIView\"", "!".repeat(200_000));
+        assert!(matches!(
+            hostile.parse::<Expression>(),
+            Err(Error::QueryError(_))
+        ));
+        let hostile = format!("{}index=0{}", "(".repeat(200_000), ")".repeat(200_000));
+        assert!(matches!(
+            hostile.parse::<Expression>(),
+            Err(Error::QueryError(_))
+        ));
+    }
+
+    #[test]
+    fn strings() {
+        assert_eq!(
+            tokenize(r#""a\"b""#).unwrap(),
+            [Token::String("a\"b".into())]
+        );
+        for s in [r#""abc"#, r#""abc\"#, r#""abc\""#] {
+            let err = tokenize(s).unwrap_err();
+            assert!(
+                err.to_string().contains("Unterminated string literal"),
+                "{err}"
+            );
+        }
+    }
+
+    #[test]
+    fn numbers() {
+        assert_eq!(
+            tokenize("1e-5 -2.5E+3 7e2").unwrap(),
+            [
+                Token::Number(1e-5),
+                Token::Number(-2.5e3),
+                Token::Number(7e2)
+            ]
+        );
+        // A sign is only part of a number right after the exponent marker
+        assert_eq!(
+            tokenize("1-2").unwrap(),
+            [Token::Number(1.0), Token::Number(-2.0)]
+        );
+    }
+
+    #[test]
+    fn long_chains() {
+        let chain = vec!["index=0"; 200_000].join(" && ");
+        assert!(chain.parse::<Expression>().is_ok());
+    }
+}
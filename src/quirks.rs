@@ -0,0 +1,136 @@
+/// Differences in how archives of a given format and coder version are encoded.
+///
+/// The decoder and higher level extractors consult quirks instead of comparing versions
+/// themselves. Unknown version combinations get conservative quirks that don't assume
+/// anything about the encoder, and the decoder reports them with
+/// [DecodeWarning::UnknownVersion](crate::DecodeWarning::UnknownVersion).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Quirks {
+    format_version: u32,
+    coder_version: u32,
+    known: bool,
+    minimal_var_ints: bool,
+}
+
+/// Version combinations produced by Apple tools.
+const KNOWN_VERSIONS: &[Quirks] = &[
+    Quirks {
+        format_version: 1,
+        coder_version: 9,
+        known: true,
+        minimal_var_ints: true,
+    },
+    Quirks {
+        format_version: 1,
+        coder_version: 10,
+        known: true,
+        minimal_var_ints: true,
+    },
+];
+
+impl Quirks {
+    /// Returns quirks of a known version combination, or `None` if it's unknown.
+    pub fn known(format_version: u32, coder_version: u32) -> Option<Self> {
+        KNOWN_VERSIONS
+            .iter()
+            .find(|q| q.format_version == format_version && q.coder_version == coder_version)
+            .copied()
+    }
+
+    /// Returns quirks of a version combination. Unknown combinations get conservative
+    /// quirks, e.g. non-minimal variable integers aren't considered anomalies, since
+    /// the encoder that produced them isn't known.
+    pub fn for_versions(format_version: u32, coder_version: u32) -> Self {
+        Self::known(format_version, coder_version).unwrap_or(Self {
+            format_version,
+            coder_version,
+            known: false,
+            minimal_var_ints: false,
+        })
+    }
+
+    pub fn format_version(&self) -> u32 {
+        self.format_version
+    }
+
+    pub fn coder_version(&self) -> u32 {
+        self.coder_version
+    }
+
+    /// Returns `true` if the version combination is known, otherwise the quirks are assumed.
+    pub fn is_known(&self) -> bool {
+        self.known
+    }
+
+    /// Returns `true` if the encoder of these versions always writes minimal variable
+    /// integers, so non-minimal ones are reported as
+    /// [anomalies](crate::DecodeWarning::NonMinimalVarInt).
+    pub fn minimal_var_ints(&self) -> bool {
+        self.minimal_var_ints
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{io::Cursor, ClassName, DecodeOptions, DecodeWarning, NIBArchive, Object};
+    use alloc::{vec, vec::Vec};
+
+    /// Encodes an empty object with a class name index encoded non-minimally.
+    fn encode(coder_version: u32) -> Vec<u8> {
+        let mut archive = NIBArchive::new(
+            vec![Object::new(0, 0, 0)],
+            vec![],
+            vec![],
+            vec![ClassName::with_fallbacks("UIView".into(), vec![])],
+        )
+        .unwrap();
+        archive.set_coder_version(coder_version);
+        let mut bytes = archive.to_bytes();
+        let offset =
+            |bytes: &[u8], i: usize| u32::from_le_bytes(bytes[i..i + 4].try_into().unwrap());
+        bytes.insert(offset(&bytes, 22) as usize, 0x00);
+        for i in [30, 38, 46] {
+            let shifted = offset(&bytes, i) + 1;
+            bytes[i..i + 4].copy_from_slice(&shifted.to_le_bytes());
+        }
+        bytes
+    }
+
+    fn decode(bytes: &[u8], strict: bool) -> Result<Vec<DecodeWarning>, crate::Error> {
+        let mut options = DecodeOptions::new();
+        options.set_strict(strict);
+        NIBArchive::from_reader_with_options(&mut Cursor::new(bytes), &options).map(|(_, w)| w)
+    }
+
+    #[test]
+    fn known_versions() {
+        assert!(Quirks::for_versions(1, 9).minimal_var_ints());
+        assert!(Quirks::for_versions(1, 10).is_known());
+
+        let bytes = encode(10);
+        assert_eq!(
+            decode(&bytes, false).unwrap(),
+            [DecodeWarning::NonMinimalVarInt { offset: 50 }]
+        );
+        assert!(decode(&bytes, true).is_err());
+    }
+
+    #[test]
+    fn unknown_versions() {
+        let quirks = Quirks::for_versions(1, 11);
+        assert!(!quirks.is_known());
+        assert!(!quirks.minimal_var_ints());
+
+        let bytes = encode(11);
+        let warning = DecodeWarning::UnknownVersion {
+            format_version: 1,
+            coder_version: 11,
+        };
+        assert_eq!(
+            decode(&bytes, false).unwrap(),
+            decode(&bytes, true).unwrap()
+        );
+        assert_eq!(decode(&bytes, true).unwrap(), [warning]);
+    }
+}
@@ -0,0 +1,380 @@
+use crate::{
+    consts::keys,
+    strings::{decode_string, StringEncoding},
+    Error, NIBArchive, ValueVariant,
+};
+use std::collections::{HashMap, HashSet};
+
+/// Keys of values holding accessibility strings.
+const ACCESSIBILITY_KEYS: &[&str] = &[
+    keys::UI_ACCESSIBILITY_LABEL,
+    keys::UI_ACCESSIBILITY_HINT,
+    keys::UI_ACCESSIBILITY_VALUE,
+    keys::UI_ACCESSIBILITY_IDENTIFIER,
+    keys::UI_ACCESSIBILITY_CONFIGURATION_VALUE,
+];
+
+/// Class name prefixes of Apple frameworks. Classes with such prefixes aren't redacted.
+const SYSTEM_CLASS_PREFIXES: &[&str] = &[
+    "UI", "NS", "CA", "CL", "MK", "WK", "AV", "SK", "SCN", "PK", "_UI", "_NS",
+];
+
+/// Keys of string values holding class names, e.g. a custom class of a proxy object.
+const CLASS_NAME_KEYS: &[&str] = &[
+    keys::UI_CLASS_NAME,
+    keys::UI_ORIGINAL_CLASS_NAME,
+    keys::NS_CLASS_NAME,
+    keys::NS_ORIGINAL_CLASS_NAME,
+];
+
+/// Options that control [NIBArchive::redact()].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RedactOptions {
+    strings: bool,
+    accessibility: bool,
+    class_names: bool,
+    system_class_prefixes: Vec<String>,
+}
+
+impl Default for RedactOptions {
+    fn default() -> Self {
+        Self {
+            strings: true,
+            accessibility: true,
+            class_names: true,
+            system_class_prefixes: SYSTEM_CLASS_PREFIXES
+                .iter()
+                .map(|p| p.to_string())
+                .collect(),
+        }
+    }
+}
+
+impl RedactOptions {
+    /// Creates default options, which redact everything.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns whether all string data values are redacted.
+    pub fn strings(&self) -> bool {
+        self.strings
+    }
+
+    /// Enables or disables redaction of all string data values.
+    pub fn set_strings(&mut self, value: bool) {
+        self.strings = value;
+    }
+
+    /// Returns whether accessibility strings are redacted.
+    pub fn accessibility(&self) -> bool {
+        self.accessibility
+    }
+
+    /// Enables or disables redaction of accessibility labels, hints, values
+    /// and identifiers. Only matters if [strings](RedactOptions::set_strings())
+    /// aren't redacted.
+    pub fn set_accessibility(&mut self, value: bool) {
+        self.accessibility = value;
+    }
+
+    /// Returns whether custom class names are redacted.
+    pub fn class_names(&self) -> bool {
+        self.class_names
+    }
+
+    /// Enables or disables redaction of class names that don't start with
+    /// one of the [system prefixes](RedactOptions::set_system_class_prefixes()),
+    /// along with string values holding them, e.g. `UIClassName`.
+    pub fn set_class_names(&mut self, value: bool) {
+        self.class_names = value;
+    }
+
+    /// Returns class name prefixes of system frameworks.
+    pub fn system_class_prefixes(&self) -> &[String] {
+        &self.system_class_prefixes
+    }
+
+    /// Sets class name prefixes of system frameworks, e.g. `UI` and `NS`.
+    pub fn set_system_class_prefixes(&mut self, prefixes: Vec<String>) {
+        self.system_class_prefixes = prefixes;
+    }
+}
+
+/// Counts of elements replaced by [NIBArchive::redact()].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct RedactSummary {
+    /// Count of redacted string values, including values holding class names.
+    pub strings: usize,
+    /// Count of redacted class names.
+    pub class_names: usize,
+}
+
+impl NIBArchive {
+    /// Replaces strings and custom class names with placeholders, so the archive
+    /// can be shared without revealing its contents.
+    ///
+    /// Strings keep their encoding and byte length: ASCII letters become `x`/`X`, ASCII
+    /// digits become `0`, other letters and digits become placeholders of the same encoded
+    /// length, everything else is kept as is. Custom class names become `Class1`, `Class2`,
+    /// etc., padded with `_` to their original length, and so do string values holding
+    /// them (`UIClassName`, `NSClassName` and their `Original` variants). The structure
+    /// of the archive isn't changed.
+    pub fn redact(&mut self, options: &RedactOptions) -> RedactSummary {
+        let mut summary = RedactSummary::default();
+
+        let mut placeholders: HashMap<String, String> = HashMap::new();
+        let mut class_name_values = HashSet::new();
+        if options.class_names {
+            let mut placeholder_for = |name: &str, summary: &mut RedactSummary| {
+                if let Some(placeholder) = placeholders.get(name) {
+                    return placeholder.clone();
+                }
+                summary.class_names += 1;
+                let mut placeholder = format!("Class{}", summary.class_names);
+                while placeholder.len() < name.len() {
+                    placeholder.push('_');
+                }
+                placeholders.insert(name.to_string(), placeholder.clone());
+                placeholder
+            };
+            for class_name in &mut self.class_names {
+                if !is_custom_class(class_name.name(), &options.system_class_prefixes) {
+                    continue;
+                }
+                let placeholder = placeholder_for(class_name.name(), &mut summary);
+                class_name.set_name(placeholder);
+            }
+            for (i, val) in self.values.iter_mut().enumerate() {
+                let holds_class_name = self
+                    .keys
+                    .get(val.key_id().as_usize())
+                    .is_some_and(|k| CLASS_NAME_KEYS.contains(&k.as_str()));
+                if !holds_class_name {
+                    continue;
+                }
+                let Some((string, encoding)) = string_of(val.value()) else {
+                    continue;
+                };
+                class_name_values.insert(i);
+                if is_custom_class(&string, &options.system_class_prefixes) {
+                    let placeholder = placeholder_for(&string, &mut summary);
+                    val.set_value(ValueVariant::Data(encoding.encode(&placeholder)));
+                    summary.strings += 1;
+                }
+            }
+        }
+
+        let mut targets = Vec::new();
+        if options.strings {
+            targets.extend((0..self.values.len()).filter(|i| !class_name_values.contains(i)));
+        } else if options.accessibility {
+            for obj in &self.objects {
+                let start = obj.first_value_id().as_usize();
+                for (i, val) in self.object_values(obj).iter().enumerate() {
+                    let is_accessibility = self
+                        .keys
+                        .get(val.key_id().as_usize())
+                        .is_some_and(|k| ACCESSIBILITY_KEYS.contains(&k.as_str()));
+                    if is_accessibility {
+                        targets.push(start + i);
+                    }
+                }
+            }
+        }
+        for i in targets {
+            let Some((string, encoding)) = string_of(self.values[i].value()) else {
+                continue;
+            };
+            let placeholder = encoding.encode(&mask(&string));
+            self.values[i].set_value(ValueVariant::Data(placeholder));
+            summary.strings += 1;
+        }
+
+        summary
+    }
+
+    /// Renames custom class names according to `mapping` of original names to new ones
+    /// and returns the count of renamed class names.
+    ///
+    /// Custom classes that aren't in the mapping get generated names (`C1`, `C2`, etc.),
+    /// which are added to the mapping, so it can be saved with [class_mapping_to_string()]
+    /// and reused for other archives. Classes are custom if they don't start with one of the
+    /// [default system prefixes](RedactOptions::system_class_prefixes()), though entries
+    /// given in the mapping are applied to any class.
+    ///
+    /// Fallback lists point to class names by index, so they stay consistent. String values
+    /// holding class names (`UIClassName`, `NSClassName` and their `Original` variants)
+    /// are renamed as well.
+    pub fn obfuscate_class_names(&mut self, mapping: &mut HashMap<String, String>) -> usize {
+        let prefixes = RedactOptions::default().system_class_prefixes;
+        let mut used: HashSet<String> = self
+            .class_names
+            .iter()
+            .map(|c| c.name().to_string())
+            .chain(mapping.values().cloned())
+            .collect();
+        let mut counter = 0;
+        for class_name in &self.class_names {
+            let name = class_name.name();
+            if mapping.contains_key(name) || !is_custom_class(name, &prefixes) {
+                continue;
+            }
+            let new_name = loop {
+                counter += 1;
+                let candidate = format!("C{counter}");
+                if !used.contains(&candidate) {
+                    break candidate;
+                }
+            };
+            used.insert(new_name.clone());
+            mapping.insert(name.to_string(), new_name);
+        }
+
+        for val in &mut self.values {
+            let holds_class_name = self
+                .keys
+                .get(val.key_id().as_usize())
+                .is_some_and(|k| CLASS_NAME_KEYS.contains(&k.as_str()));
+            if !holds_class_name {
+                continue;
+            }
+            let Some((string, encoding)) = string_of(val.value()) else {
+                continue;
+            };
+            if let Some(new_name) = mapping.get(&string) {
+                val.set_value(ValueVariant::Data(encoding.encode(new_name)));
+            }
+        }
+        self.rename_classes(mapping)
+    }
+}
+
+/// Formats a class name mapping as lines of `Original = New`, sorted by original names.
+pub fn class_mapping_to_string(mapping: &HashMap<String, String>) -> String {
+    let mut entries: Vec<_> = mapping.iter().collect();
+    entries.sort();
+    let mut result = String::new();
+    for (original, new) in entries {
+        result.push_str(&format!("{original} = {new}\n"));
+    }
+    result
+}
+
+/// Parses a class name mapping formatted by [class_mapping_to_string()].
+///
+/// Empty lines and lines starting with `#` are skipped.
+pub fn parse_class_mapping(s: &str) -> Result<HashMap<String, String>, Error> {
+    let mut mapping = HashMap::new();
+    for (n, line) in s.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let Some((original, new)) = line.split_once('=') else {
+            return Err(Error::FormatError(format!(
+                "Missing `=` in class mapping line {}",
+                n + 1
+            )));
+        };
+        let (original, new) = (original.trim(), new.trim());
+        if original.is_empty() || new.is_empty() {
+            return Err(Error::FormatError(format!(
+                "Empty class name in class mapping line {}",
+                n + 1
+            )));
+        }
+        mapping.insert(original.to_string(), new.to_string());
+    }
+    Ok(mapping)
+}
+
+fn string_of(value: &ValueVariant) -> Option<(String, StringEncoding)> {
+    match value {
+        ValueVariant::Data(data) => decode_string(data),
+        _ => None,
+    }
+}
+
+fn is_custom_class(name: &str, system_prefixes: &[String]) -> bool {
+    !system_prefixes.iter().any(|p| name.starts_with(p.as_str()))
+}
+
+/// Replaces letters and digits of a string, keeping its layout. Every character is
+/// replaced with one of the same length in both UTF-8 and UTF-16.
+fn mask(s: &str) -> String {
+    s.chars()
+        .map(|c| match c {
+            'A'..='Z' => 'X',
+            'a'..='z' => 'x',
+            '0'..='9' => '0',
+            c if !c.is_alphanumeric() => c,
+            c => match c.len_utf8() {
+                2 => '\u{F8}',
+                3 => '\u{FF58}',
+                _ => '\u{1D431}',
+            },
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ClassName, Object, Value};
+
+    fn archive() -> NIBArchive {
+        NIBArchive::new(
+            vec![Object::new(0, 0, 3), Object::new(1, 3, 0)],
+            vec!["UIClassName".into(), "UIText".into()],
+            vec![
+                Value::new(0, ValueVariant::Data(b"SecretView".to_vec())),
+                Value::new(0, ValueVariant::Data(b"UIButton".to_vec())),
+                Value::new(1, ValueVariant::Data("Größe 42".as_bytes().to_vec())),
+            ],
+            vec![
+                ClassName::with_fallbacks("UIProxyObject".into(), Vec::new()),
+                ClassName::with_fallbacks("SecretView".into(), Vec::new()),
+            ],
+        )
+        .unwrap()
+    }
+
+    fn data(archive: &NIBArchive, index: usize) -> &[u8] {
+        match archive.values()[index].value() {
+            ValueVariant::Data(data) => data,
+            value => panic!("{value:?} isn't data"),
+        }
+    }
+
+    #[test]
+    fn class_name_values() {
+        let mut archive = archive();
+        let mut options = RedactOptions::new();
+        options.set_strings(false);
+        options.set_accessibility(false);
+        let summary = archive.redact(&options);
+        assert_eq!(
+            summary,
+            RedactSummary {
+                strings: 1,
+                class_names: 1
+            }
+        );
+        assert_eq!(archive.class_names()[1].name(), "Class1____");
+        assert_eq!(data(&archive, 0), b"Class1____");
+        assert_eq!(data(&archive, 1), b"UIButton");
+        assert_eq!(data(&archive, 2), "Größe 42".as_bytes());
+    }
+
+    #[test]
+    fn byte_length() {
+        let mut archive = archive();
+        let summary = archive.redact(&RedactOptions::new());
+        assert_eq!(summary.strings, 2);
+        assert_eq!(data(&archive, 0), b"Class1____");
+        assert_eq!(data(&archive, 2), "Xxøøx 00".as_bytes());
+        assert_eq!(data(&archive, 2).len(), "Größe 42".len());
+        assert_eq!(mask("日本 𝔸"), "\u{FF58}\u{FF58} \u{1D431}");
+    }
+}
@@ -0,0 +1,297 @@
+//! Self-contained HTML reports of archives.
+//!
+//! A report is meant for people who don't work with nibs directly: it shows the view
+//! hierarchy, classes, strings, connections, constraints and sizes of an archive
+//! on a single page without any external resources.
+
+use crate::{
+    consts::{self, classes, keys},
+    xib::escape,
+    NIBArchive, ViewNode,
+};
+use std::{collections::BTreeMap, fmt::Write};
+
+const STYLE: &str =
+    "body{font-family:-apple-system,Helvetica,Arial,sans-serif;margin:2em;color:#222}\
+table{border-collapse:collapse;margin-bottom:1em}\
+th,td{border:1px solid #ccc;padding:4px 8px;text-align:left;vertical-align:top}\
+th{background:#f4f4f4}td.num{text-align:right}\
+ul.tree{list-style:none;padding-left:1.2em}code{font-size:90%}";
+
+/// Classes of objects that connect outlets and actions, along with keys of their
+/// label, source and destination. Concrete classes are matched by the class registry.
+const CONNECTIONS: &[(&str, [&str; 3])] = &[
+    (
+        classes::UI_RUNTIME_CONNECTION,
+        [keys::UI_LABEL, keys::UI_SOURCE, keys::UI_DESTINATION],
+    ),
+    (
+        classes::NS_NIB_CONNECTOR,
+        [keys::NS_LABEL, keys::NS_SOURCE, keys::NS_DESTINATION],
+    ),
+];
+
+/// Renders an HTML report of a given archive. `title` is shown as the page's heading,
+/// e.g. the name of the file.
+pub fn nib_to_html(archive: &NIBArchive, title: &str) -> String {
+    let mut html = String::new();
+    let _ = write!(
+        html,
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{0}</title>\n\
+         <style>{STYLE}</style>\n</head>\n<body>\n<h1>{0}</h1>\n",
+        escape(title)
+    );
+    write_summary(archive, &mut html);
+    write_hierarchy(archive, &mut html);
+    write_classes(archive, &mut html);
+    write_strings(archive, &mut html);
+    write_connections(archive, &mut html);
+    write_constraints(archive, &mut html);
+    write_sizes(archive, &mut html);
+    html.push_str("</body>\n</html>\n");
+    html
+}
+
+fn write_summary(archive: &NIBArchive, html: &mut String) {
+    let _ = writeln!(
+        html,
+        "<p>{} objects, {} keys, {} values, {} class names. Format version {}, coder version {}.</p>",
+        archive.objects().len(),
+        archive.keys().len(),
+        archive.values().len(),
+        archive.class_names().len(),
+        archive.format_version(),
+        archive.coder_version()
+    );
+}
+
+fn write_hierarchy(archive: &NIBArchive, html: &mut String) {
+    html.push_str("<h2>View hierarchy</h2>\n");
+    let views = archive.view_hierarchy();
+    if views.is_empty() {
+        html.push_str("<p>No views.</p>\n");
+        return;
+    }
+    html.push_str("<ul class=\"tree\">\n");
+    for view in &views {
+        write_view(view, html);
+    }
+    html.push_str("</ul>\n");
+}
+
+fn write_view(view: &ViewNode, html: &mut String) {
+    let _ = write!(
+        html,
+        "<li><code>{}</code> #{}",
+        escape(&view.class_name),
+        view.index
+    );
+    if let Some(frame) = &view.frame {
+        let _ = write!(html, " {}", escape(&frame.to_string()));
+    }
+    if let Some(identifier) = &view.accessibility_identifier {
+        let _ = write!(html, " <em>{}</em>", escape(identifier));
+    }
+    if !view.subviews.is_empty() {
+        html.push_str("\n<ul class=\"tree\">\n");
+        for subview in &view.subviews {
+            write_view(subview, html);
+        }
+        html.push_str("</ul>\n");
+    }
+    html.push_str("</li>\n");
+}
+
+fn write_classes(archive: &NIBArchive, html: &mut String) {
+    html.push_str("<h2>Classes</h2>\n");
+    let mut counts: BTreeMap<&str, usize> = BTreeMap::new();
+    for obj in archive.objects() {
+        if let Some(class_name) = archive.class_name(obj.class_name_id()) {
+            *counts.entry(class_name.name()).or_default() += 1;
+        }
+    }
+    html.push_str("<table>\n<tr><th>Class</th><th>Objects</th></tr>\n");
+    for (name, count) in counts {
+        let _ = writeln!(
+            html,
+            "<tr><td><code>{}</code></td><td class=\"num\">{count}</td></tr>",
+            escape(name)
+        );
+    }
+    html.push_str("</table>\n");
+}
+
+fn write_strings(archive: &NIBArchive, html: &mut String) {
+    html.push_str("<h2>Strings</h2>\n");
+    let strings = archive.localizable_strings();
+    if strings.is_empty() {
+        html.push_str("<p>No strings.</p>\n");
+        return;
+    }
+    html.push_str("<table>\n<tr><th>Key path</th><th>Class</th><th>String</th></tr>\n");
+    for s in &strings {
+        let _ = writeln!(
+            html,
+            "<tr><td><code>{}</code></td><td><code>{}</code></td><td>{}</td></tr>",
+            escape(s.key_path()),
+            escape(s.class_name()),
+            escape(s.value())
+        );
+    }
+    html.push_str("</table>\n");
+}
+
+fn write_connections(archive: &NIBArchive, html: &mut String) {
+    html.push_str("<h2>Connections</h2>\n");
+    let mut rows = String::new();
+    for i in 0..archive.objects().len() {
+        let Some((_, [label_key, source_key, destination_key])) = CONNECTIONS
+            .iter()
+            .find(|(class_name, _)| is_kind_of(archive, i, class_name))
+        else {
+            continue;
+        };
+        let label = archive.string_value(i, label_key).unwrap_or_default();
+        let _ = writeln!(
+            rows,
+            "<tr><td><code>{}</code></td><td><code>{}</code></td><td>{}</td><td>{}</td></tr>",
+            escape(&object_class(archive, Some(i))),
+            escape(&label),
+            escape(&object_class(archive, archive.object_ref(i, source_key))),
+            escape(&object_class(
+                archive,
+                archive.object_ref(i, destination_key)
+            ))
+        );
+    }
+    if rows.is_empty() {
+        html.push_str("<p>No connections.</p>\n");
+        return;
+    }
+    html.push_str(
+        "<table>\n<tr><th>Kind</th><th>Label</th><th>Source</th><th>Destination</th></tr>\n",
+    );
+    html.push_str(&rows);
+    html.push_str("</table>\n");
+}
+
+fn write_constraints(archive: &NIBArchive, html: &mut String) {
+    html.push_str("<h2>Constraints</h2>\n");
+    let constraints = archive.layout_constraints();
+    if constraints.is_empty() {
+        html.push_str("<p>No constraints.</p>\n");
+        return;
+    }
+    html.push_str("<table>\n<tr><th>Object</th><th>Constraint</th><th>Identifier</th></tr>\n");
+    for constraint in &constraints {
+        let _ = writeln!(
+            html,
+            "<tr><td>#{}</td><td><code>{}</code></td><td>{}</td></tr>",
+            constraint.index,
+            escape(&constraint.to_string()),
+            escape(constraint.identifier.as_deref().unwrap_or_default())
+        );
+    }
+    html.push_str("</table>\n");
+}
+
+fn write_sizes(archive: &NIBArchive, html: &mut String) {
+    html.push_str("<h2>Size</h2>\n");
+    let report = archive.size_report();
+    html.push_str("<table>\n<tr><th>Section</th><th>Bytes</th></tr>\n");
+    for (section, size) in [
+        ("Header", report.header),
+        ("Objects", report.objects),
+        ("Keys", report.keys),
+        ("Values", report.values),
+        ("Class names", report.class_names),
+        ("Trailing bytes", report.trailing),
+        ("Total", report.total),
+    ] {
+        let _ = writeln!(
+            html,
+            "<tr><td>{section}</td><td class=\"num\">{size}</td></tr>"
+        );
+    }
+    html.push_str("</table>\n");
+
+    html.push_str("<table>\n<tr><th>Class</th><th>Objects</th><th>Bytes</th></tr>\n");
+    for class in &report.classes {
+        let _ = writeln!(
+            html,
+            "<tr><td><code>{}</code></td><td class=\"num\">{}</td><td class=\"num\">{}</td></tr>",
+            escape(&class.name),
+            class.objects,
+            class.size
+        );
+    }
+    html.push_str("</table>\n");
+}
+
+/// Returns a class name of an object followed by its index, e.g. `UIButton #3`.
+/// Returns `true` if an object at `index` is an instance of `name`, falls back to it
+/// or is an instance of a registered subclass of it.
+fn is_kind_of(archive: &NIBArchive, index: usize, name: &str) -> bool {
+    archive.is_kind_of(index, name)
+        || archive
+            .objects()
+            .get(index)
+            .and_then(|obj| archive.class_name(obj.class_name_id()))
+            .and_then(|c| consts::class_info(c.name()))
+            .is_some_and(|info| info.is_kind_of(name))
+}
+
+fn object_class(archive: &NIBArchive, index: Option<usize>) -> String {
+    let Some(index) = index else {
+        return String::new();
+    };
+    let name = archive
+        .objects()
+        .get(index)
+        .and_then(|obj| archive.class_name(obj.class_name_id()))
+        .map_or("", |c| c.name());
+    format!("{name} #{index}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::nib_to_html;
+    use crate::{ClassName, NIBArchive, Object, Value, ValueVariant};
+
+    #[test]
+    fn connections() {
+        let class = |name: &str| ClassName::with_fallbacks(name.into(), Vec::new());
+        let archive = NIBArchive::new(
+            vec![
+                Object::new(0, 0, 1),
+                Object::new(1, 1, 3),
+                Object::new(2, 4, 0),
+            ],
+            vec![
+                "UINibConnectionsKey".into(),
+                "UILabel".into(),
+                "UISource".into(),
+                "UIDestination".into(),
+            ],
+            vec![
+                Value::new(0, ValueVariant::ObjectRef(1)),
+                Value::new(1, ValueVariant::Data(b"titleLabel".to_vec())),
+                Value::new(2, ValueVariant::ObjectRef(0)),
+                Value::new(3, ValueVariant::ObjectRef(2)),
+            ],
+            vec![
+                class("NSObject"),
+                class("UIRuntimeOutletConnection"),
+                class("UILabel"),
+            ],
+        )
+        .unwrap();
+        let html = nib_to_html(&archive, "<Main>");
+        assert!(html.contains("<h1>&lt;Main&gt;</h1>"));
+        assert!(html.contains(
+            "<tr><td><code>UIRuntimeOutletConnection #1</code></td><td><code>titleLabel</code></td>\
+             <td>NSObject #0</td><td>UILabel #2</td></tr>"
+        ));
+        assert!(!html.contains("No connections."));
+    }
+}
@@ -0,0 +1,113 @@
+use crate::{
+    consts::{classes, keys},
+    NIBArchive, ValueVariant,
+};
+
+/// Classes that hold a single attribute along with keys of the object, the key path
+/// and the value.
+const KEY_VALUE_PAIRS: &[(&str, [&str; 3])] = &[
+    (
+        classes::UI_NIB_KEY_VALUE_PAIR,
+        [keys::UI_OBJECT, keys::UI_KEY_PATH, keys::UI_VALUE],
+    ),
+    (
+        classes::UI_ACCESSIBILITY_CONFIGURATION,
+        [
+            keys::UI_ACCESSIBILITY_CONFIGURATION_OBJECT,
+            keys::UI_ACCESSIBILITY_CONFIGURATION_KEY_PATH,
+            keys::UI_ACCESSIBILITY_CONFIGURATION_VALUE,
+        ],
+    ),
+];
+
+/// A user-defined runtime attribute, i.e. a value Interface Builder sets on an object
+/// with key-value coding when the nib is loaded.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RuntimeAttribute {
+    /// Index of the object the attribute is set on.
+    pub object: usize,
+    pub key_path: String,
+    /// The value itself. Values that aren't stored inline (colors, images, etc.)
+    /// are references to objects.
+    pub value: ValueVariant,
+}
+
+impl NIBArchive {
+    /// Returns user-defined runtime attributes of all objects.
+    ///
+    /// Both UIKit (`UINibKeyValuePair`, `UIAccessibilityConfiguration`) and AppKit
+    /// (`NSIBUserDefinedRuntimeAttributesConnector`) encodings are recognized.
+    /// Attributes are sorted by object index.
+    pub fn runtime_attributes(&self) -> Vec<RuntimeAttribute> {
+        let mut result = Vec::new();
+        for i in 0..self.objects.len() {
+            let pair = KEY_VALUE_PAIRS
+                .iter()
+                .find(|(class_name, _)| self.is_kind_of(i, class_name));
+            if let Some((_, [object_key, key_path_key, value_key])) = pair {
+                let (Some(object), Some(key_path), Some(value)) = (
+                    self.object_ref(i, object_key),
+                    self.string_value(i, key_path_key),
+                    self.object_value(i, value_key),
+                ) else {
+                    continue;
+                };
+                result.push(RuntimeAttribute {
+                    object,
+                    key_path,
+                    value: value.value().clone(),
+                });
+            } else if self.is_kind_of(i, classes::NS_IB_USER_DEFINED_RUNTIME_ATTRIBUTES_CONNECTOR) {
+                let Some(object) = self.object_ref(i, keys::NS_OBJECT) else {
+                    continue;
+                };
+                let key_paths = self.array_values(i, keys::NS_KEY_PATHS);
+                let values = self.array_values(i, keys::NS_VALUES);
+                for (key_path, value) in key_paths.into_iter().zip(values) {
+                    if let Some(key_path) = self.resolve_string(key_path) {
+                        result.push(RuntimeAttribute {
+                            object,
+                            key_path,
+                            value: value.clone(),
+                        });
+                    }
+                }
+            }
+        }
+        result.sort_by_key(|attr| attr.object);
+        result
+    }
+
+    pub(crate) fn object_ref(&self, index: usize, key: &str) -> Option<usize> {
+        match self.object_value(index, key)?.value() {
+            ValueVariant::ObjectRef(r) if (*r as usize) < self.objects.len() => Some(*r as usize),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn string_value(&self, index: usize, key: &str) -> Option<String> {
+        self.resolve_string(self.object_value(index, key)?.value())
+    }
+
+    /// Returns a string stored either inline or in an `NSString` object.
+    pub(crate) fn resolve_string(&self, value: &ValueVariant) -> Option<String> {
+        match value {
+            ValueVariant::ObjectRef(r) => self
+                .object_value(*r as usize, keys::NS_BYTES)?
+                .value()
+                .data_as_string(),
+            value => value.data_as_string(),
+        }
+    }
+
+    /// Returns elements of an array referenced by a value with a given key.
+    pub(crate) fn array_values(&self, index: usize, key: &str) -> Vec<&ValueVariant> {
+        let Some(array) = self.object_ref(index, key) else {
+            return Vec::new();
+        };
+        self.object_values(&self.objects[array])
+            .iter()
+            .map(|v| v.value())
+            .collect()
+    }
+}
@@ -0,0 +1,39 @@
+use crate::{
+    consts::{classes, keys},
+    NIBArchive,
+};
+
+/// A storyboard segue decoded from a `UIStoryboardSegueTemplate` object.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Segue {
+    /// Index of the segue template object.
+    pub index: usize,
+    /// Class name of the template, e.g. `UIStoryboardShowSegueTemplate`.
+    pub template_class: String,
+    pub identifier: Option<String>,
+    /// Storyboard identifier of the destination view controller.
+    pub destination: Option<String>,
+    /// Class name of a custom `UIStoryboardSegue` subclass.
+    pub segue_class: Option<String>,
+}
+
+impl NIBArchive {
+    /// Returns segues of a storyboard nib, i.e. objects of `UIStoryboardSegueTemplate`
+    /// class and its subclasses.
+    pub fn segues(&self) -> Vec<Segue> {
+        (0..self.objects.len())
+            .filter(|i| self.is_kind_of(*i, classes::UI_STORYBOARD_SEGUE_TEMPLATE))
+            .map(|index| Segue {
+                index,
+                template_class: self
+                    .class_names
+                    .get(self.objects[index].class_name_id().as_usize())
+                    .map_or_else(String::new, |c| c.name().to_string()),
+                identifier: self.string_value(index, keys::UI_IDENTIFIER),
+                destination: self
+                    .string_value(index, keys::UI_DESTINATION_VIEW_CONTROLLER_IDENTIFIER),
+                segue_class: self.string_value(index, keys::UI_SEGUE_CLASS_NAME),
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,53 @@
+use crate::{DecodeOptions, DecodeWarning, Error, NIBArchive};
+use std::io::Read;
+
+impl NIBArchive {
+    /// Reads and decodes a NIB Archive from a reader that can't seek, e.g. a pipe,
+    /// a network stream or a decompressor.
+    ///
+    /// The whole stream is read into memory before decoding, but no more than
+    /// [the total size limit](crate::Limits::max_total_size()) allows.
+    pub fn from_stream<T: Read>(reader: T) -> Result<Self, Error> {
+        let (archive, _) = Self::from_stream_with_options(reader, &DecodeOptions::default())?;
+        Ok(archive)
+    }
+
+    /// Same as [NIBArchive::from_stream()], but uses given options.
+    pub fn from_stream_with_options<T: Read>(
+        mut reader: T,
+        options: &DecodeOptions,
+    ) -> Result<(Self, Vec<DecodeWarning>), Error> {
+        let bytes = Self::read_limited(&mut reader, options.limits().max_total_size())?;
+        Self::from_slice_with_options(&bytes, options)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ClassName, DecodeOptions, Error, LimitKind, Limits, NIBArchive, Object};
+
+    #[test]
+    fn from_stream() {
+        let archive = NIBArchive::new(
+            vec![Object::new(0, 0, 0)],
+            Vec::new(),
+            Vec::new(),
+            vec![ClassName::with_fallbacks("NSObject".into(), Vec::new())],
+        )
+        .unwrap();
+        let bytes = archive.to_bytes();
+        assert_eq!(NIBArchive::from_stream(bytes.as_slice()).unwrap(), archive);
+
+        let mut limits = Limits::default();
+        limits.set_max_total_size(bytes.len() as u64 - 1);
+        let mut options = DecodeOptions::default();
+        options.set_limits(limits);
+        assert!(matches!(
+            NIBArchive::from_stream_with_options(bytes.as_slice(), &options),
+            Err(Error::LimitExceeded {
+                limit: LimitKind::TotalSize,
+                ..
+            })
+        ));
+    }
+}
@@ -0,0 +1,111 @@
+use crate::{NIBArchive, Value, ValueVariant};
+use std::collections::HashMap;
+
+/// Sizes of sections and elements of an encoded archive, returned by
+/// [NIBArchive::size_report()].
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct SizeReport {
+    /// Size of the magic bytes and the header.
+    pub header: usize,
+    pub objects: usize,
+    pub keys: usize,
+    pub values: usize,
+    pub class_names: usize,
+    /// Size of preserved bytes following the last section.
+    pub trailing: usize,
+    /// The whole size of the encoded archive.
+    pub total: usize,
+    /// Data values sorted by size, the largest first.
+    pub data_values: Vec<DataValueSize>,
+    /// Sizes attributed to classes, sorted by size, the largest first.
+    pub classes: Vec<ClassSize>,
+}
+
+/// Size of a single [data value](ValueVariant::Data).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DataValueSize {
+    pub value_index: usize,
+    /// Index of the object owning the value, if any.
+    pub object_index: Option<usize>,
+    pub key: String,
+    /// Length of the data itself.
+    pub size: usize,
+}
+
+/// Bytes taken by objects of a single class.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClassSize {
+    pub name: String,
+    /// Count of objects of the class.
+    pub objects: usize,
+    /// Encoded size of the objects and their values.
+    pub size: usize,
+}
+
+impl NIBArchive {
+    /// Returns sizes of sections the archive takes when encoded, along with sizes of data
+    /// values and of objects grouped by class, which helps finding out why an archive is large.
+    pub fn size_report(&self) -> SizeReport {
+        let plan = self.encoding_plan();
+        let header = &plan.header;
+        let total = self.planned_len(&plan);
+        let trailing = self.preserved.as_ref().map_or(0, |p| p.trailing.len());
+        let mut report = SizeReport {
+            header: header.offset_objects as usize,
+            objects: (header.offset_keys - header.offset_objects) as usize,
+            keys: (header.offset_values - header.offset_keys) as usize,
+            values: (header.offset_class_names - header.offset_values) as usize,
+            class_names: total - trailing - header.offset_class_names as usize,
+            trailing,
+            total,
+            ..Default::default()
+        };
+
+        let mut owners = vec![None; self.values.len()];
+        let mut classes: HashMap<usize, ClassSize> = HashMap::new();
+        for (i, obj) in self.objects.iter().enumerate() {
+            let values = self.object_values(obj);
+            if let Some(range) = obj.value_range().filter(|_| !values.is_empty()) {
+                for owner in &mut owners[range] {
+                    *owner = Some(i);
+                }
+            }
+            let class_index = obj.class_name_id().as_usize();
+            let class_size = classes.entry(class_index).or_insert_with(|| ClassSize {
+                name: self
+                    .class_names
+                    .get(class_index)
+                    .map_or_else(String::new, |c| c.name().to_string()),
+                objects: 0,
+                size: 0,
+            });
+            class_size.objects += 1;
+            class_size.size +=
+                obj.encoded_len() + values.iter().map(Value::encoded_len).sum::<usize>();
+        }
+
+        for (i, val) in self.values.iter().enumerate() {
+            if let ValueVariant::Data(data) = val.value() {
+                report.data_values.push(DataValueSize {
+                    value_index: i,
+                    object_index: owners[i],
+                    key: self
+                        .keys
+                        .get(val.key_id().as_usize())
+                        .cloned()
+                        .unwrap_or_default(),
+                    size: data.len(),
+                });
+            }
+        }
+        report
+            .data_values
+            .sort_by(|a, b| b.size.cmp(&a.size).then(a.value_index.cmp(&b.value_index)));
+
+        report.classes = classes.into_values().collect();
+        report
+            .classes
+            .sort_by(|a, b| b.size.cmp(&a.size).then_with(|| a.name.cmp(&b.name)));
+        report
+    }
+}
@@ -0,0 +1,173 @@
+use crate::{
+    header::Header,
+    io::{Cursor, Read},
+    Error, MAGIC_BYTES,
+};
+
+/// A kind of file recognized by [sniff()].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FileKind {
+    /// A NIB Archive with a sane header.
+    NibArchive,
+    /// A legacy nib serialized by `NSArchiver` (a typed stream).
+    TypedStream,
+    /// A binary or an XML property list, e.g. a nib serialized by `NSKeyedArchiver`.
+    KeyedArchive,
+    /// An Interface Builder XIB or storyboard document, i.e. the source of a nib.
+    Xib,
+    /// Anything else, including NIB Archives with a broken header.
+    Unknown,
+}
+
+impl core::fmt::Display for FileKind {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            FileKind::NibArchive => write!(f, "NIB Archive"),
+            FileKind::TypedStream => write!(f, "NSArchiver typed stream"),
+            FileKind::KeyedArchive => write!(f, "NSKeyedArchiver property list"),
+            FileKind::Xib => write!(f, "Interface Builder XIB document"),
+            FileKind::Unknown => write!(f, "unknown"),
+        }
+    }
+}
+
+/// Count of bytes [sniff()] needs at most.
+const SNIFF_LENGTH: usize = 256;
+
+/// Quickly determines the kind of a file by its first bytes.
+///
+/// A NIB Archive is recognized if it has the magic bytes and a header whose
+/// section offsets go in order and don't exceed the length of `bytes`, so `bytes` should
+/// contain the whole file. Use [sniff_reader()] if only the beginning is available.
+pub fn sniff<B: AsRef<[u8]>>(bytes: B) -> FileKind {
+    let bytes = bytes.as_ref();
+    sniff_with_length(bytes, bytes.len() as u64)
+}
+
+/// Same as [sniff()], but reads the first bytes from a given reader.
+///
+/// Since the length of the file is unknown, the section offsets aren't checked against it.
+pub fn sniff_reader<T: Read>(reader: &mut T) -> Result<FileKind, Error> {
+    sniff_continued(&[], reader)
+}
+
+/// Same as [sniff_reader()], but the first bytes of the file, which are already read,
+/// are given in `start`.
+pub(crate) fn sniff_continued<T: Read>(start: &[u8], reader: &mut T) -> Result<FileKind, Error> {
+    let mut buf = [0; SNIFF_LENGTH];
+    let mut length = start.len().min(SNIFF_LENGTH);
+    buf[..length].copy_from_slice(&start[..length]);
+    while length < SNIFF_LENGTH {
+        match reader.read(&mut buf[length..])? {
+            0 => break,
+            n => length += n,
+        }
+    }
+    Ok(sniff_with_length(&buf[..length], u64::MAX))
+}
+
+fn sniff_with_length(bytes: &[u8], length: u64) -> FileKind {
+    if bytes.starts_with(MAGIC_BYTES) {
+        let mut cursor = Cursor::new(&bytes[MAGIC_BYTES.len()..]);
+        return match Header::try_from_reader(&mut cursor) {
+            Ok(header) if header.is_sane(length) => FileKind::NibArchive,
+            _ => FileKind::Unknown,
+        };
+    }
+    if bytes.starts_with(b"\x04\x0bstreamtyped") || bytes.starts_with(b"\x04\x0btypedstream") {
+        return FileKind::TypedStream;
+    }
+    if bytes.starts_with(b"bplist00") {
+        return FileKind::KeyedArchive;
+    }
+    let root = xml_root(bytes);
+    if root.starts_with(b"<plist") || root.starts_with(b"<!DOCTYPE plist") {
+        FileKind::KeyedArchive
+    } else if root.starts_with(b"<document") && is_xib_document(root) {
+        FileKind::Xib
+    } else {
+        FileKind::Unknown
+    }
+}
+
+/// Skips the XML declaration, processing instructions, comments and whitespace
+/// that may precede the root element or the document type declaration.
+fn xml_root(mut bytes: &[u8]) -> &[u8] {
+    loop {
+        bytes = bytes.trim_ascii_start();
+        let end = if bytes.starts_with(b"<?") {
+            b"?>".as_slice()
+        } else if bytes.starts_with(b"<!--") {
+            b"-->".as_slice()
+        } else {
+            return bytes;
+        };
+        match bytes.windows(end.len()).position(|w| w == end) {
+            Some(i) => bytes = &bytes[i + end.len()..],
+            None => return &[],
+        }
+    }
+}
+
+/// Returns `true` if a `<document>` tag has an Interface Builder document type.
+fn is_xib_document(tag: &[u8]) -> bool {
+    const TYPE: &[u8] = b"type=\"com.apple.InterfaceBuilder3.";
+    let tag = match tag.iter().position(|b| *b == b'>') {
+        Some(end) => &tag[..end],
+        None => tag,
+    };
+    tag.windows(TYPE.len()).any(|w| w == TYPE)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::format;
+
+    const XML_DECLARATION: &str = r#"<?xml version="1.0" encoding="UTF-8"?>"#;
+
+    #[test]
+    fn property_lists() {
+        assert_eq!(sniff(b"bplist00\xd4\x01\x02"), FileKind::KeyedArchive);
+        assert_eq!(sniff(b"bplist15"), FileKind::Unknown);
+        assert_eq!(sniff(b"<plist version=\"1.0\">"), FileKind::KeyedArchive);
+        let doctype = format!(
+            "{XML_DECLARATION}\n<!DOCTYPE plist PUBLIC \"-//Apple//DTD PLIST 1.0//EN\" \
+             \"http://www.apple.com/DTDs/PropertyList-1.0.dtd\">\n<plist version=\"1.0\">"
+        );
+        assert_eq!(sniff(&doctype), FileKind::KeyedArchive);
+        let comment = format!("{XML_DECLARATION}\n<!-- <document> -->\n<plist>");
+        assert_eq!(sniff(comment), FileKind::KeyedArchive);
+    }
+
+    #[test]
+    fn xibs() {
+        let xib = format!(
+            "{XML_DECLARATION}\n<document type=\"com.apple.InterfaceBuilder3.CocoaTouch.XIB\" \
+             version=\"3.0\" toolsVersion=\"21701\">"
+        );
+        assert_eq!(sniff(&xib), FileKind::Xib);
+        assert_eq!(sniff_reader(&mut xib.as_bytes()).unwrap(), FileKind::Xib);
+        let storyboard = format!(
+            "{XML_DECLARATION}\n<document type=\"com.apple.InterfaceBuilder3.Cocoa.Storyboard.XIB\">"
+        );
+        assert_eq!(sniff(storyboard), FileKind::Xib);
+    }
+
+    #[test]
+    fn other_xml() {
+        assert_eq!(
+            sniff(format!("{XML_DECLARATION}\n<svg>")),
+            FileKind::Unknown
+        );
+        assert_eq!(
+            sniff(format!("{XML_DECLARATION}\n<document>")),
+            FileKind::Unknown
+        );
+        assert_eq!(
+            sniff(format!("{XML_DECLARATION}\n<!-- <plist>")),
+            FileKind::Unknown
+        );
+        assert_eq!(sniff(XML_DECLARATION), FileKind::Unknown);
+    }
+}
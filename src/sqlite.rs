@@ -0,0 +1,239 @@
+//! Export of archives into SQLite databases for querying many archives at once.
+//!
+//! Every element of an archive becomes a row of a table:
+//! - `archives(id, name)`;
+//! - `class_names(archive_id, idx, name)` and
+//!   `fallback_classes(archive_id, class_idx, position, fallback_idx)`;
+//! - `keys(archive_id, idx, name)`;
+//! - `objects(archive_id, idx, class_idx, class_name, values_index, value_count)`;
+//! - `"values"(archive_id, idx, object_idx, key_idx, key, type, int_value, real_value,
+//!   text_value, data, ref_object)`.
+//!
+//! A value is stored in one of the typed columns: integers and booleans in `int_value`,
+//! floating point numbers in `real_value`, data in `data` (and in `text_value` if it's
+//! a string) and object references in `ref_object`. The `refs(archive_id, from_object,
+//! key, to_object)` view lists references between objects.
+//!
+//! For example, all views with alpha less than 1 are found with
+//! `SELECT o.* FROM objects o JOIN "values" v ON v.archive_id = o.archive_id
+//! AND v.object_idx = o.idx WHERE v.key = 'UIAlpha' AND v.real_value < 1`.
+
+use crate::{Error, NIBArchive, ValueVariant};
+use rusqlite::{params, Connection, Transaction};
+use std::path::Path;
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS archives (id INTEGER PRIMARY KEY, name TEXT NOT NULL);
+CREATE TABLE IF NOT EXISTS class_names (
+    archive_id INTEGER NOT NULL REFERENCES archives(id),
+    idx INTEGER NOT NULL,
+    name TEXT NOT NULL,
+    PRIMARY KEY (archive_id, idx)
+);
+CREATE TABLE IF NOT EXISTS fallback_classes (
+    archive_id INTEGER NOT NULL REFERENCES archives(id),
+    class_idx INTEGER NOT NULL,
+    position INTEGER NOT NULL,
+    fallback_idx INTEGER NOT NULL,
+    PRIMARY KEY (archive_id, class_idx, position)
+);
+CREATE TABLE IF NOT EXISTS keys (
+    archive_id INTEGER NOT NULL REFERENCES archives(id),
+    idx INTEGER NOT NULL,
+    name TEXT NOT NULL,
+    PRIMARY KEY (archive_id, idx)
+);
+CREATE TABLE IF NOT EXISTS objects (
+    archive_id INTEGER NOT NULL REFERENCES archives(id),
+    idx INTEGER NOT NULL,
+    class_idx INTEGER NOT NULL,
+    class_name TEXT,
+    values_index INTEGER NOT NULL,
+    value_count INTEGER NOT NULL,
+    PRIMARY KEY (archive_id, idx)
+);
+CREATE TABLE IF NOT EXISTS \"values\" (
+    archive_id INTEGER NOT NULL REFERENCES archives(id),
+    idx INTEGER NOT NULL,
+    object_idx INTEGER,
+    key_idx INTEGER NOT NULL,
+    key TEXT,
+    type TEXT NOT NULL,
+    int_value INTEGER,
+    real_value REAL,
+    text_value TEXT,
+    data BLOB,
+    ref_object INTEGER,
+    PRIMARY KEY (archive_id, idx)
+);
+CREATE INDEX IF NOT EXISTS values_by_key ON \"values\" (key);
+CREATE INDEX IF NOT EXISTS objects_by_class ON objects (class_name);
+CREATE VIEW IF NOT EXISTS refs AS
+    SELECT archive_id, object_idx AS from_object, key, ref_object AS to_object
+    FROM \"values\" WHERE ref_object IS NOT NULL;
+";
+
+/// Writes archives into an SQLite database at `path`, creating it if it doesn't exist.
+///
+/// `archives` are pairs of names, e.g. paths of the files, and archives. Archives are
+/// appended to the ones already stored in the database, so results of
+/// [decode_dir()](crate::batch::decode_dir()) can be exported in chunks.
+pub fn export_sqlite<'a, P, I, S>(archives: I, path: P) -> Result<(), Error>
+where
+    P: AsRef<Path>,
+    I: IntoIterator<Item = (S, &'a NIBArchive)>,
+    S: AsRef<str>,
+{
+    let mut connection = Connection::open(path).map_err(sqlite_error)?;
+    connection.execute_batch(SCHEMA).map_err(sqlite_error)?;
+    let transaction = connection.transaction().map_err(sqlite_error)?;
+    for (name, archive) in archives {
+        insert_archive(&transaction, name.as_ref(), archive).map_err(sqlite_error)?;
+    }
+    transaction.commit().map_err(sqlite_error)
+}
+
+fn insert_archive(
+    transaction: &Transaction,
+    name: &str,
+    archive: &NIBArchive,
+) -> rusqlite::Result<()> {
+    transaction.execute("INSERT INTO archives (name) VALUES (?1)", params![name])?;
+    let id = transaction.last_insert_rowid();
+
+    let mut insert_class = transaction
+        .prepare("INSERT INTO class_names (archive_id, idx, name) VALUES (?1, ?2, ?3)")?;
+    let mut insert_fallback = transaction.prepare(
+        "INSERT INTO fallback_classes (archive_id, class_idx, position, fallback_idx) \
+         VALUES (?1, ?2, ?3, ?4)",
+    )?;
+    for (i, class_name) in archive.class_names().iter().enumerate() {
+        insert_class.execute(params![id, i, class_name.name()])?;
+        for (position, fallback) in class_name.fallback_class_ids().iter().enumerate() {
+            insert_fallback.execute(params![id, i, position, fallback.0])?;
+        }
+    }
+
+    let mut insert_key =
+        transaction.prepare("INSERT INTO keys (archive_id, idx, name) VALUES (?1, ?2, ?3)")?;
+    for (i, key) in archive.keys().iter().enumerate() {
+        insert_key.execute(params![id, i, key])?;
+    }
+
+    let mut owners = vec![None; archive.values().len()];
+    let mut insert_object = transaction.prepare(
+        "INSERT INTO objects (archive_id, idx, class_idx, class_name, values_index, value_count) \
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+    )?;
+    for (i, obj) in archive.objects().iter().enumerate() {
+        let class_name = archive.class_name(obj.class_name_id()).map(|c| c.name());
+        insert_object.execute(params![
+            id,
+            i,
+            obj.class_name_id().0,
+            class_name,
+            obj.first_value_id().0,
+            obj.value_count()
+        ])?;
+        let start = obj.first_value_id().as_usize();
+        let count = obj.value_count().max(0) as usize;
+        for owner in owners.iter_mut().skip(start).take(count) {
+            *owner = Some(i);
+        }
+    }
+
+    let mut insert_value = transaction.prepare(
+        "INSERT INTO \"values\" (archive_id, idx, object_idx, key_idx, key, type, \
+         int_value, real_value, text_value, data, ref_object) \
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
+    )?;
+    for (i, val) in archive.values().iter().enumerate() {
+        let value = val.value();
+        let int_value = match value {
+            ValueVariant::Bool(v) => Some(*v as i64),
+            _ => value.as_i64(),
+        };
+        let real_value = match value {
+            ValueVariant::Float(_) | ValueVariant::Double(_) => value.as_f64(),
+            _ => None,
+        };
+        let data = match value {
+            ValueVariant::Data(data) | ValueVariant::Unknown { raw: data, .. } => {
+                Some(data.as_slice())
+            }
+            _ => None,
+        };
+        insert_value.execute(params![
+            id,
+            i,
+            owners[i],
+            val.key_id().0,
+            archive.key(val.key_id()),
+            value.type_name(),
+            int_value,
+            real_value,
+            value.data_as_string(),
+            data,
+            value.as_object_ref().map(|r| r.0)
+        ])?;
+    }
+    Ok(())
+}
+
+fn sqlite_error(e: rusqlite::Error) -> Error {
+    Error::IOError(std::io::Error::other(e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ClassName, Object, Value};
+
+    #[test]
+    fn query() {
+        let archive = NIBArchive::new(
+            vec![Object::new(0, 0, 2), Object::new(0, 2, 1)],
+            vec!["UISubviews".into(), "UIAlpha".into()],
+            vec![
+                Value::new(0, ValueVariant::ObjectRef(1)),
+                Value::new(1, ValueVariant::Float(1.0)),
+                Value::new(1, ValueVariant::Float(0.5)),
+            ],
+            vec![ClassName::with_fallbacks("UIView".into(), Vec::new())],
+        )
+        .unwrap();
+        let path = std::env::temp_dir().join(format!("nibarchive-{}.sqlite", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        export_sqlite([("a.nib", &archive)], &path).unwrap();
+        export_sqlite([("b.nib", &archive)], &path).unwrap();
+
+        let connection = Connection::open(&path).unwrap();
+        let mut statement = connection
+            .prepare(
+                "SELECT a.name, o.idx FROM objects o \
+                 JOIN archives a ON a.id = o.archive_id \
+                 JOIN \"values\" v ON v.archive_id = o.archive_id AND v.object_idx = o.idx \
+                 WHERE v.key = 'UIAlpha' AND v.real_value < 1 ORDER BY a.name",
+            )
+            .unwrap();
+        let rows: Vec<(String, i64)> = statement
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(rows, [("a.nib".into(), 1), ("b.nib".into(), 1)]);
+
+        let refs: (i64, String, i64) = connection
+            .query_row(
+                "SELECT from_object, key, to_object FROM refs LIMIT 1",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+            )
+            .unwrap();
+        assert_eq!(refs, (0, "UISubviews".into(), 1));
+
+        drop(statement);
+        drop(connection);
+        std::fs::remove_file(&path).unwrap();
+    }
+}
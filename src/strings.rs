@@ -0,0 +1,382 @@
+use crate::{
+    consts::keys::{self, LOCALIZABLE},
+    NIBArchive, ValueVariant,
+};
+use std::collections::{HashMap, VecDeque};
+
+/// Represents a string value found in a NIB Archive.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct LocalizableString {
+    key_path: String,
+    object_index: usize,
+    value_index: usize,
+    class_name: String,
+    value: String,
+}
+
+impl LocalizableString {
+    /// Returns a stable key path of the string.
+    ///
+    /// A key path starts with the class name of the root object followed by keys that
+    /// lead to the object containing the string and the string's key itself, e.g.
+    /// `NSObject.UINibObjectsKey[3].UIText`. An index in square brackets is only present if
+    /// an object has multiple values with the same key.
+    pub fn key_path(&self) -> &str {
+        &self.key_path
+    }
+
+    /// Returns the index of an object containing the string.
+    pub fn object_index(&self) -> usize {
+        self.object_index
+    }
+
+    /// Returns the index of a value containing the string.
+    pub fn value_index(&self) -> usize {
+        self.value_index
+    }
+
+    /// Returns the class name of an object containing the string.
+    pub fn class_name(&self) -> &str {
+        &self.class_name
+    }
+
+    /// Returns the string itself.
+    pub fn value(&self) -> &str {
+        &self.value
+    }
+}
+
+impl NIBArchive {
+    /// Returns user-visible strings of objects reachable from the root object.
+    ///
+    /// A string is either stored in a [data value](ValueVariant::Data) with one of
+    /// the [localizable keys](keys::LOCALIZABLE), e.g. `UIText` or `NSTitle`, or in
+    /// the `NS.bytes` value of an `NSString` referenced by such a key. Other strings,
+    /// like outlet and action names of connections, class names or identifiers,
+    /// aren't localizable, since changing them breaks loading of the nib.
+    ///
+    /// Look at [ValueVariant::data_as_string()] to find out which data is considered a string.
+    pub fn localizable_strings(&self) -> Vec<LocalizableString> {
+        let paths = self.object_key_paths();
+        let mut strings = Vec::new();
+        for (object_index, path) in paths.iter().enumerate() {
+            let Some((path, referencing_key)) = path else {
+                continue;
+            };
+            let obj = &self.objects[object_index];
+            let class_name = self
+                .class_names
+                .get(obj.class_name_id().as_usize())
+                .map(|c| c.name().to_string())
+                .unwrap_or_default();
+            for (value_index, component) in self.value_key_path_components(object_index) {
+                let val = &self.values[value_index];
+                let key = self.keys.get(val.key_id().as_usize()).map(String::as_str);
+                let text_key = match key {
+                    Some(keys::NS_BYTES) => referencing_key.as_deref(),
+                    key => key,
+                };
+                if !text_key.is_some_and(|key| LOCALIZABLE.contains(&key)) {
+                    continue;
+                }
+                let ValueVariant::Data(data) = val.value() else {
+                    continue;
+                };
+                let Some((value, _)) = decode_string(data) else {
+                    continue;
+                };
+                strings.push(LocalizableString {
+                    key_path: format!("{path}.{component}"),
+                    object_index,
+                    value_index,
+                    class_name: class_name.clone(),
+                    value,
+                });
+            }
+        }
+        strings
+    }
+
+    /// Replaces strings addressed by [key paths](LocalizableString::key_path) with
+    /// new values from `strings` and returns the count of replaced strings.
+    ///
+    /// A string keeps its encoding, so UTF-16 strings remain UTF-16 with the same byte order.
+    /// Key paths that don't address any string are ignored.
+    pub fn apply_strings(&mut self, strings: &HashMap<String, String>) -> usize {
+        let mut count = 0;
+        for s in self.localizable_strings() {
+            let Some(new_value) = strings.get(s.key_path()) else {
+                continue;
+            };
+            let ValueVariant::Data(data) = self.values[s.value_index].value() else {
+                continue;
+            };
+            let Some((_, encoding)) = decode_string(data) else {
+                continue;
+            };
+            self.values[s.value_index].set_value(ValueVariant::Data(encoding.encode(new_value)));
+            count += 1;
+        }
+        count
+    }
+
+    /// Returns key paths of all objects reachable from the root object along with keys
+    /// the objects are referenced by.
+    ///
+    /// Objects are visited in a breadth-first order, so every object gets the shortest path.
+    fn object_key_paths(&self) -> Vec<Option<(String, Option<String>)>> {
+        let mut paths = vec![None; self.objects.len()];
+        let Some(root) = self.objects.first() else {
+            return paths;
+        };
+        let root_path = self
+            .class_names
+            .get(root.class_name_id().as_usize())
+            .map(|c| c.name().to_string())
+            .unwrap_or_default();
+        paths[0] = Some((root_path, None));
+        let mut queue = VecDeque::from([0]);
+        while let Some(index) = queue.pop_front() {
+            for (value_index, component) in self.value_key_path_components(index) {
+                let ValueVariant::ObjectRef(r) = self.values[value_index].value() else {
+                    continue;
+                };
+                let r = *r as usize;
+                if r < paths.len() && paths[r].is_none() {
+                    let (path, _) = paths[index].as_ref().unwrap();
+                    let key = self.keys.get(self.values[value_index].key_id().as_usize());
+                    paths[r] = Some((format!("{path}.{component}"), key.cloned()));
+                    queue.push_back(r);
+                }
+            }
+        }
+        paths
+    }
+
+    /// Returns indeces of values of an object along with their key path components,
+    /// i.e. keys with an optional index among the values with the same key.
+    fn value_key_path_components(&self, object_index: usize) -> Vec<(usize, String)> {
+        let obj = &self.objects[object_index];
+        let values = self.object_values(obj);
+        let start = obj.first_value_id().as_usize();
+        let mut components = Vec::with_capacity(values.len());
+        for (i, val) in values.iter().enumerate() {
+            let key_index = val.key_id();
+            let key = self
+                .keys
+                .get(key_index.as_usize())
+                .map(String::as_str)
+                .unwrap_or_default();
+            let same_key_count = values.iter().filter(|v| v.key_id() == key_index).count();
+            let component = if same_key_count > 1 {
+                let n = values[..i]
+                    .iter()
+                    .filter(|v| v.key_id() == key_index)
+                    .count();
+                format!("{key}[{n}]")
+            } else {
+                key.to_string()
+            };
+            components.push((start + i, component));
+        }
+        components
+    }
+}
+
+/// Encoding of a string stored in a data value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum StringEncoding {
+    Utf8,
+    Utf16Le { bom: bool },
+    Utf16Be { bom: bool },
+}
+
+impl StringEncoding {
+    pub(crate) fn encode(self, s: &str) -> Vec<u8> {
+        match self {
+            Self::Utf8 => s.as_bytes().to_vec(),
+            Self::Utf16Le { bom } => encode_utf16(s, bom.then_some([0xFF, 0xFE]), u16::to_le_bytes),
+            Self::Utf16Be { bom } => encode_utf16(s, bom.then_some([0xFE, 0xFF]), u16::to_be_bytes),
+        }
+    }
+}
+
+/// Tries to decode a string from a data value and detect its encoding.
+///
+/// UTF-16 strings are recognized either by a byte order mark, or, if there's none,
+/// by zero high bytes of most of their code units.
+pub(crate) fn decode_string(data: &[u8]) -> Option<(String, StringEncoding)> {
+    let (string, encoding) = match data {
+        [0xFF, 0xFE, rest @ ..] => (
+            decode_utf16(rest, u16::from_le_bytes)?,
+            StringEncoding::Utf16Le { bom: true },
+        ),
+        [0xFE, 0xFF, rest @ ..] => (
+            decode_utf16(rest, u16::from_be_bytes)?,
+            StringEncoding::Utf16Be { bom: true },
+        ),
+        _ => match std::str::from_utf8(data) {
+            Ok(s) if !s.contains('\0') => (s.to_string(), StringEncoding::Utf8),
+            _ => {
+                let zeros_at = |parity| {
+                    data.iter()
+                        .skip(parity)
+                        .step_by(2)
+                        .filter(|b| **b == 0)
+                        .count()
+                };
+                let half = data.len() / 2;
+                if zeros_at(1) * 2 > half && zeros_at(0) == 0 {
+                    (
+                        decode_utf16(data, u16::from_le_bytes)?,
+                        StringEncoding::Utf16Le { bom: false },
+                    )
+                } else if zeros_at(0) * 2 > half && zeros_at(1) == 0 {
+                    (
+                        decode_utf16(data, u16::from_be_bytes)?,
+                        StringEncoding::Utf16Be { bom: false },
+                    )
+                } else {
+                    return None;
+                }
+            }
+        },
+    };
+    if string.is_empty()
+        || string
+            .chars()
+            .any(|c| c.is_control() && !matches!(c, '\n' | '\r' | '\t'))
+    {
+        return None;
+    }
+    Some((string, encoding))
+}
+
+fn decode_utf16(data: &[u8], f: fn([u8; 2]) -> u16) -> Option<String> {
+    if !data.len().is_multiple_of(2) {
+        return None;
+    }
+    let units: Vec<u16> = data.chunks_exact(2).map(|c| f([c[0], c[1]])).collect();
+    String::from_utf16(&units).ok()
+}
+
+fn encode_utf16(s: &str, bom: Option<[u8; 2]>, f: fn(u16) -> [u8; 2]) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(2 + s.len() * 2);
+    if let Some(bom) = bom {
+        bytes.extend_from_slice(&bom);
+    }
+    for unit in s.encode_utf16() {
+        bytes.extend_from_slice(&f(unit));
+    }
+    bytes
+}
+
+/// Formats strings as an Apple `.strings` file.
+pub fn strings_to_dot_strings(strings: &[LocalizableString]) -> String {
+    let mut result = String::new();
+    for s in strings {
+        result.push_str(&format!(
+            "/* Class = \"{}\"; Object = {}; */\n\"{}\" = \"{}\";\n\n",
+            s.class_name.replace("*/", "* /"),
+            s.object_index,
+            escape(&s.key_path),
+            escape(&s.value)
+        ));
+    }
+    result
+}
+
+/// Formats strings as a JSON object mapping key paths to strings.
+pub fn strings_to_json(strings: &[LocalizableString]) -> String {
+    let mut result = String::from("{");
+    for (i, s) in strings.iter().enumerate() {
+        if i > 0 {
+            result.push(',');
+        }
+        result.push_str(&format!(
+            "\n  \"{}\": \"{}\"",
+            escape(&s.key_path),
+            escape(&s.value)
+        ));
+    }
+    result.push_str("\n}\n");
+    result
+}
+
+/// Escapes a string to be placed inside of double quotes. The rules are the same
+/// for `.strings` files and JSON.
+fn escape(s: &str) -> String {
+    let mut result = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '"' => result.push_str("\\\""),
+            '\\' => result.push_str("\\\\"),
+            '\n' => result.push_str("\\n"),
+            '\r' => result.push_str("\\r"),
+            '\t' => result.push_str("\\t"),
+            c if c.is_control() => result.push_str(&format!("\\u{:04x}", c as u32)),
+            c => result.push(c),
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ClassName, NIBArchive, Object, Value, ValueVariant};
+
+    fn utf16_with_bom(s: &str) -> Vec<u8> {
+        let mut bytes = vec![0xFF, 0xFE];
+        bytes.extend(s.encode_utf16().flat_map(u16::to_le_bytes));
+        bytes
+    }
+
+    #[test]
+    fn localizable_strings() {
+        let class = |name: &str| ClassName::with_fallbacks(name.into(), Vec::new());
+        let archive = NIBArchive::new(
+            vec![
+                Object::new(0, 0, 4),
+                Object::new(1, 4, 1),
+                Object::new(2, 5, 1),
+                Object::new(3, 6, 1),
+            ],
+            vec![
+                "UIText".into(),
+                "UITitle".into(),
+                "UIImageData".into(),
+                "UIClassName".into(),
+                "UITextView".into(),
+                "UIConnection".into(),
+                "NS.bytes".into(),
+                "UILabel".into(),
+            ],
+            vec![
+                Value::new(0, ValueVariant::Data(b"Hello".to_vec())),
+                Value::new(2, ValueVariant::Data(vec![0x89, b'P', b'N', b'G', 0, 1])),
+                Value::new(4, ValueVariant::ObjectRef(1)),
+                Value::new(5, ValueVariant::ObjectRef(2)),
+                Value::new(1, ValueVariant::Data(utf16_with_bom("Grüße"))),
+                Value::new(3, ValueVariant::Data(b"MyView".to_vec())),
+                Value::new(7, ValueVariant::ObjectRef(3)),
+                Value::new(6, ValueVariant::Data(b"titleLabel".to_vec())),
+            ],
+            vec![
+                class("UILabel"),
+                class("UIButton"),
+                class("UIRuntimeOutletConnection"),
+                class("NSString"),
+            ],
+        )
+        .unwrap();
+        let strings = archive.localizable_strings();
+        let found: Vec<_> = strings.iter().map(|s| (s.key_path(), s.value())).collect();
+        assert_eq!(
+            found,
+            [
+                ("UILabel.UIText", "Hello"),
+                ("UILabel.UITextView.UITitle", "Grüße")
+            ]
+        );
+    }
+}
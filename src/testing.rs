@@ -0,0 +1,162 @@
+//! Helpers for testing code that produces archives.
+//!
+//! The module contains [proptest](mod@proptest) strategies that generate internally
+//! consistent archives and round-trip assertions that encode and decode an archive and
+//! compare the result with the original one.
+
+use crate::{
+    generator::{
+        ArchiveParts, MAX_CLASS_NAMES, MAX_FALLBACK_CLASSES, MAX_KEYS, MAX_OBJECTS,
+        MAX_VALUES_PER_OBJECT,
+    },
+    NIBArchive, ValueVariant,
+};
+use proptest::{collection, prelude::*};
+
+/// A strategy for values of any known type. Object references point to one of
+/// `object_count` objects, which has to be greater than zero.
+pub fn value_variant(object_count: u32) -> impl Strategy<Value = ValueVariant> {
+    prop_oneof![
+        any::<i8>().prop_map(ValueVariant::Int8),
+        any::<i16>().prop_map(ValueVariant::Int16),
+        any::<i32>().prop_map(ValueVariant::Int32),
+        any::<i64>().prop_map(ValueVariant::Int64),
+        any::<bool>().prop_map(ValueVariant::Bool),
+        any::<f32>().prop_map(ValueVariant::Float),
+        any::<f64>().prop_map(ValueVariant::Double),
+        collection::vec(any::<u8>(), 0..64).prop_map(ValueVariant::Data),
+        Just(ValueVariant::Nil),
+        (0..object_count).prop_map(ValueVariant::ObjectRef),
+    ]
+}
+
+/// A strategy for internally consistent archives: keys are unique, every index points
+/// to an existing element, values of objects are contiguous and object references
+/// point to existing objects.
+pub fn archive() -> impl Strategy<Value = NIBArchive> {
+    (1..=MAX_CLASS_NAMES, 1..=MAX_KEYS, 1..=MAX_OBJECTS)
+        .prop_flat_map(|(class_names_count, keys_count, objects_count)| {
+            let class_names = collection::vec(
+                (
+                    ".*",
+                    collection::vec(0..class_names_count, 0..=MAX_FALLBACK_CLASSES),
+                ),
+                class_names_count,
+            );
+            let keys = collection::hash_set(".*", keys_count);
+            let objects = collection::vec(
+                (
+                    0..class_names_count,
+                    collection::vec(
+                        (0..keys_count, value_variant(objects_count as u32)),
+                        0..=MAX_VALUES_PER_OBJECT,
+                    ),
+                ),
+                objects_count,
+            );
+            (class_names, keys, objects, any::<u32>(), any::<u32>())
+        })
+        .prop_map(
+            |(class_names, keys, objects, format_version, coder_version)| {
+                ArchiveParts {
+                    class_names,
+                    keys: keys.into_iter().collect(),
+                    objects,
+                    format_version,
+                    coder_version,
+                }
+                .build()
+            },
+        )
+}
+
+/// Encodes and decodes an archive and panics if the result differs from the original.
+///
+/// Archives are compared semantically: values are compared by their key names rather
+/// than key indeces, since duplicate keys are merged during encoding, and floating point
+/// values are compared bitwise, so `NaN`s are equal. If the archive
+/// [minimizes value widths](NIBArchive::minimize_value_widths()), its values are
+/// expected to be minimized.
+#[track_caller]
+pub fn assert_roundtrip(archive: &NIBArchive) {
+    let decoded = match NIBArchive::from_bytes(archive.to_bytes()) {
+        Ok(decoded) => decoded,
+        Err(e) => panic!("Failed to decode an encoded archive: {e}"),
+    };
+    if let Err(message) = compare(archive, &decoded) {
+        panic!("The archive changed after a round trip: {message}");
+    }
+}
+
+/// Decodes an archive and checks it with [assert_roundtrip()]. Panics if the bytes
+/// can't be decoded.
+#[track_caller]
+pub fn assert_roundtrip_bytes(bytes: &[u8]) {
+    let archive = match NIBArchive::from_bytes(bytes) {
+        Ok(archive) => archive,
+        Err(e) => panic!("Failed to decode an archive: {e}"),
+    };
+    assert_roundtrip(&archive);
+}
+
+fn compare(expected: &NIBArchive, found: &NIBArchive) -> Result<(), String> {
+    if expected.format_version() != found.format_version() {
+        return Err(format!(
+            "format version {} != {}",
+            expected.format_version(),
+            found.format_version()
+        ));
+    }
+    if expected.coder_version() != found.coder_version() {
+        return Err(format!(
+            "coder version {} != {}",
+            expected.coder_version(),
+            found.coder_version()
+        ));
+    }
+    if expected.objects() != found.objects() {
+        return Err("objects differ".to_string());
+    }
+    if expected.class_names() != found.class_names() {
+        return Err("class names differ".to_string());
+    }
+    if expected.values().len() != found.values().len() {
+        return Err(format!(
+            "value count {} != {}",
+            expected.values().len(),
+            found.values().len()
+        ));
+    }
+    for (i, (a, b)) in expected.values().iter().zip(found.values()).enumerate() {
+        let key_a = expected.key(a.key_id());
+        let key_b = found.key(b.key_id());
+        if key_a != key_b {
+            return Err(format!("key of value {i}: {key_a:?} != {key_b:?}"));
+        }
+        let value_a = match expected.minimize_value_widths() {
+            true => a.value().minimized().unwrap_or_else(|| a.value().clone()),
+            false => a.value().clone(),
+        };
+        if !value_a.is_identical(b.value()) {
+            return Err(format!("value {i}: {value_a:?} != {:?}", b.value()));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    proptest! {
+        #[test]
+        fn roundtrip(archive in archive()) {
+            assert_roundtrip(&archive);
+        }
+
+        #[test]
+        fn roundtrip_bytes(archive in archive()) {
+            assert_roundtrip_bytes(&archive.to_bytes());
+        }
+    }
+}
@@ -0,0 +1,277 @@
+use crate::{
+    consts::{classes, keys},
+    ClassName, KeyIndex, NIBArchive, Object, ObjectIndex, Point, Rect, Size, Value, ValueVariant,
+    VarInt,
+};
+use std::collections::HashMap;
+
+/// Maps a Rust type onto an object of an archive, like `encodeWithCoder:` of `NSCoding`.
+///
+/// Fields are written with the helpers of [NibEncoder]:
+///
+/// ```
+/// use nibarchive::{NIBArchive, NibEncoder, Rect, ToNib};
+///
+/// struct View {
+///     frame: Rect,
+///     tag: i64,
+///     subviews: Vec<View>,
+/// }
+///
+/// impl ToNib for View {
+///     fn class_name(&self) -> &str {
+///         "UIView"
+///     }
+///
+///     fn to_nib(&self, enc: &mut NibEncoder) {
+///         enc.field("UIFrame", &self.frame);
+///         enc.field("UITag", &self.tag);
+///         enc.array("UISubviews", &self.subviews);
+///     }
+/// }
+///
+/// let view = View { frame: Rect::default(), tag: 1, subviews: Vec::new() };
+/// let archive = NIBArchive::from_object(&view);
+/// assert_eq!(archive.objects().len(), 2);
+/// ```
+pub trait ToNib {
+    /// Returns the class name of the encoded object.
+    fn class_name(&self) -> &str;
+
+    /// Writes values of the object into `enc`.
+    fn to_nib(&self, enc: &mut NibEncoder);
+}
+
+/// Converts a Rust type into a single value of an archive.
+///
+/// Returns `None` if no value should be written, e.g. for `Option::None`.
+pub trait ToNibValue {
+    fn to_nib_value(&self, enc: &mut NibEncoder) -> Option<ValueVariant>;
+}
+
+/// Builds an archive from Rust types implementing [ToNib].
+///
+/// The first encoded object becomes the root object of the archive. Values of every
+/// object are kept together, so objects referenced by a field are encoded
+/// before the object referencing them is finished.
+#[derive(Debug, Default)]
+pub struct NibEncoder {
+    objects: Vec<Object>,
+    keys: Vec<String>,
+    key_indeces: HashMap<String, KeyIndex>,
+    values: Vec<Value>,
+    class_names: Vec<ClassName>,
+    class_name_indeces: HashMap<String, VarInt>,
+    /// Values of the objects being encoded, the innermost one is the last.
+    pending: Vec<Vec<Value>>,
+}
+
+impl NibEncoder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Encodes an object and returns its index.
+    pub fn encode<T: ToNib + ?Sized>(&mut self, value: &T) -> ObjectIndex {
+        self.encode_with(value.class_name(), |enc| value.to_nib(enc))
+    }
+
+    /// Adds a value to the object being encoded.
+    ///
+    /// # Panics
+    ///
+    /// Panics if no object is being encoded.
+    pub fn value(&mut self, key: &str, value: ValueVariant) {
+        let key = self.key(key);
+        self.pending
+            .last_mut()
+            .expect("values can only be added while encoding an object")
+            .push(Value::new(key, value));
+    }
+
+    /// Adds a field to the object being encoded, unless the field has no value.
+    pub fn field<T: ToNibValue + ?Sized>(&mut self, key: &str, value: &T) {
+        if let Some(value) = value.to_nib_value(self) {
+            self.value(key, value);
+        }
+    }
+
+    /// Encodes an object and adds a field referencing it.
+    pub fn object<T: ToNib + ?Sized>(&mut self, key: &str, value: &T) {
+        let index = self.encode(value);
+        self.value(key, ValueVariant::ObjectRef(index.0 as u32));
+    }
+
+    /// Encodes elements as an `NSArray` and adds a field referencing it.
+    pub fn array<T: ToNib>(&mut self, key: &str, elements: &[T]) {
+        let index = self.encode_with(classes::NS_ARRAY, |enc| {
+            for element in elements {
+                enc.object(keys::UI_NIB_ENCODER_EMPTY, element);
+            }
+        });
+        self.value(key, ValueVariant::ObjectRef(index.0 as u32));
+    }
+
+    /// Returns the archive with all encoded objects.
+    pub fn finish(self) -> NIBArchive {
+        NIBArchive::new_unchecked(self.objects, self.keys, self.values, self.class_names)
+    }
+
+    fn encode_with<F: FnOnce(&mut Self)>(&mut self, class_name: &str, f: F) -> ObjectIndex {
+        let index = self.objects.len();
+        let class_name_index = self.class_name(class_name);
+        self.objects.push(Object::new(class_name_index, 0, 0));
+        self.pending.push(Vec::new());
+        f(self);
+        let values = self.pending.pop().unwrap_or_default();
+        let obj = &mut self.objects[index];
+        obj.set_values_index(self.values.len() as VarInt);
+        obj.set_value_count(values.len() as VarInt);
+        self.values.extend(values);
+        ObjectIndex(index as VarInt)
+    }
+
+    fn key(&mut self, key: &str) -> KeyIndex {
+        if let Some(index) = self.key_indeces.get(key) {
+            return *index;
+        }
+        let index = KeyIndex(self.keys.len() as VarInt);
+        self.keys.push(key.to_string());
+        self.key_indeces.insert(key.to_string(), index);
+        index
+    }
+
+    fn class_name(&mut self, name: &str) -> VarInt {
+        if let Some(index) = self.class_name_indeces.get(name) {
+            return *index;
+        }
+        let index = self.class_names.len() as VarInt;
+        self.class_names
+            .push(ClassName::with_fallbacks(name.to_string(), Vec::new()));
+        self.class_name_indeces.insert(name.to_string(), index);
+        index
+    }
+}
+
+impl NIBArchive {
+    /// Creates an archive whose root object is `root`.
+    pub fn from_object<T: ToNib + ?Sized>(root: &T) -> Self {
+        let mut enc = NibEncoder::new();
+        enc.encode(root);
+        enc.finish()
+    }
+}
+
+macro_rules! impl_to_nib_value {
+    ($($t:ty => $variant:ident),*) => {
+        $(
+            impl ToNibValue for $t {
+                fn to_nib_value(&self, _enc: &mut NibEncoder) -> Option<ValueVariant> {
+                    Some(ValueVariant::$variant(*self))
+                }
+            }
+        )*
+    };
+}
+
+impl_to_nib_value!(
+    i8 => Int8, i16 => Int16, i32 => Int32, i64 => Int64, f32 => Float, f64 => Double,
+    bool => Bool
+);
+
+macro_rules! impl_to_nib_value_unsigned {
+    ($($t:ty),*) => {
+        $(
+            impl ToNibValue for $t {
+                /// Writes the number with the smallest integer type that holds it.
+                /// Numbers above `i64::MAX` wrap around.
+                fn to_nib_value(&self, _enc: &mut NibEncoder) -> Option<ValueVariant> {
+                    let value = ValueVariant::Int64(*self as i64);
+                    Some(value.minimized().unwrap_or(value))
+                }
+            }
+        )*
+    };
+}
+
+impl_to_nib_value_unsigned!(u8, u16, u32, u64, usize);
+
+impl ToNibValue for str {
+    /// Writes a UTF-8 string into a data value.
+    fn to_nib_value(&self, _enc: &mut NibEncoder) -> Option<ValueVariant> {
+        Some(ValueVariant::Data(self.as_bytes().to_vec()))
+    }
+}
+
+impl ToNibValue for String {
+    /// Writes a UTF-8 string into a data value.
+    fn to_nib_value(&self, enc: &mut NibEncoder) -> Option<ValueVariant> {
+        self.as_str().to_nib_value(enc)
+    }
+}
+
+impl ToNibValue for [u8] {
+    fn to_nib_value(&self, _enc: &mut NibEncoder) -> Option<ValueVariant> {
+        Some(ValueVariant::Data(self.to_vec()))
+    }
+}
+
+impl ToNibValue for Vec<u8> {
+    fn to_nib_value(&self, enc: &mut NibEncoder) -> Option<ValueVariant> {
+        self.as_slice().to_nib_value(enc)
+    }
+}
+
+impl ToNibValue for ObjectIndex {
+    fn to_nib_value(&self, _enc: &mut NibEncoder) -> Option<ValueVariant> {
+        Some(ValueVariant::ObjectRef(self.0 as u32))
+    }
+}
+
+impl ToNibValue for Point {
+    /// Writes the point as 2 little-endian doubles.
+    fn to_nib_value(&self, _enc: &mut NibEncoder) -> Option<ValueVariant> {
+        Some(doubles(&[self.x, self.y]))
+    }
+}
+
+impl ToNibValue for Size {
+    /// Writes the size as 2 little-endian doubles.
+    fn to_nib_value(&self, _enc: &mut NibEncoder) -> Option<ValueVariant> {
+        Some(doubles(&[self.width, self.height]))
+    }
+}
+
+impl ToNibValue for Rect {
+    /// Writes the rectangle as 4 little-endian doubles.
+    fn to_nib_value(&self, _enc: &mut NibEncoder) -> Option<ValueVariant> {
+        Some(doubles(&[
+            self.origin.x,
+            self.origin.y,
+            self.size.width,
+            self.size.height,
+        ]))
+    }
+}
+
+impl ToNibValue for ValueVariant {
+    fn to_nib_value(&self, _enc: &mut NibEncoder) -> Option<ValueVariant> {
+        Some(self.clone())
+    }
+}
+
+impl<T: ToNibValue> ToNibValue for Option<T> {
+    fn to_nib_value(&self, enc: &mut NibEncoder) -> Option<ValueVariant> {
+        self.as_ref()?.to_nib_value(enc)
+    }
+}
+
+impl<T: ToNibValue + ?Sized> ToNibValue for &T {
+    fn to_nib_value(&self, enc: &mut NibEncoder) -> Option<ValueVariant> {
+        (**self).to_nib_value(enc)
+    }
+}
+
+fn doubles(numbers: &[f64]) -> ValueVariant {
+    ValueVariant::Data(numbers.iter().flat_map(|n| n.to_le_bytes()).collect())
+}
@@ -0,0 +1,189 @@
+//! Typed models of common UIKit classes.
+//!
+//! The models implement [FromNib], so they can be decoded with
+//! [NIBArchive::decode_object()](crate::NIBArchive::decode_object()) without knowing
+//! the keys UIKit uses. Subclasses embed the model of their superclass, e.g. [UILabel]
+//! has a [UIView]. Subviews are always decoded as [UIView]s.
+//!
+//! ```no_run
+//! use nibarchive::{uikit::UILabel, NIBArchive};
+//!
+//! let archive = NIBArchive::from_file("View.nib")?;
+//! for (index, _) in archive.find_by_class("UILabel") {
+//!     let label: UILabel = archive.decode_object(index)?;
+//!     println!("{:?} at {}", label.text, label.view.frame);
+//! }
+//! # Ok::<(), nibarchive::Error>(())
+//! ```
+
+use crate::{consts::keys, Color, Error, FromNib, NibContext, Object, ObjectIndex, Point, Rect};
+
+/// A `UIView`.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct UIView {
+    pub class_name: String,
+    /// Frame in the coordinate system of the superview. It's computed from the bounds
+    /// and the center if the archive doesn't store it.
+    pub frame: Rect,
+    pub bounds: Rect,
+    pub center: Point,
+    pub tag: i64,
+    pub hidden: bool,
+    pub alpha: f64,
+    pub background_color: Option<Color>,
+    pub accessibility_identifier: Option<String>,
+    pub subviews: Vec<UIView>,
+}
+
+impl FromNib for UIView {
+    fn from_nib(obj: &Object, ctx: &NibContext) -> Result<Self, Error> {
+        let bounds: Rect = ctx.field_or_default(obj, keys::UI_BOUNDS)?;
+        let center: Point = ctx.field_or_default(obj, keys::UI_CENTER)?;
+        let frame = ctx.field_opt(obj, keys::UI_FRAME)?.unwrap_or(Rect {
+            origin: Point {
+                x: center.x - bounds.size.width / 2.0,
+                y: center.y - bounds.size.height / 2.0,
+            },
+            size: bounds.size,
+        });
+        Ok(Self {
+            class_name: ctx.class_name(obj).to_string(),
+            frame,
+            bounds,
+            center,
+            tag: ctx.field_or_default(obj, keys::UI_TAG)?,
+            hidden: ctx.field_or_default(obj, keys::UI_HIDDEN)?,
+            alpha: ctx.field_opt(obj, keys::UI_ALPHA)?.unwrap_or(1.0),
+            background_color: color(obj, ctx, keys::UI_BACKGROUND_COLOR)?,
+            accessibility_identifier: ctx.field_opt(obj, keys::UI_ACCESSIBILITY_IDENTIFIER)?,
+            subviews: ctx.array(obj, keys::UI_SUBVIEWS)?,
+        })
+    }
+}
+
+/// A `UILabel`.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct UILabel {
+    pub view: UIView,
+    pub text: Option<String>,
+    pub text_color: Option<Color>,
+    pub number_of_lines: i64,
+}
+
+impl FromNib for UILabel {
+    fn from_nib(obj: &Object, ctx: &NibContext) -> Result<Self, Error> {
+        Ok(Self {
+            view: UIView::from_nib(obj, ctx)?,
+            text: ctx.field_opt(obj, keys::UI_TEXT)?,
+            text_color: color(obj, ctx, keys::UI_TEXT_COLOR)?,
+            number_of_lines: ctx.field_opt(obj, keys::UI_NUMBER_OF_LINES)?.unwrap_or(1),
+        })
+    }
+}
+
+/// A `UIButton`.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct UIButton {
+    pub view: UIView,
+    pub enabled: bool,
+    /// Title of the normal state.
+    pub title: Option<String>,
+}
+
+impl FromNib for UIButton {
+    fn from_nib(obj: &Object, ctx: &NibContext) -> Result<Self, Error> {
+        // Titles are kept in a dictionary of contents of button states
+        let mut title = None;
+        if let Some(contents) =
+            ctx.field_opt::<ObjectIndex>(obj, keys::UI_BUTTON_STATEFUL_CONTENT)?
+        {
+            for content in ctx.decode::<Elements>(contents)?.0 {
+                title = ctx.decode::<ButtonContent>(content)?.0;
+                if title.is_some() {
+                    break;
+                }
+            }
+        }
+        Ok(Self {
+            view: UIView::from_nib(obj, ctx)?,
+            enabled: ctx.field_opt(obj, keys::UI_ENABLED)?.unwrap_or(true),
+            title,
+        })
+    }
+}
+
+/// A `UIImageView`.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct UIImageView {
+    pub view: UIView,
+    /// Name of the image resource, if the image is loaded from the bundle.
+    pub image_name: Option<String>,
+}
+
+impl FromNib for UIImageView {
+    fn from_nib(obj: &Object, ctx: &NibContext) -> Result<Self, Error> {
+        Ok(Self {
+            view: UIView::from_nib(obj, ctx)?,
+            image_name: ctx
+                .object_opt::<ImageName>(obj, keys::UI_IMAGE)?
+                .and_then(|image| image.0),
+        })
+    }
+}
+
+/// A `UIViewController`.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct UIViewController {
+    pub class_name: String,
+    pub title: Option<String>,
+    pub nib_name: Option<String>,
+    pub storyboard_identifier: Option<String>,
+    pub view: Option<UIView>,
+}
+
+impl FromNib for UIViewController {
+    fn from_nib(obj: &Object, ctx: &NibContext) -> Result<Self, Error> {
+        Ok(Self {
+            class_name: ctx.class_name(obj).to_string(),
+            title: ctx.field_opt(obj, keys::UI_TITLE)?,
+            nib_name: ctx.field_opt(obj, keys::UI_NIB_NAME)?,
+            storyboard_identifier: ctx.field_opt(obj, keys::UI_STORYBOARD_IDENTIFIER)?,
+            view: ctx.object_opt(obj, keys::UI_VIEW)?,
+        })
+    }
+}
+
+/// Decodes a color referenced by a given key.
+fn color(obj: &Object, ctx: &NibContext, key: &str) -> Result<Option<Color>, Error> {
+    Ok(ctx
+        .field_opt::<ObjectIndex>(obj, key)?
+        .and_then(|index| ctx.archive().color(index.as_usize())))
+}
+
+/// Elements of an array, a set or a dictionary.
+struct Elements(Vec<ObjectIndex>);
+
+impl FromNib for Elements {
+    fn from_nib(obj: &Object, ctx: &NibContext) -> Result<Self, Error> {
+        ctx.fields(obj, keys::UI_NIB_ENCODER_EMPTY).map(Self)
+    }
+}
+
+/// Title of a `UIButtonContent`.
+struct ButtonContent(Option<String>);
+
+impl FromNib for ButtonContent {
+    fn from_nib(obj: &Object, ctx: &NibContext) -> Result<Self, Error> {
+        // Keys of the dictionary are numbers, which aren't objects with a title
+        Ok(Self(ctx.field_opt(obj, keys::UI_TITLE).ok().flatten()))
+    }
+}
+
+/// Resource name of a `UIImageNibPlaceholder`.
+struct ImageName(Option<String>);
+
+impl FromNib for ImageName {
+    fn from_nib(obj: &Object, ctx: &NibContext) -> Result<Self, Error> {
+        ctx.field_opt(obj, keys::UI_RESOURCE_NAME).map(Self)
+    }
+}
@@ -1,20 +1,29 @@
-use crate::{decode_var_int, encode_var_int, Error, VarInt};
-use std::io::{Read, Seek};
-
-const TYPE_INT8: u8 = 0;
-const TYPE_INT16: u8 = 1;
-const TYPE_INT32: u8 = 2;
-const TYPE_INT64: u8 = 3;
-const TYPE_BOOL_FALSE: u8 = 4;
-const TYPE_BOOL_TRUE: u8 = 5;
-const TYPE_FLOAT: u8 = 6;
-const TYPE_DOUBLE: u8 = 7;
-const TYPE_DATA: u8 = 8;
-const TYPE_NIL: u8 = 9;
-const TYPE_OBJECT_REF: u8 = 10;
+use crate::{
+    codec::{write_fixed, Endianness},
+    encode_var_int,
+    io::Cursor,
+    var_int_len, DecodeOptions, Error, KeyIndex, NIBArchive, ObjectIndex, VarInt, MAGIC_BYTES,
+};
+use alloc::{string::String, vec::Vec};
+
+const LE: Endianness = Endianness::Little;
+
+pub(crate) const TYPE_INT8: u8 = 0;
+pub(crate) const TYPE_INT16: u8 = 1;
+pub(crate) const TYPE_INT32: u8 = 2;
+pub(crate) const TYPE_INT64: u8 = 3;
+pub(crate) const TYPE_BOOL_FALSE: u8 = 4;
+pub(crate) const TYPE_BOOL_TRUE: u8 = 5;
+pub(crate) const TYPE_FLOAT: u8 = 6;
+pub(crate) const TYPE_DOUBLE: u8 = 7;
+pub(crate) const TYPE_DATA: u8 = 8;
+pub(crate) const TYPE_NIL: u8 = 9;
+pub(crate) const TYPE_OBJECT_REF: u8 = 10;
 
 /// Represents any object value.
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub enum ValueVariant {
     Int8(i8),
     Int16(i16),
@@ -26,149 +35,276 @@ pub enum ValueVariant {
     Data(Vec<u8>),
     Nil,
     ObjectRef(u32),
+    /// A value of a type this crate doesn't know about, along with its raw payload.
+    ///
+    /// Only produced when [DecodeOptions::set_lenient_value_types()] is enabled.
+    ///
+    /// [DecodeOptions::set_lenient_value_types()]: crate::DecodeOptions::set_lenient_value_types()
+    Unknown {
+        type_byte: u8,
+        raw: Vec<u8>,
+    },
 }
 
-/// Represents a single value of a NIB Archive.
-///
-/// A value contains an index to a key with its name and a value itself.
-#[derive(Debug, Clone, PartialEq)]
-pub struct Value {
-    key_index: VarInt,
-    value: ValueVariant,
-}
+impl ValueVariant {
+    /// Returns the name of the value's type, e.g. `Int8` or `ObjectRef`.
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            ValueVariant::Int8(_) => "Int8",
+            ValueVariant::Int16(_) => "Int16",
+            ValueVariant::Int32(_) => "Int32",
+            ValueVariant::Int64(_) => "Int64",
+            ValueVariant::Bool(_) => "Bool",
+            ValueVariant::Float(_) => "Float",
+            ValueVariant::Double(_) => "Double",
+            ValueVariant::Data(_) => "Data",
+            ValueVariant::Nil => "Nil",
+            ValueVariant::ObjectRef(_) => "ObjectRef",
+            ValueVariant::Unknown { .. } => "Unknown",
+        }
+    }
 
-impl Value {
-    pub(crate) fn try_from_reader<T: Read + Seek>(mut reader: &mut T) -> Result<Self, Error> {
-        let key_index = decode_var_int(&mut reader)?;
-        let mut value_type_byte = [0; 1];
-        reader.read_exact(&mut value_type_byte)?;
-        let value_type_byte = value_type_byte[0];
-        let value = match value_type_byte {
-            TYPE_INT8 => {
-                let mut buf = [0; 1];
-                reader.read_exact(&mut buf)?;
-                ValueVariant::Int8(i8::from_le_bytes(buf))
-            }
-            TYPE_INT16 => {
-                let mut buf = [0; 2];
-                reader.read_exact(&mut buf)?;
-                ValueVariant::Int16(i16::from_le_bytes(buf))
-            }
-            TYPE_INT32 => {
-                let mut buf = [0; 4];
-                reader.read_exact(&mut buf)?;
-                ValueVariant::Int32(i32::from_le_bytes(buf))
-            }
-            TYPE_INT64 => {
-                let mut buf = [0; 8];
-                reader.read_exact(&mut buf)?;
-                ValueVariant::Int64(i64::from_le_bytes(buf))
-            }
-            TYPE_BOOL_FALSE => ValueVariant::Bool(false),
-            TYPE_BOOL_TRUE => ValueVariant::Bool(true),
-            TYPE_FLOAT => {
-                let mut buf = [0; 4];
-                reader.read_exact(&mut buf)?;
-                ValueVariant::Float(f32::from_le_bytes(buf))
-            }
-            TYPE_DOUBLE => {
-                let mut buf = [0; 8];
-                reader.read_exact(&mut buf)?;
-                ValueVariant::Double(f64::from_le_bytes(buf))
-            }
-            TYPE_DATA => {
-                let length = decode_var_int(&mut reader)?;
-                let mut buf = vec![0; length as usize];
-                reader.read_exact(&mut buf)?;
-                ValueVariant::Data(buf)
-            }
-            TYPE_NIL => ValueVariant::Nil,
-            TYPE_OBJECT_REF => {
-                let mut buf = [0; 4];
-                reader.read_exact(&mut buf)?;
-                ValueVariant::ObjectRef(u32::from_le_bytes(buf))
-            }
-            _ => {
-                return Err(Error::FormatError(format!(
-                    "Unknown value type {value_type_byte:#04x}"
-                )))
-            }
-        };
-        Ok(Self { key_index, value })
+    /// Same as `==`, but compares floating point values bitwise, so `-0.0` differs
+    /// from `0.0` and equal `NaN`s are identical.
+    pub(crate) fn is_identical(&self, other: &Self) -> bool {
+        match (self, other) {
+            (ValueVariant::Float(a), ValueVariant::Float(b)) => a.to_bits() == b.to_bits(),
+            (ValueVariant::Double(a), ValueVariant::Double(b)) => a.to_bits() == b.to_bits(),
+            _ => self == other,
+        }
     }
 
-    pub(crate) fn to_bytes(&self) -> Vec<u8> {
-        let mut bytes = encode_var_int(self.key_index);
+    /// Tries to interpret a [data value](ValueVariant::Data) as a string.
+    ///
+    /// Both UTF-8 and UTF-16 (little and big endian, with or without a byte order mark)
+    /// strings are recognized. Data that is empty or contains control characters other
+    /// than line breaks and tabs isn't considered a string.
+    ///
+    /// Returns `None` for non-data values.
+    #[cfg(feature = "std")]
+    pub fn data_as_string(&self) -> Option<String> {
+        match self {
+            ValueVariant::Data(data) => crate::strings::decode_string(data).map(|(s, _)| s),
+            _ => None,
+        }
+    }
+
+    /// Returns an integer value as `i64`.
+    ///
+    /// Returns `None` for non-integer values.
+    pub fn as_i64(&self) -> Option<i64> {
+        match self {
+            ValueVariant::Int8(v) => Some((*v).into()),
+            ValueVariant::Int16(v) => Some((*v).into()),
+            ValueVariant::Int32(v) => Some((*v).into()),
+            ValueVariant::Int64(v) => Some(*v),
+            _ => None,
+        }
+    }
+
+    /// Returns a floating point or an integer value as `f64`.
+    ///
+    /// Returns `None` for other values.
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            ValueVariant::Float(v) => Some((*v).into()),
+            ValueVariant::Double(v) => Some(*v),
+            _ => self.as_i64().map(|v| v as f64),
+        }
+    }
+
+    /// Encodes the value along with a given key index.
+    pub(crate) fn to_bytes(&self, key_index: KeyIndex) -> Vec<u8> {
+        let mut bytes = encode_var_int(key_index.0);
 
-        match &self.value {
-            ValueVariant::Int8(v) => {
+        match self {
+            Self::Int8(v) => {
                 bytes.push(TYPE_INT8);
-                bytes.extend_from_slice(&v.to_le_bytes());
+                write_fixed(*v, &mut bytes, LE);
             }
-            ValueVariant::Int16(v) => {
+            Self::Int16(v) => {
                 bytes.push(TYPE_INT16);
-                bytes.extend_from_slice(&v.to_le_bytes());
+                write_fixed(*v, &mut bytes, LE);
             }
-            ValueVariant::Int32(v) => {
+            Self::Int32(v) => {
                 bytes.push(TYPE_INT32);
-                bytes.extend_from_slice(&v.to_le_bytes());
+                write_fixed(*v, &mut bytes, LE);
             }
-            ValueVariant::Int64(v) => {
+            Self::Int64(v) => {
                 bytes.push(TYPE_INT64);
-                bytes.extend_from_slice(&v.to_le_bytes());
+                write_fixed(*v, &mut bytes, LE);
             }
-            ValueVariant::Bool(v) => {
+            Self::Bool(v) => {
                 if !v {
                     bytes.push(TYPE_BOOL_FALSE);
                 } else {
                     bytes.push(TYPE_BOOL_TRUE);
                 }
             }
-            ValueVariant::Float(v) => {
+            Self::Float(v) => {
                 bytes.push(TYPE_FLOAT);
-                bytes.extend_from_slice(&v.to_le_bytes());
+                write_fixed(*v, &mut bytes, LE);
             }
-            ValueVariant::Double(v) => {
+            Self::Double(v) => {
                 bytes.push(TYPE_DOUBLE);
-                bytes.extend_from_slice(&v.to_le_bytes());
+                write_fixed(*v, &mut bytes, LE);
             }
-            ValueVariant::Data(v) => {
+            Self::Data(v) => {
                 bytes.push(TYPE_DATA);
                 bytes.append(&mut encode_var_int(v.len() as i32));
                 bytes.extend_from_slice(v);
             }
-            ValueVariant::Nil => {
+            Self::Nil => {
                 bytes.push(TYPE_NIL);
             }
-            ValueVariant::ObjectRef(v) => {
+            Self::ObjectRef(v) => {
                 bytes.push(TYPE_OBJECT_REF);
-                bytes.extend_from_slice(&v.to_le_bytes());
+                write_fixed(*v, &mut bytes, LE);
+            }
+            Self::Unknown { type_byte, raw } => {
+                bytes.push(*type_byte);
+                bytes.extend_from_slice(raw);
             }
         }
 
         bytes
     }
 
+    /// Returns the length of the value encoded along with a given key index.
+    pub(crate) fn encoded_len(&self, key_index: KeyIndex) -> usize {
+        let value_len = match self {
+            Self::Int8(_) => 1,
+            Self::Int16(_) => 2,
+            Self::Int32(_) | Self::Float(_) | Self::ObjectRef(_) => 4,
+            Self::Int64(_) | Self::Double(_) => 8,
+            Self::Bool(_) | Self::Nil => 0,
+            Self::Data(v) => var_int_len(v.len() as VarInt) + v.len(),
+            Self::Unknown { raw, .. } => raw.len(),
+        };
+        // Key index, a type byte and the value itself
+        var_int_len(key_index.0) + 1 + value_len
+    }
+
+    /// Returns the same value stored with the smallest type that holds it exactly,
+    /// or `None` if the type is already the smallest one.
+    ///
+    /// Integers are narrowed down to `Int8`, doubles to floats.
+    pub fn minimized(&self) -> Option<ValueVariant> {
+        match self {
+            ValueVariant::Int16(_) | ValueVariant::Int32(_) | ValueVariant::Int64(_) => {
+                let v = self.as_i64()?;
+                let minimized = if let Ok(v) = i8::try_from(v) {
+                    ValueVariant::Int8(v)
+                } else if let Ok(v) = i16::try_from(v) {
+                    ValueVariant::Int16(v)
+                } else if let Ok(v) = i32::try_from(v) {
+                    ValueVariant::Int32(v)
+                } else {
+                    ValueVariant::Int64(v)
+                };
+                (&minimized != self).then_some(minimized)
+            }
+            ValueVariant::Double(v) if (*v as f32) as f64 == *v => {
+                Some(ValueVariant::Float(*v as f32))
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns the index of a referenced object.
+    ///
+    /// Returns `None` if the value isn't an object reference.
+    pub fn as_object_ref(&self) -> Option<ObjectIndex> {
+        match self {
+            ValueVariant::ObjectRef(v) => Some(ObjectIndex(*v as VarInt)),
+            _ => None,
+        }
+    }
+
+    /// Returns `true` if the value is a [data value](ValueVariant::Data) starting with
+    /// the magic bytes of a NIB Archive, i.e. a whole nib embedded into another one.
+    pub fn is_embedded_archive(&self) -> bool {
+        matches!(self, ValueVariant::Data(data) if data.starts_with(MAGIC_BYTES))
+    }
+
+    /// Tries to decode a [data value](ValueVariant::Data) as an embedded NIB Archive.
+    ///
+    /// Returns `None` if the value isn't a data value or doesn't start with `NIBArchive`.
+    pub fn as_embedded_archive(&self) -> Option<Result<NIBArchive, Error>> {
+        self.as_embedded_archive_with_options(&DecodeOptions::default())
+    }
+
+    /// Same as [ValueVariant::as_embedded_archive()], but decodes the archive
+    /// using given options.
+    pub fn as_embedded_archive_with_options(
+        &self,
+        options: &DecodeOptions,
+    ) -> Option<Result<NIBArchive, Error>> {
+        match self {
+            ValueVariant::Data(data) if data.starts_with(MAGIC_BYTES) => Some(
+                NIBArchive::from_reader_with_options(&mut Cursor::new(data), options)
+                    .map(|(archive, _)| archive),
+            ),
+            _ => None,
+        }
+    }
+}
+
+/// Represents a single value of a NIB Archive.
+///
+/// A value contains an index to a key with its name and a value itself.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub struct Value {
+    key_index: KeyIndex,
+    value: ValueVariant,
+}
+
+impl Value {
+    pub(crate) fn to_bytes(&self) -> Vec<u8> {
+        self.value.to_bytes(self.key_index)
+    }
+
+    pub(crate) fn encoded_len(&self) -> usize {
+        self.value.encoded_len(self.key_index)
+    }
+
+    pub(crate) fn is_minimizable(&self) -> bool {
+        self.value.minimized().is_some()
+    }
+
     /// Creates a new NIB Archive value.
-    pub fn new(key_index: VarInt, value: ValueVariant) -> Self {
-        Self { key_index, value }
+    ///
+    /// The key index may be given either typed or as a raw `i32`.
+    pub fn new(key_index: impl Into<KeyIndex>, value: ValueVariant) -> Self {
+        Self {
+            key_index: key_index.into(),
+            value,
+        }
     }
 
     /// Returns an index to a key with value's name.
-    pub fn key_index(&self) -> VarInt {
+    pub fn key_id(&self) -> KeyIndex {
         self.key_index
     }
 
+    /// Returns a raw index to a key with value's name.
+    #[deprecated(note = "use `Value::key_id()`, which returns a typed index")]
+    pub fn key_index(&self) -> VarInt {
+        self.key_index.0
+    }
+
     /// Sets value's key index.
-    pub fn set_key_index(&mut self, index: VarInt) {
-        self.key_index = index
+    pub fn set_key_index(&mut self, index: impl Into<KeyIndex>) {
+        self.key_index = index.into()
     }
 
     /// Returns a reference to a key associated with the current value.
     ///
     /// Pass the return value of [crate::NIBArchive::keys()] method for a proper result.
     pub fn key<'a>(&self, keys: &'a [String]) -> &'a String {
-        &keys[self.key_index() as usize]
+        &keys[self.key_id().as_usize()]
     }
 
     /// Return the underlying value.
@@ -181,8 +317,14 @@ impl Value {
         self.value = value
     }
 
-    /// Consumes itself and returns a unit of `key_index` and `value`.
+    /// Tries to decode the value as an embedded NIB Archive.
+    /// See [ValueVariant::as_embedded_archive()].
+    pub fn as_embedded_archive(&self) -> Option<Result<NIBArchive, Error>> {
+        self.value.as_embedded_archive()
+    }
+
+    /// Consumes itself and returns a unit of raw `key_index` and `value`.
     pub fn into_inner(self) -> (VarInt, ValueVariant) {
-        (self.key_index, self.value)
+        (self.key_index.0, self.value)
     }
 }
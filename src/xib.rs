@@ -0,0 +1,285 @@
+//! Experimental reconstruction of Interface Builder documents.
+//!
+//! A compiled `.nib` doesn't keep most of the information a `.xib` has (object identifiers,
+//! design-time attributes, constraints editing metadata, etc.), so the output is only
+//! a rough approximation. It contains views of common UIKit and AppKit classes along with
+//! their frames, autoresizing masks, texts and subviews.
+
+use crate::{
+    consts::{classes, keys},
+    NIBArchive, ValueVariant,
+};
+use std::fmt::Write;
+
+/// Maps class names to element names of Interface Builder documents.
+const ELEMENTS: &[(&str, &str)] = &[
+    (classes::UI_VIEW, "view"),
+    (classes::UI_LABEL, "label"),
+    (classes::UI_BUTTON, "button"),
+    (classes::UI_IMAGE_VIEW, "imageView"),
+    (classes::UI_SCROLL_VIEW, "scrollView"),
+    (classes::UI_TABLE_VIEW, "tableView"),
+    (classes::UI_COLLECTION_VIEW, "collectionView"),
+    (classes::UI_TEXT_FIELD, "textField"),
+    (classes::UI_TEXT_VIEW, "textView"),
+    (classes::UI_SWITCH, "switch"),
+    (classes::UI_SLIDER, "slider"),
+    (classes::UI_STACK_VIEW, "stackView"),
+    (classes::UI_ACTIVITY_INDICATOR_VIEW, "activityIndicatorView"),
+    (classes::NS_VIEW, "customView"),
+    (classes::NS_BUTTON, "button"),
+    (classes::NS_TEXT_FIELD, "textField"),
+    (classes::NS_IMAGE_VIEW, "imageView"),
+    (classes::NS_SCROLL_VIEW, "scrollView"),
+    (classes::NS_STACK_VIEW, "stackView"),
+];
+
+const AUTORESIZING_KEYS: &[&str] = &[keys::UI_AUTORESIZING_MASK, keys::NS_V_FLAGS];
+const TEXT_KEYS: &[&str] = &[keys::UI_TEXT, keys::NS_CONTENTS];
+
+/// Reconstructs an Interface Builder XML document from a given archive.
+///
+/// The document is meant to be inspected by a human or opened in Xcode as a starting
+/// point; it's not guaranteed to compile back into an identical `.nib`.
+pub fn nib_to_xib(archive: &NIBArchive) -> String {
+    let is_appkit = archive
+        .class_names()
+        .iter()
+        .any(|c| c.name().starts_with("NS") && element_name(c.name()).is_some());
+    let (doc_type, runtime) = if is_appkit {
+        ("com.apple.InterfaceBuilder3.Cocoa.XIB", "MacOSX.Cocoa")
+    } else {
+        (
+            "com.apple.InterfaceBuilder3.CocoaTouch.XIB",
+            "iOS.CocoaTouch",
+        )
+    };
+
+    let views: Vec<usize> = (0..archive.objects().len())
+        .filter(|i| view_element(archive, *i).is_some())
+        .collect();
+    let mut is_subview = vec![false; archive.objects().len()];
+    for view in &views {
+        for subview in subviews(archive, *view) {
+            is_subview[subview] = true;
+        }
+    }
+
+    let mut xml = String::new();
+    xml.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    let _ = writeln!(
+        xml,
+        "<document type=\"{doc_type}\" version=\"3.0\" targetRuntime=\"{runtime}\" propertyAccessControl=\"none\">"
+    );
+    xml.push_str("    <objects>\n");
+    let mut visited = vec![false; archive.objects().len()];
+    for view in views.iter().filter(|v| !is_subview[**v]) {
+        write_view(archive, *view, 2, &mut visited, &mut xml);
+    }
+    xml.push_str("    </objects>\n");
+    xml.push_str("</document>\n");
+    xml
+}
+
+fn write_view(
+    archive: &NIBArchive,
+    index: usize,
+    depth: usize,
+    visited: &mut [bool],
+    xml: &mut String,
+) {
+    let Some((element, custom_class)) = view_element(archive, index) else {
+        return;
+    };
+    if visited[index] {
+        return;
+    }
+    visited[index] = true;
+    let indent = "    ".repeat(depth);
+
+    let _ = write!(xml, "{indent}<{element} id=\"obj-{index}\"");
+    if let Some(custom_class) = custom_class {
+        let _ = write!(xml, " customClass=\"{}\"", escape(custom_class));
+    }
+    if let Some(text) = find_value(archive, index, TEXT_KEYS).and_then(|v| v.data_as_string()) {
+        let _ = write!(xml, " text=\"{}\"", escape(&text));
+    }
+    xml.push_str(">\n");
+
+    if let Some(frame) = find_value(archive, index, keys::FRAME).and_then(|v| v.as_rect()) {
+        let _ = writeln!(
+            xml,
+            "{indent}    <rect key=\"frame\" x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\"/>",
+            frame.origin.x, frame.origin.y, frame.size.width, frame.size.height
+        );
+    }
+    if let Some(mask) = find_value(archive, index, AUTORESIZING_KEYS).and_then(ValueVariant::as_i64)
+    {
+        const FLAGS: [&str; 6] = [
+            "flexibleMinX",
+            "widthSizable",
+            "flexibleMaxX",
+            "flexibleMinY",
+            "heightSizable",
+            "flexibleMaxY",
+        ];
+        let _ = write!(
+            xml,
+            "{indent}    <autoresizingMask key=\"autoresizingMask\""
+        );
+        for (bit, flag) in FLAGS.iter().enumerate() {
+            if mask & (1 << bit) != 0 {
+                let _ = write!(xml, " {flag}=\"YES\"");
+            }
+        }
+        xml.push_str("/>\n");
+    }
+
+    let subviews = subviews(archive, index);
+    if !subviews.is_empty() {
+        let _ = writeln!(xml, "{indent}    <subviews>");
+        for subview in subviews {
+            write_view(archive, subview, depth + 2, visited, xml);
+        }
+        let _ = writeln!(xml, "{indent}    </subviews>");
+    }
+    let _ = writeln!(xml, "{indent}</{element}>");
+}
+
+fn element_name(class_name: &str) -> Option<&'static str> {
+    ELEMENTS
+        .iter()
+        .find(|(name, _)| *name == class_name)
+        .map(|(_, element)| *element)
+}
+
+/// Returns an element name of a view and its custom class name if the object's class
+/// isn't a known one. Returns `None` if the object isn't a view.
+fn view_element(archive: &NIBArchive, index: usize) -> Option<(&'static str, Option<&str>)> {
+    let obj = &archive.objects()[index];
+    let class_name = archive.class_names().get(obj.class_name_id().as_usize())?;
+    if let Some(element) = element_name(class_name.name()) {
+        return Some((element, None));
+    }
+    let fallback = class_name
+        .fallback_class_ids()
+        .iter()
+        .filter_map(|i| archive.class_names().get(i.as_usize()))
+        .find_map(|c| element_name(c.name()));
+    if let Some(element) = fallback {
+        return Some((element, Some(class_name.name())));
+    }
+    // Views of unknown classes still have a frame or subviews
+    let has_view_keys = [keys::FRAME, keys::SUBVIEWS]
+        .iter()
+        .any(|keys| find_value(archive, index, keys).is_some());
+    has_view_keys.then_some(("view", Some(class_name.name())))
+}
+
+fn find_value<'a>(
+    archive: &'a NIBArchive,
+    index: usize,
+    keys: &[&str],
+) -> Option<&'a ValueVariant> {
+    keys.iter()
+        .find_map(|key| archive.object_value(index, key))
+        .map(|v| v.value())
+}
+
+/// Returns subviews of a view. Subviews are stored in an array object whose values
+/// reference the subviews themselves.
+fn subviews(archive: &NIBArchive, index: usize) -> Vec<usize> {
+    let Some(ValueVariant::ObjectRef(array)) = find_value(archive, index, keys::SUBVIEWS) else {
+        return Vec::new();
+    };
+    let Some(array) = archive.objects().get(*array as usize) else {
+        return Vec::new();
+    };
+    archive
+        .object_values(array)
+        .iter()
+        .filter_map(|v| match v.value() {
+            ValueVariant::ObjectRef(r) if (*r as usize) < archive.objects().len() => {
+                Some(*r as usize)
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+/// Escapes a string to be placed into XML text or a double-quoted attribute value.
+/// Line breaks and tabs are escaped too, so they survive attribute value normalization.
+/// Other control characters aren't allowed in XML 1.0, so they're replaced with U+FFFD.
+pub(crate) fn escape(s: &str) -> String {
+    let mut result = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => result.push_str("&amp;"),
+            '<' => result.push_str("&lt;"),
+            '>' => result.push_str("&gt;"),
+            '"' => result.push_str("&quot;"),
+            '\n' => result.push_str("&#10;"),
+            '\r' => result.push_str("&#13;"),
+            '\t' => result.push_str("&#9;"),
+            c if c < ' ' => result.push(char::REPLACEMENT_CHARACTER),
+            c => result.push(c),
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ClassName, ClassNameIndex, Object, Value};
+
+    #[test]
+    fn escape_special_characters() {
+        assert_eq!(
+            escape("<a & \"b\">\r\n\t\u{0}\u{1b}é"),
+            "&lt;a &amp; &quot;b&quot;&gt;&#13;&#10;&#9;\u{fffd}\u{fffd}é"
+        );
+    }
+
+    #[test]
+    fn label_in_view() {
+        let archive = NIBArchive::new(
+            vec![
+                Object::new(0, 0, 1),
+                Object::new(1, 1, 1),
+                Object::new(2, 2, 1),
+            ],
+            vec![
+                keys::UI_SUBVIEWS.into(),
+                keys::UI_NIB_ENCODER_EMPTY.into(),
+                keys::UI_TEXT.into(),
+            ],
+            vec![
+                Value::new(0, ValueVariant::ObjectRef(1)),
+                Value::new(1, ValueVariant::ObjectRef(2)),
+                Value::new(2, ValueVariant::Data(b"Line\r\n\tEnd".to_vec())),
+            ],
+            vec![
+                ClassName::with_fallbacks(classes::UI_VIEW.into(), Vec::new()),
+                ClassName::with_fallbacks(classes::NS_ARRAY.into(), Vec::new()),
+                ClassName::with_fallbacks("My\u{7}Label".into(), vec![ClassNameIndex(3)]),
+                ClassName::with_fallbacks(classes::UI_LABEL.into(), Vec::new()),
+            ],
+        )
+        .unwrap();
+        let xib = nib_to_xib(&archive);
+        assert!(xib.contains("targetRuntime=\"iOS.CocoaTouch\""), "{xib}");
+        assert!(
+            xib.contains(concat!(
+                "        <view id=\"obj-0\">\n",
+                "            <subviews>\n",
+                "                <label id=\"obj-2\" customClass=\"My\u{fffd}Label\" ",
+                "text=\"Line&#13;&#10;&#9;End\">\n",
+                "                </label>\n",
+                "            </subviews>\n",
+                "        </view>\n",
+            )),
+            "{xib}"
+        );
+    }
+}
@@ -0,0 +1,216 @@
+use crate::{xib::escape, Error, LocalizableString};
+use std::collections::HashMap;
+
+/// Formats strings as an XLIFF 1.2 document, the format used by Xcode for exporting
+/// localizations.
+///
+/// Every string becomes a `trans-unit` with its [key path](LocalizableString::key_path)
+/// as the identifier and the class name and object index as a note. `original` is the
+/// name of the file the strings come from, e.g. `Base.lproj/Main.storyboardc`.
+/// Targets are only written if `target_language` is given, and they're left empty.
+pub fn strings_to_xliff(
+    strings: &[LocalizableString],
+    original: &str,
+    source_language: &str,
+    target_language: Option<&str>,
+) -> String {
+    let mut result = String::from(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+         <xliff xmlns=\"urn:oasis:names:tc:xliff:document:1.2\" version=\"1.2\">\n",
+    );
+    result.push_str(&format!(
+        "  <file original=\"{}\" source-language=\"{}\"",
+        escape(original),
+        escape(source_language)
+    ));
+    if let Some(target_language) = target_language {
+        result.push_str(&format!(" target-language=\"{}\"", escape(target_language)));
+    }
+    result.push_str(" datatype=\"plaintext\">\n    <body>\n");
+    for s in strings {
+        result.push_str(&format!(
+            "      <trans-unit id=\"{}\" xml:space=\"preserve\">\n        <source>{}</source>\n",
+            escape(s.key_path()),
+            escape(s.value())
+        ));
+        if target_language.is_some() {
+            result.push_str("        <target state=\"new\"></target>\n");
+        }
+        result.push_str(&format!(
+            "        <note>Class = \"{}\"; Object = {};</note>\n      </trans-unit>\n",
+            escape(s.class_name()),
+            s.object_index()
+        ));
+    }
+    result.push_str("    </body>\n  </file>\n</xliff>\n");
+    result
+}
+
+/// Parses an XLIFF 1.2 document and returns a map of `trans-unit` identifiers to their
+/// targets, which can be applied with [NIBArchive::apply_strings()].
+///
+/// Units without a target or with an empty one are skipped. Units of all `file` elements
+/// are returned.
+///
+/// [NIBArchive::apply_strings()]: crate::NIBArchive::apply_strings()
+pub fn parse_xliff(s: &str) -> Result<HashMap<String, String>, Error> {
+    let mut result = HashMap::new();
+    let mut rest = s;
+    while let Some(start) = find_tag(rest, "trans-unit") {
+        rest = &rest[start..];
+        let tag_end = rest
+            .find('>')
+            .ok_or_else(|| xliff_error("Unclosed trans-unit tag"))?;
+        let id = attribute(&rest[..tag_end], "id")
+            .ok_or_else(|| xliff_error("A trans-unit without an id"))?;
+        let unit_end = rest
+            .find("</trans-unit>")
+            .ok_or_else(|| xliff_error("Unclosed trans-unit element"))?;
+        let unit = &rest[tag_end + 1..unit_end];
+        if let Some(target) = element_text(unit, "target")? {
+            if !target.is_empty() {
+                result.insert(id, target);
+            }
+        }
+        rest = &rest[unit_end..];
+    }
+    Ok(result)
+}
+
+fn xliff_error(message: &str) -> Error {
+    Error::FormatError(format!("Malformed XLIFF: {message}"))
+}
+
+/// Returns the position of an opening tag with a given name.
+fn find_tag(s: &str, name: &str) -> Option<usize> {
+    let pattern = format!("<{name}");
+    let mut offset = 0;
+    while let Some(pos) = s[offset..].find(&pattern) {
+        let pos = offset + pos;
+        let next = s[pos + pattern.len()..].chars().next();
+        if matches!(next, Some(c) if c.is_whitespace() || c == '>' || c == '/') {
+            return Some(pos);
+        }
+        offset = pos + pattern.len();
+    }
+    None
+}
+
+/// Returns the unescaped value of an attribute of a given tag.
+fn attribute(tag: &str, name: &str) -> Option<String> {
+    let mut offset = 0;
+    while let Some(pos) = tag[offset..].find(name) {
+        let pos = offset + pos;
+        offset = pos + name.len();
+        let preceded_by_space = tag[..pos].ends_with(|c: char| c.is_whitespace());
+        let after = tag[offset..].trim_start();
+        let Some(after) = after.strip_prefix('=') else {
+            continue;
+        };
+        if !preceded_by_space {
+            continue;
+        }
+        let after = after.trim_start();
+        let quote = after.chars().next().filter(|c| *c == '"' || *c == '\'')?;
+        let value = &after[1..];
+        let end = value.find(quote)?;
+        return Some(unescape(&value[..end]));
+    }
+    None
+}
+
+/// Returns the unescaped text of the first element with a given name.
+fn element_text(s: &str, name: &str) -> Result<Option<String>, Error> {
+    let Some(start) = find_tag(s, name) else {
+        return Ok(None);
+    };
+    let s = &s[start..];
+    let tag_end = s
+        .find('>')
+        .ok_or_else(|| xliff_error(&format!("Unclosed {name} tag")))?;
+    if s[..tag_end].ends_with('/') {
+        return Ok(Some(String::new()));
+    }
+    let closing = format!("</{name}>");
+    let end = s
+        .find(&closing)
+        .ok_or_else(|| xliff_error(&format!("Unclosed {name} element")))?;
+    let content = &s[tag_end + 1..end];
+    let text = match content
+        .strip_prefix("<![CDATA[")
+        .and_then(|c| c.strip_suffix("]]>"))
+    {
+        Some(cdata) => cdata.to_string(),
+        None => unescape(content),
+    };
+    Ok(Some(text))
+}
+
+fn unescape(s: &str) -> String {
+    let mut result = String::with_capacity(s.len());
+    let mut rest = s;
+    while let Some(pos) = rest.find('&') {
+        result.push_str(&rest[..pos]);
+        rest = &rest[pos..];
+        let Some(end) = rest.find(';') else {
+            break;
+        };
+        let entity = &rest[1..end];
+        let c = match entity {
+            "amp" => Some('&'),
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "quot" => Some('"'),
+            "apos" => Some('\''),
+            _ => entity
+                .strip_prefix("#x")
+                .and_then(|hex| u32::from_str_radix(hex, 16).ok())
+                .or_else(|| entity.strip_prefix('#').and_then(|dec| dec.parse().ok()))
+                .and_then(char::from_u32),
+        };
+        match c {
+            Some(c) => {
+                result.push(c);
+                rest = &rest[end + 1..];
+            }
+            None => {
+                result.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    result.push_str(rest);
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ClassName, NIBArchive, Object, Value, ValueVariant};
+
+    #[test]
+    fn escaped_roundtrip() {
+        let text = "Line 1\n<Line & \"2\">";
+        let archive = NIBArchive::new(
+            vec![Object::new(0, 0, 1)],
+            vec!["UIText".into()],
+            vec![Value::new(0, ValueVariant::Data(text.as_bytes().to_vec()))],
+            vec![ClassName::with_fallbacks("UILabel".into(), Vec::new())],
+        )
+        .unwrap();
+        let strings = archive.localizable_strings();
+        let xliff = strings_to_xliff(&strings, "Main.nib", "en", Some("de"));
+        let source = "Line 1&#10;&lt;Line &amp; &quot;2&quot;&gt;";
+        assert!(xliff.contains(&format!("<source>{source}</source>")));
+
+        let translated = xliff.replace(
+            "<target state=\"new\"></target>",
+            &format!("<target>{source}</target>"),
+        );
+        let targets = parse_xliff(&translated).unwrap();
+        assert_eq!(
+            targets.get(strings[0].key_path()).map(String::as_str),
+            Some(text)
+        );
+    }
+}